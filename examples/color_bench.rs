@@ -0,0 +1,58 @@
+use std::time::Instant;
+use yolo_vision::utils::color::{Backend, ColorSpace, YuvConverter};
+
+const WIDTH: usize = 1920;
+const HEIGHT: usize = 1080;
+const ROUNDS: u32 = 50;
+
+/// 简单线性同余随机数，保证每次运行的数据一致
+fn noise(len: usize) -> Vec<u8> {
+    let mut state = 42u64;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 56) as u8
+        })
+        .collect()
+}
+
+fn bench(name: &str, f: impl Fn() -> Vec<u8>) {
+    f();
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        std::hint::black_box(f());
+    }
+    println!("{:<16} avg={:>10.3?}/frame", name, start.elapsed() / ROUNDS);
+}
+
+/// run: cargo run --release --example color_bench
+fn main() {
+    let rgb = noise(WIDTH * HEIGHT * 3);
+    let i420 = noise(WIDTH * HEIGHT * 3 / 2);
+    let yuyv = noise(WIDTH * HEIGHT * 2);
+
+    for backend in [Backend::Scalar, Backend::Simd] {
+        let c = YuvConverter::new(ColorSpace::BT709_LIMITED).with_backend(backend);
+        println!("{:?} ({}x{})", backend, WIDTH, HEIGHT);
+        bench("rgb_to_i420", || {
+            c.rgb_to_i420(&rgb, WIDTH, HEIGHT).unwrap()
+        });
+        bench("rgb_to_nv12", || {
+            c.rgb_to_nv12(&rgb, WIDTH, HEIGHT).unwrap()
+        });
+        bench("rgb_to_yuyv", || {
+            c.rgb_to_yuyv(&rgb, WIDTH, HEIGHT).unwrap()
+        });
+        bench("i420_to_rgb", || {
+            c.i420_to_rgb(&i420, WIDTH, HEIGHT).unwrap()
+        });
+        bench("nv12_to_rgb", || {
+            c.nv12_to_rgb(&i420, WIDTH, HEIGHT).unwrap()
+        });
+        bench("yuyv_to_rgb", || {
+            c.yuyv_to_rgb(&yuyv, WIDTH, HEIGHT).unwrap()
+        });
+    }
+}
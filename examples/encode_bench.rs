@@ -0,0 +1,132 @@
+use cv_convert::TryFromCv;
+use image::{imageops, DynamicImage};
+use rsmedia::{Encoder, EncoderBuilder, RawFrame};
+use rsmpeg::avutil::AVFrame;
+use std::time::{Duration, Instant};
+use usls::{Bbox, Y};
+use yolo_vision::annotation::{AnnotationStyle, Annotator};
+use yolo_vision::misc::av_convert::{FrameConverter, PixelFormat, RgbConverter};
+use yolo_vision::misc::avio::decode_frames;
+
+const FRAMES: u32 = 100;
+
+/// 1080p 测试帧与一批检测框
+fn inputs() -> (Vec<DynamicImage>, Vec<Y>) {
+    let frame = image::open("assets/bus.jpg")
+        .expect("Failed to open assets/bus.jpg")
+        .resize_exact(1920, 1080, imageops::FilterType::Triangle);
+    let bboxes: Vec<Bbox> = (0..20)
+        .map(|i| {
+            Bbox::default()
+                .with_xyxy(
+                    50.0 + i as f32 * 80.0,
+                    100.0 + i as f32 * 30.0,
+                    150.0 + i as f32 * 80.0,
+                    300.0 + i as f32 * 30.0,
+                )
+                .with_id(i % 5)
+                .with_confidence(0.8)
+        })
+        .collect();
+    (vec![frame], vec![Y::default().with_bboxes(&bboxes)])
+}
+
+fn report(name: &str, total: Duration) {
+    println!(
+        "{:<10} avg={:>10.3?}/frame  {:>7.1} fps",
+        name,
+        total / FRAMES,
+        FRAMES as f64 / total.as_secs_f64()
+    );
+}
+
+/// 输出到临时文件的 1280x720 编码器，默认 libx264，可用 ENCODE_BENCH_CODEC 指定（如 h264_nvenc）
+fn encoder(name: &str) -> Encoder {
+    let codec = std::env::var("ENCODE_BENCH_CODEC").unwrap_or_else(|_| "libx264".to_string());
+    let path = std::env::temp_dir().join(format!("encode-bench-{}.mp4", name));
+    EncoderBuilder::new(&path, 1280, 720)
+        .with_codec_name(codec)
+        .build()
+        .expect("Failed to build encoder")
+}
+
+/// 改动前：拷贝标注、to_rgb8 再拷贝、RawFrame 打包 RGB24，交给编码器内部转换为 YUV 并缩放
+fn before(annotator: &Annotator, xs: &[DynamicImage], ys: &[Y]) -> Duration {
+    let mut encoder = encoder("before");
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        for frame in annotator.plot(xs, ys).unwrap() {
+            let raw = RawFrame::try_from_cv(&frame.to_rgb8()).unwrap();
+            encoder.encode_raw(&raw).unwrap();
+        }
+    }
+    encoder.finish().unwrap();
+    start.elapsed()
+}
+
+/// 改动后：原地标注，一次 sws_scale 直接得到编码器的 YUV420P
+fn after(annotator: &Annotator, xs: &[DynamicImage], ys: &[Y]) -> Duration {
+    let mut encoder = encoder("after");
+    let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        for frame in annotator.annotate(xs.to_vec(), ys, &[]) {
+            encoder
+                .encode_raw(converter.convert(frame.as_rgb8().unwrap()).unwrap())
+                .unwrap();
+        }
+    }
+    encoder.finish().unwrap();
+    start.elapsed()
+}
+
+/// 未标注的解码帧：先转 RGB（DataLoader 的做法）再转换为 YUV420P
+fn decoded_rgb(frames: &[AVFrame]) -> Duration {
+    let mut encoder = encoder("decoded-rgb");
+    let mut to_rgb = RgbConverter::default();
+    let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+    let start = Instant::now();
+    for frame in frames.iter().cycle().take(FRAMES as usize) {
+        let rgb = to_rgb.convert(frame).unwrap();
+        encoder
+            .encode_raw(converter.convert(rgb.as_rgb8().unwrap()).unwrap())
+            .unwrap();
+    }
+    encoder.finish().unwrap();
+    start.elapsed()
+}
+
+/// 未标注的解码帧：原生帧一次 sws_scale 直接得到 YUV420P（--native_decode）
+fn decoded_native(frames: &[AVFrame]) -> Duration {
+    let mut encoder = encoder("decoded-native");
+    let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+    let start = Instant::now();
+    for frame in frames.iter().cycle().take(FRAMES as usize) {
+        encoder
+            .encode_raw(converter.convert_frame(frame).unwrap())
+            .unwrap();
+    }
+    encoder.finish().unwrap();
+    start.elapsed()
+}
+
+/// run: cargo run --release --example encode_bench
+/// 与线上一致使用 NVENC: ENCODE_BENCH_CODEC=h264_nvenc cargo run --release --example encode_bench
+fn main() {
+    let annotator = Annotator::new(AnnotationStyle::default()).unwrap();
+    let (xs, ys) = inputs();
+    let (xs, ys) = (&xs, &ys);
+
+    // 预热
+    before(&annotator, xs, ys);
+    after(&annotator, xs, ys);
+
+    report("before", before(&annotator, xs, ys));
+    report("after", after(&annotator, xs, ys));
+
+    let frames = decode_frames("assets/test.mp4", FRAMES as usize).unwrap();
+    decoded_rgb(&frames);
+    decoded_native(&frames);
+    report("rgb", decoded_rgb(&frames));
+    report("native", decoded_native(&frames));
+}
@@ -0,0 +1,90 @@
+use anyhow::{anyhow, Result};
+use usls::{models::YOLO, Options};
+use yolo_vision::eval::{runner, Dataset};
+
+#[derive(argh::FromArgs, Debug)]
+/// mAP 评估
+struct Args {
+    /// model file
+    #[argh(option)]
+    model: String,
+
+    /// version
+    #[argh(option, default = "8.0")]
+    ver: f32,
+
+    /// scale
+    #[argh(option, default = "String::from(\"n\")")]
+    scale: String,
+
+    /// device
+    #[argh(option, default = "String::from(\"cpu:0\")")]
+    device: String,
+
+    /// images dir
+    #[argh(option)]
+    images: String,
+
+    /// YOLO txt labels dir
+    #[argh(option)]
+    labels: Option<String>,
+
+    /// COCO annotations json
+    #[argh(option)]
+    coco: Option<String>,
+
+    /// batch_size
+    #[argh(option, default = "1")]
+    batch_size: usize,
+
+    /// write report json to this path
+    #[argh(option)]
+    report: Option<String>,
+}
+
+/// run: cargo run --example evaluate -- --model yolov8n.onnx --images val/images --labels val/labels
+fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let args: Args = argh::from_env();
+
+    let dataset = match (&args.coco, &args.labels) {
+        (Some(coco), _) => Dataset::from_coco(coco, &args.images)?,
+        (None, Some(labels)) => {
+            Dataset::from_yolo(&args.images, labels, &usls::COCO_CLASS_NAMES_80)?
+        }
+        (None, None) => return Err(anyhow!("Either --labels or --coco is required")),
+    };
+
+    let options = Options::yolo()
+        .with_model_file(&args.model)
+        .with_model_version(args.ver.into())
+        .with_model_scale(args.scale.as_str().try_into()?)
+        .with_model_device(args.device.as_str().try_into()?)
+        .with_model_ixx(0, 0, (1, args.batch_size, args.batch_size).into())
+        .with_class_confs(&[0.001])
+        .with_class_names(
+            &dataset
+                .class_names
+                .iter()
+                .map(|x| x.as_str())
+                .collect::<Vec<_>>(),
+        );
+    let mut model = YOLO::try_from(options.commit()?)?;
+
+    let report = runner::evaluate(&mut model, &dataset, args.batch_size)?;
+    println!("{}", report.table(&dataset.class_names));
+    println!(
+        "mAP@0.5 = {:.4}, mAP@0.5:0.95 = {:.4}",
+        report.map50, report.map50_95
+    );
+
+    if let Some(path) = args.report {
+        std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+        println!("report saved to {}", path);
+    }
+
+    Ok(())
+}
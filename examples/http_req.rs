@@ -1,9 +1,10 @@
-use reqwest::header::{HeaderMap, HeaderValue};
+use futures::StreamExt;
+use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
 use std::error::Error;
 use std::time::Duration;
-use yolo_vision::utils::http_client::HttpClient;
+use yolo_vision::utils::http_client::{ApiError, Auth, HttpClient};
 
 fn main() {
     // http_get();
@@ -13,40 +14,52 @@ fn main() {
     let _ = test_http_client();
 }
 
+/// 告警平台客户端：base URL、鉴权和公共请求头只配置一次
+fn alarm_client() -> Result<HttpClient, Box<dyn Error>> {
+    let client = HttpClient::builder()
+        .with_base_url("http://172.24.82.44/umeam-ctu")
+        .with_auth(Auth::BearerEnv("UMEAM_TOKEN".to_string()))
+        .with_header("Accept", "application/json")
+        .with_connect_timeout(Duration::from_secs(3))
+        .build()?;
+    Ok(client)
+}
+
+/// 告警目标，只列出用到的字段
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct AlarmTarget {
+    id: Value,
+    name: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+/// 告警计划，只列出用到的字段
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct AlarmPlan {
+    id: Value,
+    plan_name: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
 #[tokio::main]
 #[allow(dead_code)]
 async fn http_get() -> Result<(), Box<dyn Error>> {
-    // 设置请求的 URL
-    let url = "http://172.24.82.44/umeam-ctu/alarm/plan/targetList";
-
-    // 创建请求头
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_static("Bearer YOUR_TOKEN"),
-    );
-    headers.insert("Accept", HeaderValue::from_static("application/json"));
-
     // 定义查询参数
-    let params = [("param1", "value1"), ("param2", "value2")];
-
-    // 发送 GET 请求
-    let response: Value = reqwest::Client::new()
-        .get(url)
-        .query(&params)
-        .headers(headers)
-        .send()
-        .await?
-        .json()
-        .await?;
+    let params = HashMap::from([("param1", "value1"), ("param2", "value2")]);
 
-    // 打印响应结果
-    // println!("GET 响应: {:?}", response);
+    // 发送 GET 请求，code 非成功时返回 ApiError
+    let targets: Vec<AlarmTarget> = alarm_client()?
+        .get_data("/alarm/plan/targetList", None, Some(params))
+        .await?;
 
-    let data = response.get("data").unwrap();
-    let rows = data.as_array().unwrap();
-    for row in rows {
-        println!("{:?}", row);
+    for target in targets {
+        println!("{:?}", target);
         println!("---------------------");
     }
 
@@ -56,42 +69,27 @@ async fn http_get() -> Result<(), Box<dyn Error>> {
 #[tokio::main]
 #[allow(dead_code)]
 async fn http_post() -> Result<(), Box<dyn Error>> {
-    // 设置请求的 URL
-    let url = "http://172.24.82.44/umeam-ctu/alarm/plan/page";
-
-    // 创建请求头
-    let mut headers = HeaderMap::new();
-    headers.insert(
-        "Authorization",
-        HeaderValue::from_static("Bearer YOUR_TOKEN"),
-    );
-    headers.insert("Content-Type", HeaderValue::from_static("application/json"));
-
-    // 创建请求体
-    let body = serde_json::json!({
+    // 查询条件，pageNo / pageSize 由分页器填写
+    let filter = serde_json::json!({
         "key1": "value1",
-        "key2": "value2",
-        "pageNo": 1,
-        "pageSize": 10
+        "key2": "value2"
     });
 
-    // 发送 POST 请求
-    let response: Value = reqwest::Client::new()
-        .post(url)
-        .headers(headers)
-        .json(&body)
-        .send()
-        .await?
-        .json()
-        .await?;
-
-    // 打印响应结果
-    //  println!("POST 响应: {:?}", response);
-
-    let data = response.get("data").unwrap();
-    let records = data.get("records").unwrap();
-    for record in records.as_array().unwrap() {
-        println!("{:?}", record);
+    // 逐条读取所有分页
+    let client = alarm_client()?;
+    let plans = client
+        .paginate::<AlarmPlan, _>("/alarm/plan/page", &filter)?
+        .with_page_size(10)
+        .into_stream();
+    let mut plans = std::pin::pin!(plans);
+    while let Some(plan) = plans.next().await {
+        match plan {
+            Ok(plan) => println!("{:?}", plan),
+            Err(e) => match e.downcast_ref::<ApiError>() {
+                Some(api) => println!("接口返回错误 {}: {}", api.code, api.msg),
+                None => return Err(e.into()),
+            },
+        }
         println!("---------------------");
     }
 
@@ -100,7 +98,9 @@ async fn http_post() -> Result<(), Box<dyn Error>> {
 
 #[tokio::main]
 async fn test_http_client() -> Result<(), Box<dyn Error>> {
-    let http_client = HttpClient::new();
+    let http_client = HttpClient::builder()
+        .with_user_agent("yolo-vision-example")
+        .build()?;
 
     // GET 请求示例
     let mut query_params = HashMap::new();
@@ -0,0 +1,97 @@
+use std::time::{Duration, Instant};
+use yolo_vision::postprocess::{nms, Detection, NmsConfig, NmsKind, SoftNmsMethod};
+use yolo_vision::utils::geometry::BoxF;
+
+/// 简单线性同余随机数，保证每次运行的数据一致
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self) -> f32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+/// 在 1920x1080 画面上生成 n 个聚集的候选框，模拟模型原始输出
+fn candidates(n: usize, num_classes: usize) -> Vec<Detection> {
+    let mut rng = Lcg(42);
+    let centers: Vec<(f32, f32)> = (0..(n / 20).max(1))
+        .map(|_| (rng.next() * 1920.0, rng.next() * 1080.0))
+        .collect();
+    (0..n)
+        .map(|i| {
+            let (cx, cy) = centers[i % centers.len()];
+            let w = 20.0 + rng.next() * 200.0;
+            let h = 20.0 + rng.next() * 200.0;
+            let bbox = BoxF::from_cxcywh([
+                cx + (rng.next() - 0.5) * 20.0,
+                cy + (rng.next() - 0.5) * 20.0,
+                w,
+                h,
+            ]);
+            Detection::new(bbox, rng.next(), i % num_classes)
+        })
+        .collect()
+}
+
+fn bench(name: &str, dets: &[Detection], config: &NmsConfig) {
+    const ROUNDS: u32 = 10;
+    let mut total = Duration::default();
+    let mut kept = 0;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        kept = nms(dets, config).len();
+        total += start.elapsed();
+    }
+    println!(
+        "{:<16} n={:<6} kept={:<5} avg={:?}",
+        name,
+        dets.len(),
+        kept,
+        total / ROUNDS
+    );
+}
+
+/// run: cargo run --release --example nms_bench
+fn main() {
+    for n in [1_000, 5_000, 10_000] {
+        let dets = candidates(n, 80);
+        let base = NmsConfig::default().with_top_k(None);
+        bench("hard", &dets, &base);
+        bench("hard-agnostic", &dets, &base.with_class_agnostic(true));
+        bench("diou", &dets, &base.with_kind(NmsKind::DIoU));
+        bench(
+            "soft-gaussian",
+            &dets,
+            &base
+                .with_kind(NmsKind::Soft(SoftNmsMethod::Gaussian { sigma: 0.5 }))
+                .with_score_threshold(0.05),
+        );
+        bench(
+            "matrix",
+            &dets,
+            &base
+                .with_kind(NmsKind::Matrix {
+                    gaussian: true,
+                    sigma: 2.0,
+                })
+                .with_score_threshold(0.05),
+        );
+        bench(
+            "matrix-agnostic",
+            &dets,
+            &base
+                .with_kind(NmsKind::Matrix {
+                    gaussian: true,
+                    sigma: 2.0,
+                })
+                .with_class_agnostic(true)
+                .with_pre_top_k(Some(2_000))
+                .with_score_threshold(0.05),
+        );
+        println!();
+    }
+}
@@ -1,4 +1,5 @@
 use image::{Rgb, RgbImage};
+use yolo_vision::utils::color::{ColorSpace, YuvConverter};
 
 fn main() {
     // 创建一个 4x4 的 RGB 图像
@@ -21,4 +22,22 @@ fn main() {
 
     // 将图像保存为 PNG 文件
     img.save("/tmp/output.png").expect("Failed to save image");
+
+    // RGB -> I420 -> RGB，4:2:0 下采样后每个 2x2 块共享色度
+    let converter = YuvConverter::new(ColorSpace::BT709_LIMITED);
+    let (w, h) = (img_x as usize, img_y as usize);
+    let i420 = converter
+        .rgb_to_i420(img.as_raw(), w, h)
+        .expect("Failed to convert to I420");
+    println!("Y: {:?}", &i420[..w * h]);
+    println!("U: {:?}", &i420[w * h..w * h * 5 / 4]);
+    println!("V: {:?}", &i420[w * h * 5 / 4..]);
+
+    let rgb = converter
+        .i420_to_rgb(&i420, w, h)
+        .expect("Failed to convert to RGB");
+    RgbImage::from_raw(img_x, img_y, rgb)
+        .expect("Invalid RGB buffer")
+        .save("/tmp/output_i420.png")
+        .expect("Failed to save image");
 }
@@ -0,0 +1,366 @@
+use ab_glyph::PxScale;
+use anyhow::{anyhow, Context, Result};
+use image::{imageops, DynamicImage, GrayImage, Rgb, RgbImage};
+use imageproc::drawing::{
+    draw_filled_circle_mut, draw_filled_rect_mut, draw_hollow_rect_mut, draw_line_segment_mut,
+};
+use imageproc::rect::Rect;
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use usls::{Bbox, Y};
+
+use super::font::FontChain;
+use super::i18n::DisplayNames;
+use super::label::{LabelFields, LabelTemplate};
+use super::style::{AnnotationStyle, PALETTE};
+
+/// 按 [`AnnotationStyle`] 绘制检测框、标签、分割掩码、关键点和骨架
+///
+/// 旋转框（OBB）、分类概率以及没有掩码时的分割多边形仍交给 usls 的 Annotator 绘制。
+pub struct Annotator {
+    style: AnnotationStyle,
+    template: LabelTemplate,
+    fonts: FontChain,
+    names: DisplayNames,
+    fallback: usls::Annotator,
+    saveout: Option<String>,
+}
+
+impl Annotator {
+    pub fn new(style: AnnotationStyle) -> Result<Self> {
+        let template = LabelTemplate::parse(&style.label_template)?;
+        let fonts = FontChain::load(&style.font, &style.fallback_fonts)?;
+        let names = match &style.translations {
+            Some(path) => DisplayNames::from_file(path, &style.locale)?,
+            None => DisplayNames::default(),
+        };
+
+        let missing: Vec<char> = names.names().flat_map(|n| fonts.missing(n)).collect();
+        if !missing.is_empty() {
+            tracing::warn!(
+                "No font covers {:?} in class names, add a CJK font to `fallback_fonts`",
+                missing
+            );
+        }
+
+        // 骨架和掩码开关与本结构保持一致
+        let skeletons: Vec<(usize, usize)> = if style.skeletons {
+            style.skeleton().iter().map(|&[a, b]| (a, b)).collect()
+        } else {
+            Vec::new()
+        };
+        let fallback = usls::Annotator::default()
+            .with_skeletons(&skeletons)
+            .without_masks(!style.masks)
+            .with_bboxes_thickness(style.bbox_thickness as _);
+
+        Ok(Self {
+            style,
+            template,
+            fonts,
+            names,
+            fallback,
+            saveout: None,
+        })
+    }
+
+    /// [`Annotator::save`] 的输出目录 `runs/<saveout>`
+    pub fn with_saveout(mut self, x: &str) -> Self {
+        self.saveout = Some(x.to_string());
+        self
+    }
+
+    pub fn style(&self) -> &AnnotationStyle {
+        &self.style
+    }
+
+    /// 将标注后的帧保存为 PNG，返回保存的路径
+    pub fn save(&self, xs: &[DynamicImage]) -> Result<Vec<PathBuf>> {
+        let saveout = self
+            .saveout
+            .as_ref()
+            .ok_or_else(|| anyhow!("No saveout set, call `with_saveout` first"))?;
+        let dir = Path::new("runs").join(saveout);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create saveout dir: {:?}", dir))?;
+        let stamp = chrono::Local::now().format("%Y%m%d_%H%M%S%.3f");
+        xs.iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let path = dir.join(format!("{}_{}.png", stamp, i));
+                x.save(&path)
+                    .with_context(|| format!("Failed to save annotated frame: {:?}", path))?;
+                Ok(path)
+            })
+            .collect()
+    }
+
+    /// 标签文本，类别名优先使用翻译后的显示名称
+    pub fn label(&self, bbox: &Bbox, track_id: Option<u64>) -> String {
+        self.template.render(&LabelFields {
+            name: self
+                .names
+                .get(bbox.id())
+                .or(bbox.name().map(|n| n.as_str())),
+            class_id: bbox.id(),
+            confidence: bbox.confidence(),
+            track_id,
+        })
+    }
+
+    pub fn plot(&self, xs: &[DynamicImage], ys: &[Y]) -> Result<Vec<DynamicImage>> {
+        self.plot_with_tracks(xs, ys, &[])
+    }
+
+    /// 绘制一批帧，`tracks[i][j]` 为第 i 帧第 j 个检测框的跟踪 ID
+    pub fn plot_with_tracks(
+        &self,
+        xs: &[DynamicImage],
+        ys: &[Y],
+        tracks: &[Vec<Option<u64>>],
+    ) -> Result<Vec<DynamicImage>> {
+        Ok(xs
+            .par_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut img = x.to_rgb8();
+                if let Some(y) = ys.get(i) {
+                    self.draw(&mut img, y, tracks.get(i).map(|t| t.as_slice()));
+                }
+                DynamicImage::ImageRgb8(img)
+            })
+            .collect())
+    }
+
+    /// 与 [`Annotator::plot_with_tracks`] 相同，但直接绘制在传入的帧缓冲区上，RGB 帧不再拷贝
+    pub fn annotate(
+        &self,
+        xs: Vec<DynamicImage>,
+        ys: &[Y],
+        tracks: &[Vec<Option<u64>>],
+    ) -> Vec<DynamicImage> {
+        xs.into_par_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut img = match x {
+                    DynamicImage::ImageRgb8(img) => img,
+                    x => x.to_rgb8(),
+                };
+                if let Some(y) = ys.get(i) {
+                    self.draw(&mut img, y, tracks.get(i).map(|t| t.as_slice()));
+                }
+                DynamicImage::ImageRgb8(img)
+            })
+            .collect()
+    }
+
+    /// 在单帧上绘制
+    pub fn draw(&self, img: &mut RgbImage, y: &Y, tracks: Option<&[Option<u64>]>) {
+        if let Some(extras) = self.extras(y) {
+            self.draw_extras(img, &extras);
+        }
+
+        if self.style.masks || self.style.contours {
+            for m in y.masks().unwrap_or_default() {
+                let color = self.style.color(m.id(), m.name().map(|n| n.as_str()));
+                self.draw_mask(img, m.mask(), color);
+            }
+        }
+
+        let bboxes = y.bboxes().unwrap_or_default();
+        for bbox in bboxes {
+            self.draw_bbox(img, bbox);
+        }
+
+        if let Some(keypoints) = y.keypoints() {
+            for (i, kpts) in keypoints.iter().enumerate() {
+                let color = bboxes
+                    .get(i)
+                    .map(|b| self.style.color(b.id(), b.name().map(|n| n.as_str())))
+                    .unwrap_or(PALETTE[0]);
+                let points: Vec<Option<(f32, f32)>> = kpts
+                    .iter()
+                    .map(|k| {
+                        (k.confidence() >= self.style.keypoint_threshold).then(|| (k.x(), k.y()))
+                    })
+                    .collect();
+                self.draw_keypoints(img, &points, color);
+            }
+        }
+
+        // 标签最后绘制，避免被其它目标的框遮挡
+        if self.style.labels {
+            for (i, bbox) in bboxes.iter().enumerate() {
+                let track_id = tracks.and_then(|t| t.get(i).copied().flatten());
+                let color = self.style.color(bbox.id(), bbox.name().map(|n| n.as_str()));
+                self.draw_label(img, bbox, &self.label(bbox, track_id), color);
+            }
+        }
+    }
+
+    /// 本结构不直接绘制的结果：旋转框、分类概率，以及没有掩码可画轮廓时的分割多边形
+    fn extras(&self, y: &Y) -> Option<Y> {
+        // 分类结果只有概率，整体交给 usls 绘制
+        if y.probs().is_some() {
+            return Some(y.clone());
+        }
+        let has_masks = y.masks().is_some_and(|m| !m.is_empty());
+        let mbrs = y.mbrs().filter(|m| !m.is_empty());
+        let polygons = y.polygons().filter(|p| !p.is_empty() && !has_masks);
+        if mbrs.is_none() && polygons.is_none() {
+            return None;
+        }
+
+        let mut extras = Y::default();
+        if let Some(mbrs) = mbrs {
+            extras = extras.with_mbrs(mbrs);
+        }
+        if let Some(polygons) = polygons {
+            extras = extras.with_polygons(polygons);
+        }
+        Some(extras)
+    }
+
+    fn draw_extras(&self, img: &mut RgbImage, extras: &Y) {
+        let x = DynamicImage::ImageRgb8(std::mem::take(img));
+        match self.fallback.plot(
+            std::slice::from_ref(&x),
+            std::slice::from_ref(extras),
+            false,
+        ) {
+            Ok(mut out) if !out.is_empty() => *img = out.swap_remove(0).into_rgb8(),
+            Ok(_) => *img = x.into_rgb8(),
+            Err(e) => {
+                tracing::warn!("Failed to draw rotated boxes / polygons / probs: {:?}", e);
+                *img = x.into_rgb8();
+            }
+        }
+    }
+
+    fn draw_bbox(&self, img: &mut RgbImage, bbox: &Bbox) {
+        let color = Rgb(self.style.color(bbox.id(), bbox.name().map(|n| n.as_str())));
+        let (x, y) = (bbox.xmin().round() as i32, bbox.ymin().round() as i32);
+        let (w, h) = (bbox.width().round() as i32, bbox.height().round() as i32);
+        for t in 0..self.style.bbox_thickness as i32 {
+            let (w, h) = (w - 2 * t, h - 2 * t);
+            if w <= 0 || h <= 0 {
+                break;
+            }
+            draw_hollow_rect_mut(
+                img,
+                Rect::at(x + t, y + t).of_size(w as u32, h as u32),
+                color,
+            );
+        }
+    }
+
+    fn draw_label(&self, img: &mut RgbImage, bbox: &Bbox, text: &str, color: [u8; 3]) {
+        if text.is_empty() {
+            return;
+        }
+        let scale = PxScale::from(self.style.font_size);
+        let (tw, th) = self.fonts.text_size(scale, text);
+        let pad = 2;
+        let (bw, bh) = (tw + 2 * pad, th + 2 * pad);
+
+        // 框上方放不下时画在框内
+        let x = (bbox.xmin().round() as i32).clamp(0, (img.width() as i32 - bw as i32).max(0));
+        let top = bbox.ymin().round() as i32;
+        let y = if top >= bh as i32 {
+            top - bh as i32
+        } else {
+            top.max(0)
+        };
+
+        draw_filled_rect_mut(img, Rect::at(x, y).of_size(bw, bh), Rgb(color));
+        self.fonts.draw_text(
+            img,
+            Rgb(self.style.label_text_color),
+            x + pad as i32,
+            y + pad as i32,
+            scale,
+            text,
+        );
+    }
+
+    fn draw_mask(&self, img: &mut RgbImage, mask: &GrayImage, color: [u8; 3]) {
+        let resized;
+        let mask = if mask.dimensions() == img.dimensions() {
+            mask
+        } else {
+            resized = imageops::resize(
+                mask,
+                img.width(),
+                img.height(),
+                imageops::FilterType::Nearest,
+            );
+            &resized
+        };
+
+        let (w, h) = img.dimensions();
+        let alpha = self.style.mask_alpha.clamp(0.0, 1.0);
+        let inside = |x: i64, y: i64| {
+            x >= 0
+                && y >= 0
+                && x < w as i64
+                && y < h as i64
+                && mask.get_pixel(x as u32, y as u32)[0] > 0
+        };
+        for (x, y, p) in img.enumerate_pixels_mut() {
+            if mask.get_pixel(x, y)[0] == 0 {
+                continue;
+            }
+            let (xi, yi) = (x as i64, y as i64);
+            let edge = !(inside(xi - 1, yi)
+                && inside(xi + 1, yi)
+                && inside(xi, yi - 1)
+                && inside(xi, yi + 1));
+            if self.style.contours && edge {
+                p.0 = color;
+            } else if self.style.masks {
+                for (v, &c) in p.0.iter_mut().zip(color.iter()) {
+                    *v = (*v as f32 * (1.0 - alpha) + c as f32 * alpha).round() as u8;
+                }
+            }
+        }
+    }
+
+    /// 按 `skeleton_thickness` 绘制线段：沿法线方向平移若干条单像素线
+    fn draw_thick_line(&self, img: &mut RgbImage, p: (f32, f32), q: (f32, f32), color: Rgb<u8>) {
+        let (dx, dy) = (q.0 - p.0, q.1 - p.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        let (nx, ny) = if len > 0.0 {
+            (-dy / len, dx / len)
+        } else {
+            (0.0, 0.0)
+        };
+        let n = self.style.skeleton_thickness.max(1);
+        for i in 0..n {
+            let off = i as f32 - ((n - 1) / 2) as f32;
+            let (ox, oy) = (nx * off, ny * off);
+            draw_line_segment_mut(img, (p.0 + ox, p.1 + oy), (q.0 + ox, q.1 + oy), color);
+        }
+    }
+
+    fn draw_keypoints(&self, img: &mut RgbImage, points: &[Option<(f32, f32)>], color: [u8; 3]) {
+        if self.style.skeletons {
+            for &[a, b] in self.style.skeleton() {
+                if let (Some(Some(p)), Some(Some(q))) = (points.get(a), points.get(b)) {
+                    self.draw_thick_line(img, *p, *q, Rgb(color));
+                }
+            }
+        }
+        if self.style.keypoints {
+            for (i, p) in points.iter().enumerate() {
+                if let Some((x, y)) = p {
+                    draw_filled_circle_mut(
+                        img,
+                        (x.round() as i32, y.round() as i32),
+                        self.style.keypoint_radius,
+                        Rgb(PALETTE[i % PALETTE.len()]),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use ab_glyph::{point, Font, FontVec, GlyphId, PxScale, ScaleFont};
+use anyhow::{Context, Result};
+use image::{Rgb, RgbImage};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// 字体回退链：逐字符选用第一个包含该字形的字体，使同一标签可以混排拉丁字母和中日韩文字
+pub struct FontChain {
+    fonts: Vec<FontVec>,
+}
+
+impl FontChain {
+    /// 加载主字体和回退字体
+    ///
+    /// 主字体必须存在；回退字体不存在时跳过，便于在配置里列出多个平台的系统字体路径。
+    pub fn load(primary: &str, fallbacks: &[String]) -> Result<Self> {
+        let mut fonts = vec![load_font(primary)?];
+        for path in fallbacks {
+            if !Path::new(path).exists() {
+                tracing::debug!("Fallback font not found, skipped: {:?}", path);
+                continue;
+            }
+            fonts.push(load_font(path)?);
+        }
+        Ok(Self { fonts })
+    }
+
+    pub fn from_fonts(fonts: Vec<FontVec>) -> Self {
+        assert!(!fonts.is_empty(), "FontChain needs at least one font");
+        Self { fonts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.fonts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fonts.is_empty()
+    }
+
+    /// 包含该字符字形的第一个字体的下标
+    pub fn font_for(&self, c: char) -> Option<usize> {
+        self.fonts.iter().position(|f| f.glyph_id(c) != GlyphId(0))
+    }
+
+    /// 所有字体都不包含的字符（空白字符除外）
+    pub fn missing(&self, text: &str) -> Vec<char> {
+        let mut seen = HashSet::new();
+        text.chars()
+            .filter(|c| !c.is_whitespace() && self.font_for(*c).is_none())
+            .filter(|c| seen.insert(*c))
+            .collect()
+    }
+
+    /// 文本宽高（像素），高度取主字体的行高
+    pub fn text_size(&self, scale: PxScale, text: &str) -> (u32, u32) {
+        let width = self.layout(scale, text, |_, _, _| {});
+        let primary = self.fonts[0].as_scaled(scale);
+        (
+            width.ceil() as u32,
+            (primary.ascent() - primary.descent()).ceil() as u32,
+        )
+    }
+
+    /// 在 (x, y) 处绘制文本，(x, y) 为文本框左上角
+    pub fn draw_text(
+        &self,
+        img: &mut RgbImage,
+        color: Rgb<u8>,
+        x: i32,
+        y: i32,
+        scale: PxScale,
+        text: &str,
+    ) {
+        let (w, h) = (img.width() as i32, img.height() as i32);
+        self.layout(scale, text, |font, glyph_id, position| {
+            let glyph = glyph_id.with_scale_and_position(scale, position);
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                return;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = x + bounds.min.x as i32 + gx as i32;
+                let py = y + bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= w || py >= h {
+                    return;
+                }
+                let a = coverage.clamp(0.0, 1.0);
+                let p = img.get_pixel_mut(px as u32, py as u32);
+                for (v, &c) in p.0.iter_mut().zip(color.0.iter()) {
+                    *v = (*v as f32 * (1.0 - a) + c as f32 * a).round() as u8;
+                }
+            });
+        });
+    }
+
+    /// 排版：所有字形共用主字体的基线，字距只在同一字体的相邻字形间生效，返回总宽度
+    fn layout(
+        &self,
+        scale: PxScale,
+        text: &str,
+        mut f: impl FnMut(&FontVec, GlyphId, ab_glyph::Point),
+    ) -> f32 {
+        let baseline = self.fonts[0].as_scaled(scale).ascent();
+        let mut caret = 0f32;
+        let mut last: Option<(usize, GlyphId)> = None;
+        for c in text.chars() {
+            // 没有任何字体覆盖时用主字体的缺字符号
+            let index = self.font_for(c).unwrap_or(0);
+            let font = self.fonts[index].as_scaled(scale);
+            let glyph_id = font.glyph_id(c);
+            if let Some((last_index, last_id)) = last {
+                if last_index == index {
+                    caret += font.kern(last_id, glyph_id);
+                }
+            }
+            f(&self.fonts[index], glyph_id, point(caret, baseline));
+            caret += font.h_advance(glyph_id);
+            last = Some((index, glyph_id));
+        }
+        caret
+    }
+}
+
+fn load_font(path: &str) -> Result<FontVec> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read font file: {:?}", path))?;
+    // .ttc 字体集合取第一个字体
+    FontVec::try_from_vec_and_index(bytes, 0)
+        .with_context(|| format!("Invalid font file: {:?}", path))
+}
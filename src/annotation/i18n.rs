@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// 类别显示名称，从翻译文件中按语言加载
+///
+/// 翻译文件为 JSON，按语言区分，键为模型类别 ID：
+///
+/// ```json
+/// { "zh-CN": { "0": "行人", "2": "汽车" }, "en": { "0": "pedestrian" } }
+/// ```
+///
+/// 只影响标注时显示的名称，不影响按 `class_names` 过滤和配置中按类别名匹配的逻辑。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DisplayNames {
+    names: HashMap<isize, String>,
+}
+
+impl DisplayNames {
+    pub fn new(names: HashMap<isize, String>) -> Self {
+        Self { names }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>, locale: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read translation file: {:?}", path))?;
+        Self::from_json(&content, locale)
+            .with_context(|| format!("Invalid translation file: {:?}", path))
+    }
+
+    pub fn from_json(content: &str, locale: &str) -> Result<Self> {
+        let mut locales: HashMap<String, HashMap<String, String>> = serde_json::from_str(content)?;
+        let table = locales.remove(locale).ok_or_else(|| {
+            anyhow!(
+                "Locale {:?} not found, available: {:?}",
+                locale,
+                locales.keys().collect::<Vec<_>>()
+            )
+        })?;
+        let names = table
+            .into_iter()
+            .map(|(id, name)| {
+                id.parse::<isize>()
+                    .map(|id| (id, name))
+                    .map_err(|_| anyhow!("Class id must be an integer, got {:?}", id))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { names })
+    }
+
+    pub fn get(&self, class_id: isize) -> Option<&str> {
+        self.names.get(&class_id).map(|n| n.as_str())
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.values().map(|n| n.as_str())
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, bail, Result};
+
+/// 标签模板中的片段
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Text(String),
+    Name,
+    ClassId,
+    /// 置信度，保留的小数位数
+    Conf(usize),
+    TrackId,
+}
+
+/// 渲染标签所需的字段
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LabelFields<'a> {
+    pub name: Option<&'a str>,
+    pub class_id: isize,
+    pub confidence: f32,
+    pub track_id: Option<u64>,
+}
+
+/// 标签模板，例如 `{name} {conf:.2} #{track_id}`
+///
+/// 支持的占位符：`{name}`、`{id}`、`{conf}`（可带精度 `{conf:.N}`，默认 2 位）、`{track_id}`。
+/// 模板按空格切分为若干词，某个词中的占位符没有取值时（如未跟踪的目标没有 `track_id`），
+/// 整个词被省略，因此 `{name} {conf:.2} #{track_id}` 对未跟踪目标渲染为 `person 0.87`。
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelTemplate {
+    words: Vec<Vec<Segment>>,
+}
+
+impl Default for LabelTemplate {
+    fn default() -> Self {
+        Self::parse("{name} {conf:.2}").unwrap()
+    }
+}
+
+impl LabelTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let words = template
+            .split(' ')
+            .filter(|w| !w.is_empty())
+            .map(parse_word)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { words })
+    }
+
+    pub fn render(&self, fields: &LabelFields) -> String {
+        let mut words = Vec::with_capacity(self.words.len());
+        'words: for word in &self.words {
+            let mut s = String::new();
+            for segment in word {
+                match segment {
+                    Segment::Text(t) => s.push_str(t),
+                    Segment::Name => match fields.name {
+                        Some(name) => s.push_str(name),
+                        None => continue 'words,
+                    },
+                    Segment::ClassId => s.push_str(&fields.class_id.to_string()),
+                    Segment::Conf(p) => s.push_str(&format!("{:.*}", p, fields.confidence)),
+                    Segment::TrackId => match fields.track_id {
+                        Some(id) => s.push_str(&id.to_string()),
+                        None => continue 'words,
+                    },
+                }
+            }
+            words.push(s);
+        }
+        words.join(" ")
+    }
+}
+
+impl TryFrom<&str> for LabelTemplate {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        Self::parse(s)
+    }
+}
+
+fn parse_word(word: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = word;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            segments.push(Segment::Text(rest[..start].to_string()));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| anyhow!("Unclosed placeholder in label template: {:?}", word))?;
+        segments.push(parse_placeholder(&rest[start + 1..start + end])?);
+        rest = &rest[start + end + 1..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest.to_string()));
+    }
+    Ok(segments)
+}
+
+fn parse_placeholder(s: &str) -> Result<Segment> {
+    let (key, spec) = match s.split_once(':') {
+        Some((key, spec)) => (key, Some(spec)),
+        None => (s, None),
+    };
+    match (key, spec) {
+        ("name", None) => Ok(Segment::Name),
+        ("id", None) => Ok(Segment::ClassId),
+        ("track_id", None) => Ok(Segment::TrackId),
+        ("conf", None) => Ok(Segment::Conf(2)),
+        ("conf", Some(spec)) => spec
+            .strip_prefix('.')
+            .and_then(|p| p.parse().ok())
+            .map(Segment::Conf)
+            .ok_or_else(|| anyhow!("Invalid precision in label template: {{{}}}", s)),
+        _ => bail!("Unknown placeholder in label template: {{{}}}", s),
+    }
+}
@@ -0,0 +1,13 @@
+pub mod annotator;
+pub mod font;
+pub mod i18n;
+pub mod label;
+pub mod overlay;
+pub mod style;
+
+pub use annotator::Annotator;
+pub use font::FontChain;
+pub use i18n::DisplayNames;
+pub use label::{LabelFields, LabelTemplate};
+pub use overlay::{FpsMeter, Overlay, OverlayConfig, OverlayPosition, OverlayStats, ZoneConfig};
+pub use style::{AnnotationStyle, COCO_SKELETON, DEFAULT_FALLBACK_FONTS, PALETTE};
@@ -0,0 +1,350 @@
+use ab_glyph::PxScale;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use image::{DynamicImage, Rgb, RgbImage};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use usls::Y;
+
+use super::font::FontChain;
+use super::i18n::DisplayNames;
+use super::style::AnnotationStyle;
+use crate::utils::geometry::{point_in_polygon, Anchor, BoxF};
+
+/// 叠加信息在画面中的位置
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlayPosition {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// 计数区域，多边形使用归一化坐标
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ZoneConfig {
+    pub name: String,
+    pub polygon: Vec<[f32; 2]>,
+    /// 判断目标是否在区域内使用的锚点
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+/// 状态叠加层配置，每一项都可以单独开关
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct OverlayConfig {
+    pub position: OverlayPosition,
+    /// 距画面边缘的距离（像素）
+    pub margin: u32,
+    /// 字体，默认使用标注样式的字体和回退字体
+    pub font: Option<String>,
+    pub font_size: f32,
+    pub text_color: [u8; 3],
+    pub background: [u8; 3],
+    /// 背景不透明度，0..=1
+    pub opacity: f32,
+    /// 显示时间戳（本地时间，有 PTS 时一并显示）
+    pub timestamp: bool,
+    /// 时间戳格式（chrono strftime）
+    pub timestamp_format: String,
+    pub stream_name: bool,
+    pub fps: bool,
+    pub latency: bool,
+    /// 按类别统计当前帧目标数
+    pub class_counts: bool,
+    /// 按区域统计当前帧目标数
+    pub zone_counts: bool,
+    pub zones: Vec<ZoneConfig>,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            position: OverlayPosition::TopLeft,
+            margin: 10,
+            font: None,
+            font_size: 18.0,
+            text_color: [255, 255, 255],
+            background: [0, 0, 0],
+            opacity: 0.5,
+            timestamp: true,
+            timestamp_format: "%Y-%m-%d %H:%M:%S%.3f".to_string(),
+            stream_name: true,
+            fps: true,
+            latency: true,
+            class_counts: true,
+            zone_counts: true,
+            zones: Vec::new(),
+        }
+    }
+}
+
+/// 一批帧对应的运行状态
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverlayStats {
+    /// 推理帧率
+    pub fps: f32,
+    /// 从取到帧到绘制叠加层的耗时
+    pub latency: Duration,
+    /// 帧的显示时间戳
+    pub pts: Option<Duration>,
+}
+
+/// 滑动窗口帧率统计
+#[derive(Debug, Clone)]
+pub struct FpsMeter {
+    window: Duration,
+    frames: VecDeque<(Instant, usize)>,
+}
+
+impl Default for FpsMeter {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2))
+    }
+}
+
+impl FpsMeter {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            frames: VecDeque::new(),
+        }
+    }
+
+    /// 记录完成处理的帧数
+    pub fn record(&mut self, n: usize) {
+        self.record_at(Instant::now(), n);
+    }
+
+    pub fn record_at(&mut self, now: Instant, n: usize) {
+        self.frames.push_back((now, n));
+        while let Some(&(t, _)) = self.frames.front() {
+            if now.duration_since(t) <= self.window {
+                break;
+            }
+            self.frames.pop_front();
+        }
+    }
+
+    /// 窗口内的平均帧率，第一批帧不计入（只作为计时起点）
+    pub fn fps(&self) -> f32 {
+        let (Some(&(first, _)), Some(&(last, _))) = (self.frames.front(), self.frames.back())
+        else {
+            return 0.0;
+        };
+        let elapsed = last.duration_since(first).as_secs_f32();
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        let frames: usize = self.frames.iter().skip(1).map(|&(_, n)| n).sum();
+        frames as f32 / elapsed
+    }
+}
+
+/// 在标注后的画面上绘制时间戳、流名称、帧率、延迟和计数等状态信息
+pub struct Overlay {
+    config: OverlayConfig,
+    stream: String,
+    fonts: FontChain,
+    names: DisplayNames,
+}
+
+impl Overlay {
+    pub fn new(config: OverlayConfig, stream: &str, style: &AnnotationStyle) -> Result<Self> {
+        let font = config.font.as_deref().unwrap_or(&style.font);
+        let fonts = FontChain::load(font, &style.fallback_fonts)?;
+        let names = match &style.translations {
+            Some(path) => DisplayNames::from_file(path, &style.locale)?,
+            None => DisplayNames::default(),
+        };
+        Ok(Self {
+            config,
+            stream: stream.to_string(),
+            fonts,
+            names,
+        })
+    }
+
+    pub fn config(&self) -> &OverlayConfig {
+        &self.config
+    }
+
+    /// 各类别目标数，按类别名排序
+    pub fn class_counts(&self, y: &Y) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for b in y.bboxes().unwrap_or_default() {
+            let name = self
+                .names
+                .get(b.id())
+                .map(|n| n.to_string())
+                .or(b.name().cloned())
+                .unwrap_or_else(|| b.id().to_string());
+            *counts.entry(name).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// 各区域内的目标数，顺序与配置一致
+    pub fn zone_counts(&self, y: &Y, width: u32, height: u32) -> Vec<(String, usize)> {
+        let bboxes = y.bboxes().unwrap_or_default();
+        self.config
+            .zones
+            .iter()
+            .map(|zone| {
+                let polygon: Vec<(f32, f32)> = zone
+                    .polygon
+                    .iter()
+                    .map(|[x, y]| (x * width as f32, y * height as f32))
+                    .collect();
+                let count = bboxes
+                    .iter()
+                    .filter(|b| {
+                        let b = BoxF::new(b.xmin(), b.ymin(), b.xmax(), b.ymax());
+                        point_in_polygon(b.anchor(zone.anchor), &polygon)
+                    })
+                    .count();
+                (zone.name.clone(), count)
+            })
+            .collect()
+    }
+
+    /// 叠加层的文本行
+    pub fn lines(
+        &self,
+        y: &Y,
+        width: u32,
+        height: u32,
+        stats: &OverlayStats,
+        now: DateTime<Local>,
+    ) -> Vec<String> {
+        let c = &self.config;
+        let mut lines = Vec::new();
+        if c.timestamp {
+            let mut line = now.format(&c.timestamp_format).to_string();
+            if let Some(pts) = stats.pts {
+                line.push_str(&format!("  PTS {}", format_duration(pts)));
+            }
+            lines.push(line);
+        }
+        if c.stream_name {
+            lines.push(self.stream.clone());
+        }
+        match (c.fps, c.latency) {
+            (true, true) => lines.push(format!(
+                "FPS {:.1}  Latency {} ms",
+                stats.fps,
+                stats.latency.as_millis()
+            )),
+            (true, false) => lines.push(format!("FPS {:.1}", stats.fps)),
+            (false, true) => lines.push(format!("Latency {} ms", stats.latency.as_millis())),
+            (false, false) => {}
+        }
+        if c.class_counts {
+            let counts = self.class_counts(y);
+            if !counts.is_empty() {
+                lines.push(
+                    counts
+                        .iter()
+                        .map(|(name, n)| format!("{}: {}", name, n))
+                        .collect::<Vec<_>>()
+                        .join("  "),
+                );
+            }
+        }
+        if c.zone_counts {
+            for (name, n) in self.zone_counts(y, width, height) {
+                lines.push(format!("{}: {}", name, n));
+            }
+        }
+        lines
+    }
+
+    /// 为一批帧绘制叠加层，`stats` 与帧一一对应
+    pub fn apply(
+        &self,
+        xs: Vec<DynamicImage>,
+        ys: &[Y],
+        stats: &[OverlayStats],
+    ) -> Vec<DynamicImage> {
+        let now = Local::now();
+        xs.into_par_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut img = match x {
+                    DynamicImage::ImageRgb8(img) => img,
+                    x => x.to_rgb8(),
+                };
+                let stats = stats.get(i).copied().unwrap_or_default();
+                let lines = match ys.get(i) {
+                    Some(y) => self.lines(y, img.width(), img.height(), &stats, now),
+                    None => self.lines(&Y::default(), img.width(), img.height(), &stats, now),
+                };
+                self.draw(&mut img, &lines);
+                DynamicImage::ImageRgb8(img)
+            })
+            .collect()
+    }
+
+    /// 在画面角落绘制半透明背景和文本
+    pub fn draw(&self, img: &mut RgbImage, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+        let c = &self.config;
+        let scale = PxScale::from(c.font_size);
+        let sizes: Vec<(u32, u32)> = lines
+            .iter()
+            .map(|l| self.fonts.text_size(scale, l))
+            .collect();
+        let pad = (c.font_size / 3.0).ceil() as u32;
+        let line_height = sizes.iter().map(|s| s.1).max().unwrap_or(0);
+        let bw = sizes.iter().map(|s| s.0).max().unwrap_or(0) + 2 * pad;
+        let bh = line_height * lines.len() as u32 + 2 * pad;
+
+        let (w, h) = img.dimensions();
+        let x = match c.position {
+            OverlayPosition::TopLeft | OverlayPosition::BottomLeft => c.margin,
+            _ => w.saturating_sub(bw + c.margin),
+        };
+        let y = match c.position {
+            OverlayPosition::TopLeft | OverlayPosition::TopRight => c.margin,
+            _ => h.saturating_sub(bh + c.margin),
+        };
+
+        let alpha = c.opacity.clamp(0.0, 1.0);
+        for py in y..(y + bh).min(h) {
+            for px in x..(x + bw).min(w) {
+                let p = img.get_pixel_mut(px, py);
+                for (v, &b) in p.0.iter_mut().zip(c.background.iter()) {
+                    *v = (*v as f32 * (1.0 - alpha) + b as f32 * alpha).round() as u8;
+                }
+            }
+        }
+        for (i, line) in lines.iter().enumerate() {
+            self.fonts.draw_text(
+                img,
+                Rgb(c.text_color),
+                (x + pad) as i32,
+                (y + pad + line_height * i as u32) as i32,
+                scale,
+                line,
+            );
+        }
+    }
+}
+
+fn format_duration(d: Duration) -> String {
+    let ms = d.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
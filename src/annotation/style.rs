@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// COCO 17 点人体姿态的骨架连接
+pub const COCO_SKELETON: [[usize; 2]; 16] = [
+    [0, 1],
+    [0, 2],
+    [1, 3],
+    [2, 4],
+    [5, 6],
+    [5, 11],
+    [6, 12],
+    [11, 12],
+    [5, 7],
+    [6, 8],
+    [7, 9],
+    [8, 10],
+    [11, 13],
+    [12, 14],
+    [13, 15],
+    [14, 16],
+];
+
+/// 默认回退字体：优先使用放在 `fonts/` 下的思源黑体，其次是各平台常见的中文系统字体
+pub const DEFAULT_FALLBACK_FONTS: [&str; 6] = [
+    "fonts/NotoSansSC-Regular.ttf",
+    "fonts/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/System/Library/Fonts/PingFang.ttc",
+    "C:/Windows/Fonts/msyh.ttc",
+];
+
+/// 默认调色板（Ultralytics 配色），未单独配置颜色的类别按类别 ID 取色
+pub const PALETTE: [[u8; 3]; 20] = [
+    [0xFF, 0x38, 0x38],
+    [0xFF, 0x9D, 0x97],
+    [0xFF, 0x70, 0x1F],
+    [0xFF, 0xB2, 0x1D],
+    [0xCF, 0xD2, 0x31],
+    [0x48, 0xF9, 0x0A],
+    [0x92, 0xCC, 0x17],
+    [0x3D, 0xDB, 0x86],
+    [0x1A, 0x93, 0x34],
+    [0x00, 0xD4, 0xBB],
+    [0x2C, 0x99, 0xA8],
+    [0x00, 0xC2, 0xFF],
+    [0x34, 0x45, 0x93],
+    [0x64, 0x73, 0xFF],
+    [0x00, 0x18, 0xEC],
+    [0x84, 0x38, 0xFF],
+    [0x52, 0x00, 0x85],
+    [0xCB, 0x38, 0xFF],
+    [0xFF, 0x95, 0xC8],
+    [0xFF, 0x37, 0xC7],
+];
+
+/// 标注样式配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AnnotationStyle {
+    /// 主字体文件
+    pub font: String,
+    /// 回退字体，主字体缺字（如中文）时依次查找，不存在的文件被跳过
+    pub fallback_fonts: Vec<String>,
+    /// 字号（像素）
+    pub font_size: f32,
+    /// 检测框线宽
+    pub bbox_thickness: u32,
+    /// 是否绘制标签
+    pub labels: bool,
+    /// 标签模板，见 [`super::LabelTemplate`]
+    pub label_template: String,
+    /// 类别显示名称翻译文件，见 [`super::DisplayNames`]
+    pub translations: Option<String>,
+    /// 翻译文件中使用的语言
+    pub locale: String,
+    /// 标签文字颜色（RGB）
+    pub label_text_color: [u8; 3],
+    /// 按类别名或类别 ID 指定颜色（RGB），例如 `{"person": [255, 0, 0], "2": [0, 255, 0]}`
+    pub class_colors: HashMap<String, [u8; 3]>,
+    /// 是否绘制分割掩码
+    pub masks: bool,
+    /// 掩码不透明度，0..=1
+    pub mask_alpha: f32,
+    /// 是否绘制分割掩码轮廓
+    pub contours: bool,
+    /// 是否绘制关键点
+    pub keypoints: bool,
+    /// 关键点半径
+    pub keypoint_radius: i32,
+    /// 低于该置信度的关键点不绘制
+    pub keypoint_threshold: f32,
+    /// 是否绘制骨架
+    pub skeletons: bool,
+    /// 骨架线宽
+    pub skeleton_thickness: u32,
+    /// 自定义骨架拓扑（关键点索引对），默认 COCO 17 点
+    pub skeleton: Option<Vec<[usize; 2]>>,
+}
+
+impl Default for AnnotationStyle {
+    fn default() -> Self {
+        Self {
+            font: "fonts/Arial.ttf".to_string(),
+            fallback_fonts: DEFAULT_FALLBACK_FONTS
+                .iter()
+                .map(|f| f.to_string())
+                .collect(),
+            font_size: 20.0,
+            bbox_thickness: 3,
+            labels: true,
+            label_template: "{name} {conf:.2}".to_string(),
+            translations: None,
+            locale: "zh-CN".to_string(),
+            label_text_color: [255, 255, 255],
+            class_colors: HashMap::new(),
+            masks: false,
+            mask_alpha: 0.5,
+            contours: true,
+            keypoints: true,
+            keypoint_radius: 3,
+            keypoint_threshold: 0.5,
+            skeletons: true,
+            skeleton_thickness: 2,
+            skeleton: None,
+        }
+    }
+}
+
+impl AnnotationStyle {
+    /// 类别颜色：先按类别名查找，再按类别 ID 查找，最后使用默认调色板
+    pub fn color(&self, class_id: isize, name: Option<&str>) -> [u8; 3] {
+        name.and_then(|n| self.class_colors.get(n))
+            .or_else(|| self.class_colors.get(&class_id.to_string()))
+            .copied()
+            .unwrap_or(PALETTE[class_id.rem_euclid(PALETTE.len() as isize) as usize])
+    }
+
+    /// 骨架拓扑
+    pub fn skeleton(&self) -> &[[usize; 2]] {
+        self.skeleton.as_deref().unwrap_or(&COCO_SKELETON)
+    }
+}
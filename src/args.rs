@@ -1,6 +1,12 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use once_cell::sync::Lazy;
-use usls::Options;
+use usls::{models::YOLO, Options};
+
+use crate::backend::{DnnConfig, InferenceBackend, OpenCvDnn, PreprocessConfig, Preprocessed};
+use crate::config::{AppConfig, StreamConfig};
+use crate::postprocess::FusionConfig;
+use crate::tiling::TileConfig;
+use crate::tta::TtaConfig;
 
 static ARGS: Lazy<Args> = Lazy::new(argh::from_env);
 
@@ -122,6 +128,78 @@ pub(crate) struct Args {
     /// keypoint_names
     #[argh(option)]
     keypoint_names: Vec<String>,
+
+    /// annotate output frames
+    #[argh(option, default = "true")]
+    annotate: bool,
+
+    /// config file (json)
+    #[argh(option)]
+    config: Option<String>,
+
+    /// stream name in config file
+    #[argh(option, default = "String::from(\"default\")")]
+    stream: String,
+
+    /// enable sliced (tiled) inference for small objects
+    #[argh(switch)]
+    tiled: bool,
+
+    /// tile_overlap
+    #[argh(option, default = "0.2")]
+    tile_overlap: f32,
+
+    /// tile_full_frame
+    #[argh(option, default = "true")]
+    tile_full_frame: bool,
+
+    /// tile_merge: nms | nmm
+    #[argh(option, default = "String::from(\"nmm\")")]
+    tile_merge: String,
+
+    /// tile_match_threshold
+    #[argh(option, default = "0.5")]
+    tile_match_threshold: f32,
+
+    /// enable test-time augmentation
+    #[argh(switch)]
+    tta: bool,
+
+    /// tta_flip
+    #[argh(option, default = "true")]
+    tta_flip: bool,
+
+    /// tta_sizes: extra square input sizes within min/max image size
+    #[argh(option)]
+    tta_sizes: Vec<isize>,
+
+    /// tta_brightness: brightness offsets, e.g. -30 30
+    #[argh(option)]
+    tta_brightness: Vec<i32>,
+
+    /// tta_iou_threshold
+    #[argh(option, default = "0.55")]
+    tta_iou_threshold: f32,
+
+    /// tta_flip_pairs: left/right keypoint indices swapped on flip, e.g. 1 2 3 4; defaults to the COCO-17 pairs and is required for pose models with other keypoint counts
+    #[argh(option)]
+    tta_flip_pairs: Vec<usize>,
+
+    /// inference backend: usls | opencv
+    #[argh(option, default = "String::from(\"usls\")")]
+    backend: String,
+
+    /// nms_iou_threshold (opencv backend)
+    #[argh(option, default = "0.45")]
+    nms_iou_threshold: f32,
+
+    /// resize mode: letterbox | stretch | crop
+    #[argh(option, default = "String::from(\"letterbox\")")]
+    resize_mode: String,
+
+    /// decode the source with libav and keep the decoder's native frames (video files and streams only); frames nothing is drawn on are encoded without an RGB round trip
+    #[argh(switch)]
+    native_decode: bool,
 }
 
 pub(crate) fn instance() -> &'static Args {
@@ -136,8 +214,254 @@ pub fn output() -> String {
     instance().output.clone()
 }
 
+pub fn annotate() -> bool {
+    instance().annotate
+}
+
+pub fn native_decode() -> bool {
+    instance().native_decode
+}
+
+pub fn stream_name() -> String {
+    instance().stream.clone()
+}
+
+/// 当前流的配置，未指定 `--config` 时使用默认配置
+pub fn stream_config() -> Result<StreamConfig> {
+    match &instance().config {
+        Some(path) => AppConfig::from_file(path)?.stream(&instance().stream),
+        None => Ok(StreamConfig::default()),
+    }
+}
+
+/// 切片推理配置，未开启 `--tiled` 时返回 None
+///
+/// 切片尺寸与模型输入尺寸（`image_width` / `image_height`）一致。
+pub fn tile_config() -> Result<Option<TileConfig>> {
+    let args = instance();
+    if !args.tiled {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        TileConfig::default()
+            .with_tile_size(args.image_width as u32, args.image_height as u32)
+            .with_overlap_ratio(args.tile_overlap)
+            .with_full_frame(args.tile_full_frame)
+            .with_merge(args.tile_merge.as_str().try_into()?)
+            .with_match_threshold(args.tile_match_threshold),
+    ))
+}
+
+/// 测试时增强配置，未开启 `--tta` 时返回 None
+pub fn tta_config() -> Result<Option<TtaConfig>> {
+    let args = instance();
+    if !args.tta {
+        return Ok(None);
+    }
+    if args.tiled {
+        return Err(anyhow!("--tta and --tiled cannot be used together"));
+    }
+
+    let min = args.min_image_width.max(args.min_image_height);
+    let max = args.max_image_width.min(args.max_image_height);
+    for &size in &args.tta_sizes {
+        if size < min || size > max || size % 32 != 0 {
+            return Err(anyhow!(
+                "Invalid tta size {}: must be a multiple of 32 within {}..={}",
+                size,
+                min,
+                max
+            ));
+        }
+    }
+
+    if args.tta_flip_pairs.len() % 2 != 0 {
+        return Err(anyhow!(
+            "Invalid tta flip pairs {:?}: expected an even number of indices",
+            args.tta_flip_pairs
+        ));
+    }
+    let flip_pairs = (!args.tta_flip_pairs.is_empty()).then(|| {
+        args.tta_flip_pairs
+            .chunks(2)
+            .map(|p| (p[0], p[1]))
+            .collect()
+    });
+
+    Ok(Some(TtaConfig {
+        flip: args.tta_flip,
+        flip_pairs,
+        sizes: args.tta_sizes.clone(),
+        brightness: args.tta_brightness.clone(),
+        max_batch: args.max_batch_size,
+        fusion: FusionConfig {
+            iou_threshold: args.tta_iou_threshold,
+            ..Default::default()
+        },
+        ..Default::default()
+    }))
+}
+
+/// 附加模型（多模型融合、多尺度 TTA）相对命令行参数的覆盖项
+///
+/// 版本、规模和任务只对 usls 后端生效，OpenCV 后端按输出形状自动识别。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BackendOverrides {
+    pub model: Option<String>,
+    pub ver: Option<f32>,
+    pub scale: Option<String>,
+    pub task: Option<String>,
+    pub confs: Vec<f32>,
+    pub class_names: Vec<String>,
+    /// 输入尺寸 (height, width)
+    pub size: Option<(isize, isize)>,
+}
+
+impl BackendOverrides {
+    pub fn with_model(mut self, x: &str) -> Self {
+        self.model = Some(x.to_string());
+        self
+    }
+
+    pub fn with_ver(mut self, x: f32) -> Self {
+        self.ver = Some(x);
+        self
+    }
+
+    pub fn with_scale(mut self, x: &str) -> Self {
+        self.scale = Some(x.to_string());
+        self
+    }
+
+    pub fn with_task(mut self, x: Option<&str>) -> Self {
+        self.task = x.map(|x| x.to_string());
+        self
+    }
+
+    pub fn with_confs(mut self, x: &[f32]) -> Self {
+        self.confs = x.to_vec();
+        self
+    }
+
+    pub fn with_class_names(mut self, x: &[String]) -> Self {
+        self.class_names = x.to_vec();
+        self
+    }
+
+    pub fn with_size(mut self, height: isize, width: isize) -> Self {
+        self.size = Some((height, width));
+        self
+    }
+}
+
+/// 按 `--backend` 创建推理后端
+pub fn build_backend() -> Result<Box<dyn InferenceBackend>> {
+    build_backend_with(&BackendOverrides::default())
+}
+
+/// 按 `--backend` 创建推理后端，`overrides` 中给出的字段替换命令行参数
+pub fn build_backend_with(overrides: &BackendOverrides) -> Result<Box<dyn InferenceBackend>> {
+    let args = instance();
+    let (image_height, image_width) = overrides
+        .size
+        .unwrap_or((args.image_height, args.image_width));
+    let model = overrides.model.clone().or_else(|| args.model.clone());
+    let confs = if overrides.confs.is_empty() {
+        &args.confs
+    } else {
+        &overrides.confs
+    };
+    match args.backend.as_str() {
+        "usls" => {
+            let mut options = build_options_with_size(image_height, image_width)?;
+            if let Some(model) = &model {
+                options = options.with_model_file(model);
+            }
+            if let Some(ver) = overrides.ver {
+                options = options.with_model_version(ver.into());
+            }
+            if let Some(scale) = &overrides.scale {
+                options = options.with_model_scale(scale.as_str().try_into()?);
+            }
+            if let Some(task) = &overrides.task {
+                options = options.with_model_task(task.as_str().try_into()?);
+            }
+            if !overrides.confs.is_empty() {
+                options = options.with_class_confs(&overrides.confs);
+            }
+            if !overrides.class_names.is_empty() {
+                options = options.with_class_names(
+                    &overrides
+                        .class_names
+                        .iter()
+                        .map(|x| x.as_str())
+                        .collect::<Vec<_>>(),
+                );
+            }
+            // 缩放由 Preprocessor 完成并精确映射回原图，usls 收到的已是输入尺寸
+            Ok(Box::new(Preprocessed::new(
+                Box::new(YOLO::try_from(options.commit()?)?),
+                PreprocessConfig::default()
+                    .with_size(image_width as u32, image_height as u32)
+                    .with_mode(args.resize_mode.as_str().try_into()?),
+            )))
+        }
+        "opencv" => {
+            let model =
+                model.ok_or_else(|| anyhow!("--model is required for the opencv backend"))?;
+            let class_names = if !overrides.class_names.is_empty() {
+                overrides.class_names.clone()
+            } else if !args.class_names.is_empty() {
+                args.class_names.clone()
+            } else if args.use_coco_80_classes {
+                usls::COCO_CLASS_NAMES_80
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+            // 附加模型的类别数与主模型不一定相同，由输出形状和类别名称推断
+            let num_classes = if overrides.model.is_some() {
+                (!class_names.is_empty()).then_some(class_names.len())
+            } else {
+                args.num_classes
+            };
+            Ok(Box::new(OpenCvDnn::new(DnnConfig {
+                model,
+                preprocess: PreprocessConfig::default()
+                    .with_size(image_width as u32, image_height as u32)
+                    .with_mode(args.resize_mode.as_str().try_into()?),
+                num_classes,
+                num_keypoints: if overrides.model.is_some() {
+                    None
+                } else {
+                    args.num_keypoints
+                },
+                conf_threshold: confs.first().copied().unwrap_or(0.25),
+                iou_threshold: args.nms_iou_threshold,
+                class_names,
+                cuda: args.device.starts_with("cuda"),
+                batch: args.batch_size,
+                ..Default::default()
+            })?))
+        }
+        other => Err(anyhow!(
+            "Unknown backend {:?}, expected usls or opencv",
+            other
+        )),
+    }
+}
+
 pub fn build_options() -> Result<Options> {
     let args = instance();
+    build_options_with_size(args.image_height, args.image_width)
+}
+
+/// 以指定的输入尺寸构建模型参数，尺寸需在 min/max 范围内
+pub fn build_options_with_size(image_height: isize, image_width: isize) -> Result<Options> {
+    let args = instance();
 
     let mut options = Options::yolo()
         .with_model_file(args.model.as_ref().unwrap_or(&String::new()))
@@ -155,17 +479,12 @@ pub fn build_options() -> Result<Options> {
         .with_model_ixx(
             0,
             2,
-            (
-                args.min_image_height,
-                args.image_height,
-                args.max_image_height,
-            )
-                .into(),
+            (args.min_image_height, image_height, args.max_image_height).into(),
         )
         .with_model_ixx(
             0,
             3,
-            (args.min_image_width, args.image_width, args.max_image_width).into(),
+            (args.min_image_width, image_width, args.max_image_width).into(),
         )
         .with_class_confs(if args.confs.is_empty() {
             &[0.2, 0.15]
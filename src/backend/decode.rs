@@ -0,0 +1,287 @@
+use anyhow::{anyhow, Result};
+use image::{imageops, GrayImage, Luma};
+
+use crate::postprocess::{nms, Detection, NmsConfig};
+use crate::utils::geometry::BoxF;
+
+/// YOLO 检测头的输出族
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// cx, cy, w, h, objectness, 类别分数
+    V5,
+    /// cx, cy, w, h, 类别分数，无 objectness（v8 / v11）
+    #[default]
+    V8,
+}
+
+impl OutputLayout {
+    /// 按模型版本号选择布局，v8 及以后的版本使用 V8 布局
+    pub fn from_version(version: f32) -> Self {
+        if version < 8.0 {
+            Self::V5
+        } else {
+            Self::V8
+        }
+    }
+}
+
+/// 自动识别布局时的已知信息，通常来自命令行参数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutHint {
+    pub family: Option<OutputLayout>,
+    pub num_classes: Option<usize>,
+    pub num_keypoints: Option<usize>,
+}
+
+/// 检测头输出的完整描述
+///
+/// 每个候选框的通道依次为：框 (4)、objectness (V5)、类别分数 (nc)、掩码系数 (nm)、关键点 (nk × 3)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadLayout {
+    pub family: OutputLayout,
+    /// true 为 [通道, 候选数]（v8 导出默认），false 为 [候选数, 通道]（v5 导出默认）
+    pub transposed: bool,
+    pub num_classes: usize,
+    pub num_masks: usize,
+    pub num_keypoints: usize,
+}
+
+impl HeadLayout {
+    pub fn new(family: OutputLayout, num_classes: usize) -> Self {
+        Self {
+            family,
+            transposed: family == OutputLayout::V8,
+            num_classes,
+            num_masks: 0,
+            num_keypoints: 0,
+        }
+    }
+
+    pub fn with_transposed(mut self, x: bool) -> Self {
+        self.transposed = x;
+        self
+    }
+
+    pub fn with_masks(mut self, x: usize) -> Self {
+        self.num_masks = x;
+        self
+    }
+
+    pub fn with_keypoints(mut self, x: usize) -> Self {
+        self.num_keypoints = x;
+        self
+    }
+
+    /// 每个候选框的通道数
+    pub fn channels(&self) -> usize {
+        self.class_offset() + self.num_classes + self.num_masks + 3 * self.num_keypoints
+    }
+
+    fn class_offset(&self) -> usize {
+        match self.family {
+            OutputLayout::V5 => 5,
+            OutputLayout::V8 => 4,
+        }
+    }
+
+    /// 根据输出形状识别布局
+    ///
+    /// `output` 为检测头形状（可带 batch 维），`protos` 为掩码原型形状 [N, nm, h, w]。
+    /// 候选数通常远大于通道数，据此判断是否转置；类别数与关键点数满足
+    /// `通道数 = 固定通道 + nc + 3 × nk`，只知道其中一个时推算另一个，都不知道时无法区分
+    /// 类别分数和关键点，返回错误（普通检测、分割模型可指定 `num_keypoints = 0`）。
+    /// 转置输出视为 V8、未转置输出视为 V5，可通过 `hint.family` 指定。
+    pub fn detect(output: &[usize], protos: Option<&[usize]>, hint: LayoutHint) -> Result<Self> {
+        let [d1, d2] = match output {
+            &[d1, d2] | &[_, d1, d2] => [d1, d2],
+            _ => return Err(anyhow!("Unsupported output shape {:?}", output)),
+        };
+        let num_masks = match protos {
+            Some(&[_, nm, _, _]) | Some(&[nm, _, _]) => nm,
+            Some(p) => return Err(anyhow!("Unsupported mask prototype shape {:?}", p)),
+            None => 0,
+        };
+        if hint.num_classes.is_none() && hint.num_keypoints.is_none() {
+            return Err(anyhow!(
+                "Cannot tell class scores from keypoints in output {:?}, specify num_classes or num_keypoints",
+                output
+            ));
+        }
+
+        let mut candidates = Vec::new();
+        for (transposed, channels, anchors) in [(true, d1, d2), (false, d2, d1)] {
+            for family in [OutputLayout::V8, OutputLayout::V5] {
+                if hint.family.is_some_and(|f| f != family) {
+                    continue;
+                }
+                let fixed = HeadLayout::new(family, 0).with_masks(num_masks).channels();
+                // 类别分数与关键点共用的通道数：nc + 3 × nk
+                let Some(rest) = channels.checked_sub(fixed) else {
+                    continue;
+                };
+                let (num_classes, num_keypoints) = match (hint.num_classes, hint.num_keypoints) {
+                    (Some(nc), Some(nk)) if nc > 0 && nc + 3 * nk == rest => (nc, nk),
+                    (Some(nc), None) if nc > 0 && nc <= rest && (rest - nc) % 3 == 0 => {
+                        (nc, (rest - nc) / 3)
+                    }
+                    (None, Some(nk)) if rest > 3 * nk => (rest - 3 * nk, nk),
+                    _ => continue,
+                };
+                let layout = HeadLayout::new(family, num_classes)
+                    .with_transposed(transposed)
+                    .with_masks(num_masks)
+                    .with_keypoints(num_keypoints);
+                let conventional = transposed == (family == OutputLayout::V8);
+                candidates.push((2 * (anchors > channels) as u8 + conventional as u8, layout));
+            }
+        }
+
+        candidates.sort_by_key(|(rank, _)| std::cmp::Reverse(*rank));
+        match candidates.as_slice() {
+            [] => Err(anyhow!(
+                "No YOLO layout matches output {:?} with {:?}",
+                output,
+                hint
+            )),
+            [(a, _), (b, _), ..] if a == b => Err(anyhow!(
+                "Ambiguous YOLO layout for output {:?}, specify num_classes or the model version",
+                output
+            )),
+            [(_, layout), ..] => Ok(*layout),
+        }
+    }
+}
+
+/// 单个候选框的解码结果，坐标在模型输入坐标系
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawDetection {
+    pub detection: Detection,
+    /// 掩码系数，与 [`MaskProtos`] 组合得到实例掩码
+    pub mask_coefs: Vec<f32>,
+    /// 关键点 [x, y, 置信度]
+    pub keypoints: Vec<[f32; 3]>,
+}
+
+/// 解码单张图像的检测头输出，`data` 为去掉 batch 维后的数据
+pub fn decode(layout: &HeadLayout, data: &[f32], conf_threshold: f32) -> Result<Vec<RawDetection>> {
+    let channels = layout.channels();
+    if data.is_empty() || !data.len().is_multiple_of(channels) {
+        return Err(anyhow!(
+            "Output size {} is not a multiple of {} channels",
+            data.len(),
+            channels
+        ));
+    }
+    let anchors = data.len() / channels;
+    let at = |c: usize, j: usize| {
+        if layout.transposed {
+            data[c * anchors + j]
+        } else {
+            data[j * channels + c]
+        }
+    };
+
+    let cls = layout.class_offset();
+    let masks = cls + layout.num_classes;
+    let kpts = masks + layout.num_masks;
+    Ok((0..anchors)
+        .filter_map(|j| {
+            let objectness = match layout.family {
+                OutputLayout::V5 => at(4, j),
+                OutputLayout::V8 => 1.0,
+            };
+            if objectness < conf_threshold {
+                return None;
+            }
+            let (class_id, score) = argmax((cls..masks).map(|c| at(c, j)))?;
+            let score = objectness * score;
+            (score >= conf_threshold).then(|| RawDetection {
+                detection: Detection::new(
+                    BoxF::from_cxcywh([at(0, j), at(1, j), at(2, j), at(3, j)]),
+                    score,
+                    class_id,
+                ),
+                mask_coefs: (masks..kpts).map(|c| at(c, j)).collect(),
+                keypoints: (0..layout.num_keypoints)
+                    .map(|k| {
+                        let c = kpts + 3 * k;
+                        [at(c, j), at(c + 1, j), at(c + 2, j)]
+                    })
+                    .collect(),
+            })
+        })
+        .collect())
+}
+
+/// 对解码结果执行 NMS，掩码系数和关键点跟随保留下来的检测框
+pub fn nms_raw(raws: &[RawDetection], config: &NmsConfig) -> Vec<RawDetection> {
+    // 暂用 source 记录候选下标
+    let tagged: Vec<Detection> = raws
+        .iter()
+        .enumerate()
+        .map(|(i, r)| r.detection.with_source(i))
+        .collect();
+    nms(&tagged, config)
+        .into_iter()
+        .map(|d| {
+            let raw = &raws[d.source];
+            RawDetection {
+                detection: d.with_source(raw.detection.source),
+                ..raw.clone()
+            }
+        })
+        .collect()
+}
+
+/// 分割模型输出的掩码原型 [nm, h, w]
+#[derive(Debug, Clone, Copy)]
+pub struct MaskProtos<'a> {
+    data: &'a [f32],
+    dims: [usize; 3],
+}
+
+impl<'a> MaskProtos<'a> {
+    pub fn new(data: &'a [f32], dims: [usize; 3]) -> Result<Self> {
+        if data.len() != dims.iter().product::<usize>() {
+            return Err(anyhow!(
+                "Prototype size {} does not match shape {:?}",
+                data.len(),
+                dims
+            ));
+        }
+        Ok(Self { data, dims })
+    }
+
+    pub fn num_masks(&self) -> usize {
+        self.dims[0]
+    }
+
+    /// 组合出实例掩码：sigmoid(coefs · protos) 放大到模型输入尺寸，框外置零，二值化为 0/255
+    pub fn mask(&self, coefs: &[f32], bbox: &BoxF, input_size: (u32, u32)) -> GrayImage {
+        let [nm, h, w] = self.dims;
+        let plane = h * w;
+        let prob = GrayImage::from_fn(w as u32, h as u32, |x, y| {
+            let i = y as usize * w + x as usize;
+            let logit: f32 = coefs
+                .iter()
+                .take(nm)
+                .enumerate()
+                .map(|(k, c)| c * self.data[k * plane + i])
+                .sum();
+            Luma([(255.0 / (1.0 + (-logit).exp())).round() as u8])
+        });
+
+        let (iw, ih) = input_size;
+        let mut mask = imageops::resize(&prob, iw, ih, imageops::FilterType::Triangle);
+        for (x, y, p) in mask.enumerate_pixels_mut() {
+            let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let inside = cx >= bbox.x1 && cx < bbox.x2 && cy >= bbox.y1 && cy < bbox.y2;
+            p[0] = if inside && p[0] > 127 { 255 } else { 0 };
+        }
+        mask
+    }
+}
+
+fn argmax(scores: impl Iterator<Item = f32>) -> Option<(usize, f32)> {
+    scores.enumerate().max_by(|a, b| a.1.total_cmp(&b.1))
+}
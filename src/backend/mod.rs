@@ -0,0 +1,38 @@
+pub mod decode;
+pub mod opencv_dnn;
+pub mod preprocess;
+pub mod preprocessed;
+
+pub use decode::{decode, nms_raw, HeadLayout, LayoutHint, MaskProtos, OutputLayout, RawDetection};
+pub use opencv_dnn::{DnnConfig, OpenCvDnn};
+pub use preprocess::{ChannelOrder, PreprocessConfig, Preprocessor, ResizeMode, Transform};
+pub use preprocessed::Preprocessed;
+
+use anyhow::Result;
+use image::DynamicImage;
+use usls::{models::YOLO, Y};
+
+/// 推理后端：输入一批 RGB 帧，输出与 usls 相同的 `Y`，下游的融合、跟踪和标注与后端无关
+pub trait InferenceBackend {
+    fn forward(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>>;
+
+    /// 单次推理的最大帧数
+    fn batch(&self) -> usize;
+
+    /// 输出推理耗时等统计信息
+    fn summary(&mut self) {}
+}
+
+impl InferenceBackend for YOLO {
+    fn forward(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        YOLO::forward(self, xs)
+    }
+
+    fn batch(&self) -> usize {
+        YOLO::batch(self)
+    }
+
+    fn summary(&mut self) {
+        YOLO::summary(self)
+    }
+}
@@ -0,0 +1,245 @@
+use anyhow::{anyhow, Context, Result};
+use image::DynamicImage;
+use opencv::core::{Mat, Scalar, Vector, CV_32F};
+use opencv::{dnn, prelude::*};
+use std::collections::HashMap;
+use usls::{Keypoint, Mask, Y};
+
+use super::{
+    decode, nms_raw, HeadLayout, InferenceBackend, LayoutHint, MaskProtos, OutputLayout,
+    PreprocessConfig, Preprocessor,
+};
+use crate::postprocess::{Detection, NmsConfig};
+
+/// OpenCV DNN 后端配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnnConfig {
+    /// ONNX 模型文件
+    pub model: String,
+    /// 输入尺寸、缩放方式与归一化
+    pub preprocess: PreprocessConfig,
+    /// 输出族，None 时按输出形状自动识别
+    pub layout: Option<OutputLayout>,
+    /// 类别数与关键点数，用于自动识别检测头布局，至少需要知道其一；
+    /// 未指定类别数时取 `class_names` 的长度
+    pub num_classes: Option<usize>,
+    pub num_keypoints: Option<usize>,
+    pub conf_threshold: f32,
+    pub iou_threshold: f32,
+    /// 类别名称，按类别 ID 排列
+    pub class_names: Vec<String>,
+    /// 使用 CUDA 后端（需要 OpenCV 编译时启用 CUDA）
+    pub cuda: bool,
+    /// 单次推理的帧数，导出时 batch 维度固定为 1 的模型只能用 1
+    pub batch: usize,
+}
+
+impl Default for DnnConfig {
+    fn default() -> Self {
+        Self {
+            model: String::new(),
+            preprocess: PreprocessConfig::default(),
+            layout: None,
+            num_classes: None,
+            num_keypoints: None,
+            conf_threshold: 0.25,
+            iou_threshold: 0.45,
+            class_names: Vec::new(),
+            cuda: false,
+            batch: 1,
+        }
+    }
+}
+
+/// 基于 OpenCV `dnn::Net` 的 YOLO 后端，支持检测、分割和姿态模型，不依赖 ONNX Runtime
+pub struct OpenCvDnn {
+    config: DnnConfig,
+    net: dnn::Net,
+    preprocessor: Preprocessor,
+    output_names: Vector<String>,
+    /// 首次推理时根据输出形状识别
+    head: Option<HeadLayout>,
+    nms: NmsConfig,
+    names: HashMap<usize, String>,
+}
+
+impl OpenCvDnn {
+    pub fn new(config: DnnConfig) -> Result<Self> {
+        let mut net = dnn::read_net_from_onnx(&config.model)
+            .with_context(|| format!("Failed to load ONNX model: {:?}", config.model))?;
+        if config.cuda {
+            net.set_preferable_backend(dnn::DNN_BACKEND_CUDA)?;
+            net.set_preferable_target(dnn::DNN_TARGET_CUDA)?;
+        } else {
+            net.set_preferable_backend(dnn::DNN_BACKEND_OPENCV)?;
+            net.set_preferable_target(dnn::DNN_TARGET_CPU)?;
+        }
+        let output_names = net.get_unconnected_out_layers_names()?;
+
+        let preprocessor = Preprocessor::new(config.preprocess);
+        let nms = NmsConfig::default().with_iou_threshold(config.iou_threshold);
+        let names = config.class_names.iter().cloned().enumerate().collect();
+        Ok(Self {
+            config,
+            net,
+            preprocessor,
+            output_names,
+            head: None,
+            nms,
+            names,
+        })
+    }
+
+    pub fn config(&self) -> &DnnConfig {
+        &self.config
+    }
+
+    /// 已识别的检测头布局，首次推理前为 None
+    pub fn head(&self) -> Option<&HeadLayout> {
+        self.head.as_ref()
+    }
+
+    fn forward_batch(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        let [w, h] = self.config.preprocess.size;
+        let (data, transforms) = self.preprocessor.blob(xs);
+        let mut blob = Mat::new_nd_with_default(
+            &[xs.len() as i32, 3, h as i32, w as i32],
+            CV_32F,
+            Scalar::all(0.0),
+        )?;
+        blob.data_typed_mut::<f32>()?.copy_from_slice(&data);
+        self.net.set_input_def(&blob)?;
+        let mut outputs = Vector::<Mat>::new();
+        self.net.forward(&mut outputs, &self.output_names)?;
+
+        // 检测头为 3 维输出，掩码原型为 4 维输出
+        let mut head = None;
+        let mut protos = None;
+        for output in outputs.iter() {
+            let shape: Vec<usize> = output.mat_size().iter().map(|&d| d as usize).collect();
+            match shape.len() {
+                3 => head = Some((output, shape)),
+                4 => protos = Some((output, shape)),
+                _ => {}
+            }
+        }
+        let (output, shape) = head.ok_or_else(|| anyhow!("Model has no 3-d detection output"))?;
+        if shape[0] != xs.len() {
+            return Err(anyhow!(
+                "Unexpected output shape {:?} for batch of {}",
+                shape,
+                xs.len()
+            ));
+        }
+        let layout = match self.head {
+            Some(layout) => layout,
+            None => {
+                let layout = HeadLayout::detect(
+                    &shape,
+                    protos.as_ref().map(|(_, s)| s.as_slice()),
+                    LayoutHint {
+                        family: self.config.layout,
+                        num_classes: self.config.num_classes.or((!self
+                            .config
+                            .class_names
+                            .is_empty())
+                        .then_some(self.config.class_names.len())),
+                        num_keypoints: self.config.num_keypoints,
+                    },
+                )?;
+                tracing::info!("Detected output layout: {:?}", layout);
+                self.head = Some(layout);
+                layout
+            }
+        };
+
+        let data = output.data_typed::<f32>()?;
+        let per_image = shape[1] * shape[2];
+        let proto_data = match &protos {
+            Some((p, s)) => Some((p.data_typed::<f32>()?, [s[1], s[2], s[3]])),
+            None => None,
+        };
+
+        xs.iter()
+            .zip(transforms)
+            .enumerate()
+            .map(|(i, (x, t))| {
+                let raw = &data[i * per_image..(i + 1) * per_image];
+                let kept = nms_raw(
+                    &decode(&layout, raw, self.config.conf_threshold)?,
+                    &self.nms,
+                );
+                let protos = match proto_data {
+                    Some((p, dims)) => {
+                        let n = dims.iter().product::<usize>();
+                        Some(MaskProtos::new(&p[i * n..(i + 1) * n], dims)?)
+                    }
+                    None => None,
+                };
+
+                let (width, height) = (x.width(), x.height());
+                let mut bboxes = Vec::with_capacity(kept.len());
+                let mut keypoints = Vec::with_capacity(kept.len());
+                let mut masks = Vec::with_capacity(kept.len());
+                for r in &kept {
+                    let d = &r.detection;
+                    let bbox = t.inverse_box(&d.bbox).clip(width as f32, height as f32);
+                    if bbox.is_empty() {
+                        continue;
+                    }
+                    let name = self.names.get(&d.class_id);
+                    bboxes.push(
+                        Detection::new(bbox, d.score, d.class_id).to_bbox(name.map(|n| n.as_str())),
+                    );
+
+                    keypoints.push(
+                        t.inverse_keypoints(&r.keypoints)
+                            .into_iter()
+                            .enumerate()
+                            .map(|(k, [kx, ky, conf])| {
+                                Keypoint::default()
+                                    .with_xy(kx, ky)
+                                    .with_confidence(conf)
+                                    .with_id(k as isize)
+                            })
+                            .collect::<Vec<_>>(),
+                    );
+
+                    if let Some(protos) = &protos {
+                        let mask = protos.mask(&r.mask_coefs, &d.bbox, (w, h));
+                        let mut m = Mask::default()
+                            .with_mask(t.inverse_mask(&mask))
+                            .with_id(d.class_id as isize);
+                        if let Some(name) = name {
+                            m = m.with_name(name);
+                        }
+                        masks.push(m);
+                    }
+                }
+
+                let mut y = Y::default().with_bboxes(&bboxes);
+                if layout.num_keypoints > 0 {
+                    y = y.with_keypoints(&keypoints);
+                }
+                if !masks.is_empty() {
+                    y = y.with_masks(&masks);
+                }
+                Ok(y)
+            })
+            .collect()
+    }
+}
+
+impl InferenceBackend for OpenCvDnn {
+    fn forward(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        let mut ys = Vec::with_capacity(xs.len());
+        for chunk in xs.chunks(self.config.batch.max(1)) {
+            ys.extend(self.forward_batch(chunk)?);
+        }
+        Ok(ys)
+    }
+
+    fn batch(&self) -> usize {
+        self.config.batch.max(1)
+    }
+}
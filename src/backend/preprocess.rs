@@ -0,0 +1,259 @@
+use anyhow::{anyhow, Result};
+use image::{imageops, DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+
+use crate::utils::geometry::BoxF;
+
+/// 缩放到模型输入尺寸的方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ResizeMode {
+    /// 等比缩放后居中填充
+    #[default]
+    Letterbox,
+    /// 拉伸到输入尺寸，不保持宽高比
+    Stretch,
+    /// 等比缩放铺满输入后居中裁剪
+    CenterCrop,
+}
+
+impl TryFrom<&str> for ResizeMode {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "letterbox" => Ok(Self::Letterbox),
+            "stretch" => Ok(Self::Stretch),
+            "crop" | "center_crop" => Ok(Self::CenterCrop),
+            x => Err(anyhow!("Unsupported resize mode: {}", x)),
+        }
+    }
+}
+
+/// 输入张量的通道顺序
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelOrder {
+    #[default]
+    Rgb,
+    Bgr,
+}
+
+/// 预处理配置，归一化为 `(像素 / 255 - mean) / std`，mean / std 按 RGB 顺序给出
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreprocessConfig {
+    /// 模型输入尺寸 [宽, 高]
+    pub size: [u32; 2],
+    pub mode: ResizeMode,
+    pub pad_color: [u8; 3],
+    pub mean: [f32; 3],
+    pub std: [f32; 3],
+    pub channel_order: ChannelOrder,
+}
+
+impl Default for PreprocessConfig {
+    fn default() -> Self {
+        Self {
+            size: [640, 640],
+            mode: ResizeMode::Letterbox,
+            pad_color: [114, 114, 114],
+            mean: [0.0; 3],
+            std: [1.0; 3],
+            channel_order: ChannelOrder::Rgb,
+        }
+    }
+}
+
+impl PreprocessConfig {
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    pub fn with_mode(mut self, x: ResizeMode) -> Self {
+        self.mode = x;
+        self
+    }
+
+    pub fn with_pad_color(mut self, x: [u8; 3]) -> Self {
+        self.pad_color = x;
+        self
+    }
+
+    pub fn with_normalization(mut self, mean: [f32; 3], std: [f32; 3]) -> Self {
+        self.mean = mean;
+        self.std = std;
+        self
+    }
+
+    pub fn with_channel_order(mut self, x: ChannelOrder) -> Self {
+        self.channel_order = x;
+        self
+    }
+}
+
+/// 单帧实际应用的几何变换：`输入坐标 = 原图坐标 × scale + offset`
+///
+/// 缩放比例按取整后的实际缩放尺寸分别记录 x / y，因此逆变换与像素完全对齐。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// 原图尺寸
+    pub src: (u32, u32),
+    /// 模型输入尺寸
+    pub dst: (u32, u32),
+    pub scale_x: f32,
+    pub scale_y: f32,
+    /// 填充为正，裁剪为负
+    pub offset_x: f32,
+    pub offset_y: f32,
+}
+
+impl Transform {
+    /// 计算把 (width, height) 按 `mode` 变换到 `size` 的参数
+    pub fn new(width: u32, height: u32, size: [u32; 2], mode: ResizeMode) -> Self {
+        let [dst_w, dst_h] = size;
+        let (rx, ry) = (dst_w as f32 / width as f32, dst_h as f32 / height as f32);
+        let (new_w, new_h) = match mode {
+            ResizeMode::Stretch => (dst_w, dst_h),
+            ResizeMode::Letterbox => scaled(width, height, rx.min(ry)),
+            ResizeMode::CenterCrop => scaled(width, height, rx.max(ry)),
+        };
+        // 填充和裁剪都取整到像素，余下的 1 像素放在右 / 下侧
+        let offset = |dst: u32, new: u32| (dst as i64 - new as i64).div_euclid(2) as f32;
+        Self {
+            src: (width, height),
+            dst: (dst_w, dst_h),
+            scale_x: new_w as f32 / width as f32,
+            scale_y: new_h as f32 / height as f32,
+            offset_x: offset(dst_w, new_w),
+            offset_y: offset(dst_h, new_h),
+        }
+    }
+
+    /// 缩放后的图像尺寸（填充或裁剪前）
+    pub fn resized(&self) -> (u32, u32) {
+        (
+            (self.src.0 as f32 * self.scale_x).round() as u32,
+            (self.src.1 as f32 * self.scale_y).round() as u32,
+        )
+    }
+
+    pub fn forward_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            x * self.scale_x + self.offset_x,
+            y * self.scale_y + self.offset_y,
+        )
+    }
+
+    pub fn inverse_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            (x - self.offset_x) / self.scale_x,
+            (y - self.offset_y) / self.scale_y,
+        )
+    }
+
+    pub fn forward_box(&self, b: &BoxF) -> BoxF {
+        b.scale(self.scale_x, self.scale_y)
+            .translate(self.offset_x, self.offset_y)
+    }
+
+    /// 输入坐标系中的框映射回原图，不做裁剪
+    pub fn inverse_box(&self, b: &BoxF) -> BoxF {
+        b.translate(-self.offset_x, -self.offset_y)
+            .scale(1.0 / self.scale_x, 1.0 / self.scale_y)
+    }
+
+    /// 关键点 [x, y, 置信度] 映射回原图
+    pub fn inverse_keypoints(&self, keypoints: &[[f32; 3]]) -> Vec<[f32; 3]> {
+        keypoints
+            .iter()
+            .map(|&[x, y, c]| {
+                let (x, y) = self.inverse_point(x, y);
+                [x, y, c]
+            })
+            .collect()
+    }
+
+    /// 输入尺寸的掩码映射回原图尺寸：按原图像素中心在输入中的位置最近邻采样，裁剪掉的区域为 0
+    pub fn inverse_mask(&self, mask: &GrayImage) -> GrayImage {
+        let (w, h) = self.src;
+        let (mw, mh) = mask.dimensions();
+        GrayImage::from_fn(w, h, |x, y| {
+            let (mx, my) = self.forward_point(x as f32 + 0.5, y as f32 + 0.5);
+            if mx < 0.0 || my < 0.0 || mx >= mw as f32 || my >= mh as f32 {
+                Luma([0])
+            } else {
+                *mask.get_pixel(mx as u32, my as u32)
+            }
+        })
+    }
+}
+
+/// 按 [`PreprocessConfig`] 把帧变换为模型输入，并记录每帧的 [`Transform`]
+#[derive(Debug, Clone, Default)]
+pub struct Preprocessor {
+    config: PreprocessConfig,
+}
+
+impl Preprocessor {
+    pub fn new(config: PreprocessConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PreprocessConfig {
+        &self.config
+    }
+
+    pub fn transform(&self, width: u32, height: u32) -> Transform {
+        Transform::new(width, height, self.config.size, self.config.mode)
+    }
+
+    /// 缩放、填充或裁剪到输入尺寸
+    pub fn resize(&self, image: &DynamicImage) -> (RgbImage, Transform) {
+        let t = self.transform(image.width(), image.height());
+        let (new_w, new_h) = t.resized();
+        let resized = image
+            .resize_exact(new_w, new_h, imageops::FilterType::Triangle)
+            .into_rgb8();
+        let (dst_w, dst_h) = t.dst;
+        let mut canvas = RgbImage::from_pixel(dst_w, dst_h, Rgb(self.config.pad_color));
+        imageops::replace(&mut canvas, &resized, t.offset_x as i64, t.offset_y as i64);
+        (canvas, t)
+    }
+
+    /// 归一化为 CHW 并写入 `out`，长度需为 3 × 宽 × 高
+    pub fn normalize_into(&self, image: &RgbImage, out: &mut [f32]) {
+        let plane = (image.width() * image.height()) as usize;
+        let PreprocessConfig { mean, std, .. } = self.config;
+        let order = match self.config.channel_order {
+            ChannelOrder::Rgb => [0, 1, 2],
+            ChannelOrder::Bgr => [2, 1, 0],
+        };
+        for (i, p) in image.pixels().enumerate() {
+            for (c, &src) in order.iter().enumerate() {
+                out[c * plane + i] = (p[src] as f32 / 255.0 - mean[src]) / std[src];
+            }
+        }
+    }
+
+    /// 生成 NCHW 输入张量
+    pub fn blob(&self, xs: &[DynamicImage]) -> (Vec<f32>, Vec<Transform>) {
+        let [w, h] = self.config.size;
+        let per_image = 3 * (w * h) as usize;
+        let mut data = vec![0.0; xs.len() * per_image];
+        let transforms = xs
+            .iter()
+            .zip(data.chunks_exact_mut(per_image))
+            .map(|(x, out)| {
+                let (canvas, t) = self.resize(x);
+                self.normalize_into(&canvas, out);
+                t
+            })
+            .collect();
+        (data, transforms)
+    }
+}
+
+fn scaled(width: u32, height: u32, scale: f32) -> (u32, u32) {
+    (
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
@@ -0,0 +1,81 @@
+use anyhow::Result;
+use image::DynamicImage;
+use usls::Y;
+
+use super::{InferenceBackend, PreprocessConfig, Preprocessor, Transform};
+use crate::postprocess::{
+    class_names_from_ys, instances_from_y, y_from_instances, Instance, InstanceMask,
+};
+
+/// 先按 [`Preprocessor`] 把帧变换到模型输入尺寸再交给内层后端，结果按记录的 [`Transform`]
+/// 映射回原图
+///
+/// 内层后端（usls）收到的帧已是输入尺寸，其自身的缩放为恒等变换，缩放方式与逆变换都由
+/// 本模块决定；归一化和通道顺序仍由内层后端处理。
+pub struct Preprocessed {
+    inner: Box<dyn InferenceBackend>,
+    preprocessor: Preprocessor,
+}
+
+impl Preprocessed {
+    pub fn new(inner: Box<dyn InferenceBackend>, config: PreprocessConfig) -> Self {
+        Self {
+            inner,
+            preprocessor: Preprocessor::new(config),
+        }
+    }
+}
+
+impl InferenceBackend for Preprocessed {
+    fn forward(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        let (inputs, transforms): (Vec<_>, Vec<_>) = xs
+            .iter()
+            .map(|x| {
+                let (canvas, t) = self.preprocessor.resize(x);
+                (DynamicImage::ImageRgb8(canvas), t)
+            })
+            .unzip();
+        let ys = self.inner.forward(&inputs)?;
+        Ok(ys
+            .iter()
+            .zip(&transforms)
+            .map(|(y, t)| inverse_y(y, t))
+            .collect())
+    }
+
+    fn batch(&self) -> usize {
+        self.inner.batch()
+    }
+
+    fn summary(&mut self) {
+        self.inner.summary()
+    }
+}
+
+/// 输入坐标系下的结果映射回原图：检测框裁剪到画面内，完全落在填充区域的结果被丢弃
+pub fn inverse_y(y: &Y, t: &Transform) -> Y {
+    let (width, height) = t.src;
+    let instances: Vec<Instance> = instances_from_y(y)
+        .into_iter()
+        .filter_map(|mut x| {
+            let bbox = t
+                .inverse_box(&x.detection.bbox)
+                .clip(width as f32, height as f32);
+            if bbox.is_empty() {
+                return None;
+            }
+            x.detection.bbox = bbox;
+            if let Some(kpts) = &mut x.keypoints {
+                for k in kpts.iter_mut() {
+                    let (kx, ky) = t.inverse_point(k.x(), k.y());
+                    *k = k.clone().with_xy(kx, ky);
+                }
+            }
+            x.mask = x
+                .mask
+                .map(|m| InstanceMask::full(t.inverse_mask(&m.to_frame())));
+            Some(x)
+        })
+        .collect();
+    y_from_instances(&instances, &class_names_from_ys([y]))
+}
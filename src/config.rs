@@ -0,0 +1,65 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::annotation::{AnnotationStyle, OverlayConfig};
+use crate::ensemble::EnsembleConfig;
+use crate::heatmap::HeatmapConfig;
+use crate::privacy_mask::PrivacyMaskConfig;
+use crate::redaction::RedactionConfig;
+use crate::stitching::StitchConfig;
+use crate::trajectory::TrajectoryConfig;
+
+/// 单路视频流的处理配置
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct StreamConfig {
+    /// 标注样式
+    pub annotation: AnnotationStyle,
+    /// 状态叠加层
+    pub overlay: Option<OverlayConfig>,
+    /// 多模型融合
+    pub ensemble: Option<EnsembleConfig>,
+    /// 隐私打码
+    pub redaction: Option<RedactionConfig>,
+    /// 静态隐私遮挡区域
+    pub privacy_masks: Option<PrivacyMaskConfig>,
+    /// 检测热力图
+    pub heatmap: Option<HeatmapConfig>,
+    /// 目标跟踪与轨迹
+    pub trajectories: Option<TrajectoryConfig>,
+    /// 多路相机拼接，设置后以拼接后的全景流作为输入
+    pub stitching: Option<StitchConfig>,
+}
+
+/// 配置文件（JSON），按流名称区分各路视频流的配置
+///
+/// ```json
+/// { "streams": { "gate-1": { "ensemble": { "models": [ { "model": "custom.onnx" } ] } } } }
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub streams: HashMap<String, StreamConfig>,
+}
+
+impl AppConfig {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid config file: {:?}", path))
+    }
+
+    /// 获取指定流的配置
+    pub fn stream(&self, name: &str) -> Result<StreamConfig> {
+        self.streams.get(name).cloned().ok_or_else(|| {
+            anyhow!(
+                "Stream {:?} not found in config, available: {:?}",
+                name,
+                self.streams.keys().collect::<Vec<_>>()
+            )
+        })
+    }
+}
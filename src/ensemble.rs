@@ -0,0 +1,206 @@
+use anyhow::Result;
+use image::DynamicImage;
+use serde::Deserialize;
+use std::collections::HashMap;
+use usls::Y;
+
+use crate::args::{self, BackendOverrides};
+use crate::backend::InferenceBackend;
+use crate::postprocess::{
+    class_names_from_ys, fuse_groups, instances_from_y, y_from_instances, Detection, FusionConfig,
+    Instance, LabelSpace,
+};
+
+/// 参与融合的附加模型
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnsembleModel {
+    /// 模型文件
+    pub model: String,
+    #[serde(default = "default_ver")]
+    pub ver: f32,
+    #[serde(default = "default_scale")]
+    pub scale: String,
+    /// 缺省时沿用命令行的 task
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub confs: Vec<f32>,
+    /// 模型自身的类别名称（模型文件中没有时需要提供）
+    #[serde(default)]
+    pub class_names: Vec<String>,
+    /// 类别名称重映射：模型类别名 -> 融合后的类别名
+    #[serde(default)]
+    pub class_map: HashMap<String, String>,
+    /// 为 true 时丢弃 `class_map` 中未列出的类别
+    #[serde(default)]
+    pub drop_unmapped: bool,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+}
+
+/// 多模型融合配置
+#[derive(Debug, Clone, Deserialize)]
+pub struct EnsembleConfig {
+    /// 附加模型，与命令行指定的主模型一起融合
+    pub models: Vec<EnsembleModel>,
+    #[serde(default = "default_weight")]
+    pub primary_weight: f32,
+    /// 主模型的类别名称重映射
+    #[serde(default)]
+    pub primary_class_map: HashMap<String, String>,
+    #[serde(default)]
+    pub primary_drop_unmapped: bool,
+    /// 预先固定的融合后类别顺序，保证类别编号（及标注颜色）稳定
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub fusion: FusionConfig,
+}
+
+fn default_ver() -> f32 {
+    8.0
+}
+
+fn default_scale() -> String {
+    String::from("n")
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+struct Member {
+    model: Box<dyn InferenceBackend>,
+    weight: f32,
+    class_map: HashMap<String, String>,
+    drop_unmapped: bool,
+}
+
+/// 多模型融合推理：在主模型输出的基础上运行附加模型，并将结果统一到同一类别空间后融合
+///
+/// 融合结果的掩码和关键点取自簇内分数最高且带有对应结果的成员，之后与单模型输出一样交给 Annotator 绘制。
+pub struct Ensemble {
+    members: Vec<Member>,
+    config: EnsembleConfig,
+    labels: LabelSpace,
+}
+
+impl Ensemble {
+    /// 附加模型由 `--backend` 指定的后端创建，设备、输入尺寸等沿用命令行参数，模型文件等由各自的配置覆盖
+    pub fn new(config: EnsembleConfig) -> Result<Self> {
+        let members = config
+            .models
+            .iter()
+            .map(|m| {
+                let overrides = BackendOverrides::default()
+                    .with_model(&m.model)
+                    .with_ver(m.ver)
+                    .with_scale(&m.scale)
+                    .with_task(m.task.as_deref())
+                    .with_confs(&m.confs)
+                    .with_class_names(&m.class_names);
+                let model = args::build_backend_with(&overrides)?;
+                tracing::info!("ensemble model loaded: {}", m.model);
+                Ok(Member {
+                    model,
+                    weight: m.weight,
+                    class_map: m.class_map.clone(),
+                    drop_unmapped: m.drop_unmapped,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            members,
+            labels: LabelSpace::new(&config.labels),
+            config,
+        })
+    }
+
+    /// 融合后的类别名称，按统一编号排列
+    pub fn labels(&self) -> &[String] {
+        self.labels.names()
+    }
+
+    /// 对同一批帧运行附加模型，并与主模型结果 `primary` 融合
+    pub fn forward(&mut self, xs: &[DynamicImage], primary: Vec<Y>) -> Result<Vec<Y>> {
+        // sources[frame][model]
+        let mut sources: Vec<Vec<Vec<Instance>>> = primary
+            .iter()
+            .map(|y| {
+                vec![remap(
+                    &mut self.labels,
+                    y,
+                    &self.config.primary_class_map,
+                    self.config.primary_drop_unmapped,
+                )]
+            })
+            .collect();
+
+        for member in self.members.iter_mut() {
+            let ys = member.model.forward(xs)?;
+            for (frame, y) in sources.iter_mut().zip(ys.iter()) {
+                frame.push(remap(
+                    &mut self.labels,
+                    y,
+                    &member.class_map,
+                    member.drop_unmapped,
+                ));
+            }
+        }
+
+        let weights: Vec<f32> = std::iter::once(self.config.primary_weight)
+            .chain(self.members.iter().map(|m| m.weight))
+            .collect();
+        let names: HashMap<usize, String> =
+            self.labels.names().iter().cloned().enumerate().collect();
+
+        Ok(sources
+            .iter()
+            .map(|frame| {
+                y_from_instances(&fuse_frame(frame, &weights, &self.config.fusion), &names)
+            })
+            .collect())
+    }
+}
+
+/// 融合一帧内各模型的实例，掩码和关键点取自簇内分数最高且带有对应结果的成员
+fn fuse_frame(frame: &[Vec<Instance>], weights: &[f32], config: &FusionConfig) -> Vec<Instance> {
+    let detections: Vec<Vec<Detection>> = frame
+        .iter()
+        .map(|xs| xs.iter().map(|x| x.detection).collect())
+        .collect();
+    fuse_groups(&detections, weights, config)
+        .into_iter()
+        .map(|(d, members)| {
+            let members: Vec<&Instance> = members.iter().map(|&(i, k)| &frame[i][k]).collect();
+            Instance {
+                detection: d,
+                mask: members.iter().find_map(|x| x.mask.clone()),
+                keypoints: members.iter().find_map(|x| x.keypoints.clone()),
+            }
+        })
+        .collect()
+}
+
+/// 按类别名称重映射到统一类别空间，没有名称的框以 `#id` 作为名称
+fn remap(
+    labels: &mut LabelSpace,
+    y: &Y,
+    class_map: &HashMap<String, String>,
+    drop_unmapped: bool,
+) -> Vec<Instance> {
+    let names = class_names_from_ys([y]);
+    instances_from_y(y)
+        .into_iter()
+        .filter_map(|mut x| {
+            let id = x.detection.class_id;
+            let name = names
+                .get(&id)
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", id));
+            x.detection.class_id = labels.remap(&name, class_map, drop_unmapped)?;
+            Some(x)
+        })
+        .collect()
+}
@@ -0,0 +1,210 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::GroundTruth;
+
+const IMAGE_EXTENSIONS: [&str; 6] = ["jpg", "jpeg", "png", "bmp", "webp", "tiff"];
+
+/// 单张评估图片及其真值
+#[derive(Debug, Clone)]
+pub struct Sample {
+    pub image: PathBuf,
+    pub ground_truths: Vec<GroundTruth>,
+}
+
+/// 评估数据集
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub class_names: Vec<String>,
+    pub samples: Vec<Sample>,
+}
+
+impl Dataset {
+    pub fn num_classes(&self) -> usize {
+        self.class_names.len()
+    }
+
+    /// 加载 YOLO txt 格式标注
+    ///
+    /// `labels_dir` 下每张图片对应一个同名 `.txt`，每行为 `class cx cy w h`（归一化坐标）。
+    /// 缺少标注文件的图片视为没有目标。
+    pub fn from_yolo(
+        images_dir: impl AsRef<Path>,
+        labels_dir: impl AsRef<Path>,
+        class_names: &[&str],
+    ) -> Result<Self> {
+        let images_dir = images_dir.as_ref();
+        let labels_dir = labels_dir.as_ref();
+
+        let mut images: Vec<PathBuf> = fs::read_dir(images_dir)
+            .with_context(|| format!("Failed to read images dir: {:?}", images_dir))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|p| is_image(p))
+            .collect();
+        images.sort();
+
+        let mut samples = Vec::with_capacity(images.len());
+        for image in images {
+            let stem = image
+                .file_stem()
+                .ok_or_else(|| anyhow!("Invalid image path: {:?}", image))?;
+            let label = labels_dir.join(stem).with_extension("txt");
+            let ground_truths = if label.exists() {
+                let (width, height) = image::image_dimensions(&image)
+                    .with_context(|| format!("Failed to read image size: {:?}", image))?;
+                let content = fs::read_to_string(&label)
+                    .with_context(|| format!("Failed to read label file: {:?}", label))?;
+                parse_yolo_labels(&content, width as f32, height as f32)
+                    .with_context(|| format!("Invalid label file: {:?}", label))?
+            } else {
+                Vec::new()
+            };
+            samples.push(Sample {
+                image,
+                ground_truths,
+            });
+        }
+
+        Ok(Self {
+            class_names: class_names.iter().map(|s| s.to_string()).collect(),
+            samples,
+        })
+    }
+
+    /// 加载 COCO JSON 格式标注
+    ///
+    /// 类别按 `categories` 中 id 升序映射为 0..N 的连续索引，与 YOLO 模型输出保持一致；
+    /// `iscrowd` 标注作为忽略区域保留，见 [`GroundTruth::iscrowd`]。
+    pub fn from_coco(json_path: impl AsRef<Path>, images_dir: impl AsRef<Path>) -> Result<Self> {
+        let json_path = json_path.as_ref();
+        let content = fs::read_to_string(json_path)
+            .with_context(|| format!("Failed to read COCO json: {:?}", json_path))?;
+        let coco: CocoFile = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid COCO json: {:?}", json_path))?;
+        Ok(Self::from_coco_file(coco, images_dir.as_ref()))
+    }
+
+    fn from_coco_file(mut coco: CocoFile, images_dir: &Path) -> Self {
+        coco.categories.sort_by_key(|c| c.id);
+        let category_index: HashMap<u64, usize> = coco
+            .categories
+            .iter()
+            .enumerate()
+            .map(|(i, c)| (c.id, i))
+            .collect();
+
+        let mut per_image: HashMap<u64, Vec<GroundTruth>> = HashMap::new();
+        for ann in &coco.annotations {
+            let Some(&class_id) = category_index.get(&ann.category_id) else {
+                tracing::warn!(
+                    "Unknown category id {} in COCO annotations",
+                    ann.category_id
+                );
+                continue;
+            };
+            let [x, y, w, h] = ann.bbox;
+            per_image
+                .entry(ann.image_id)
+                .or_default()
+                .push(GroundTruth {
+                    class_id,
+                    xyxy: [x, y, x + w, y + h],
+                    iscrowd: ann.iscrowd != 0,
+                });
+        }
+
+        coco.images.sort_by_key(|i| i.id);
+        let samples = coco
+            .images
+            .iter()
+            .map(|img| Sample {
+                image: images_dir.join(&img.file_name),
+                ground_truths: per_image.remove(&img.id).unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            class_names: coco.categories.into_iter().map(|c| c.name).collect(),
+            samples,
+        }
+    }
+}
+
+/// 解析 YOLO txt 标注内容，返回像素坐标的真值框
+pub fn parse_yolo_labels(content: &str, width: f32, height: f32) -> Result<Vec<GroundTruth>> {
+    let mut ground_truths = Vec::new();
+    for (line_no, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 5 {
+            return Err(anyhow!(
+                "line {}: expected `class cx cy w h`, got {:?}",
+                line_no + 1,
+                line
+            ));
+        }
+        let class_id: usize = fields[0]
+            .parse()
+            .with_context(|| format!("line {}: invalid class id", line_no + 1))?;
+        let mut v = [0f32; 4];
+        for (k, field) in fields[1..5].iter().enumerate() {
+            v[k] = field
+                .parse()
+                .with_context(|| format!("line {}: invalid coordinate", line_no + 1))?;
+        }
+        let [cx, cy, w, h] = v;
+        ground_truths.push(GroundTruth {
+            class_id,
+            xyxy: [
+                (cx - w / 2.0) * width,
+                (cy - h / 2.0) * height,
+                (cx + w / 2.0) * width,
+                (cy + h / 2.0) * height,
+            ],
+            iscrowd: false,
+        });
+    }
+    Ok(ground_truths)
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| IMAGE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoFile {
+    images: Vec<CocoImage>,
+    #[serde(default)]
+    annotations: Vec<CocoAnnotation>,
+    categories: Vec<CocoCategory>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoImage {
+    id: u64,
+    file_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoAnnotation {
+    image_id: u64,
+    category_id: u64,
+    bbox: [f32; 4],
+    #[serde(default)]
+    iscrowd: u8,
+}
+
+#[derive(Debug, Deserialize)]
+struct CocoCategory {
+    id: u64,
+    name: String,
+}
@@ -0,0 +1,398 @@
+use serde::Serialize;
+use std::fmt;
+
+use super::{Detection, GroundTruth};
+use crate::utils::geometry::BoxF;
+use crate::utils::math::{greedy_match, iou_matrix, match_pairs_by_iou};
+
+/// COCO 评估使用的 IoU 阈值：0.50:0.05:0.95
+pub const COCO_IOU_THRESHOLDS: [f32; 10] = [0.5, 0.55, 0.6, 0.65, 0.7, 0.75, 0.8, 0.85, 0.9, 0.95];
+
+/// COCO 101 点插值使用的召回率采样点数
+const RECALL_POINTS: usize = 101;
+
+/// 单个类别在 IoU=0.5 下的 PR 曲线，按置信度降序排列
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PrCurve {
+    pub scores: Vec<f32>,
+    pub precision: Vec<f32>,
+    pub recall: Vec<f32>,
+}
+
+/// 单个类别的评估指标
+#[derive(Debug, Clone, Serialize)]
+pub struct ClassMetrics {
+    pub class_id: usize,
+    pub num_gts: usize,
+    pub num_preds: usize,
+    pub ap50: f32,
+    pub ap75: f32,
+    pub ap50_95: f32,
+    /// F1 最大处（IoU=0.5）的精确率
+    pub precision: f32,
+    /// F1 最大处（IoU=0.5）的召回率
+    pub recall: f32,
+    pub f1: f32,
+    pub pr_curve: PrCurve,
+}
+
+/// 混淆矩阵，行为预测类别、列为真实类别，最后一行/列为背景
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfusionMatrix {
+    pub num_classes: usize,
+    pub matrix: Vec<Vec<u64>>,
+}
+
+impl ConfusionMatrix {
+    pub fn new(num_classes: usize) -> Self {
+        Self {
+            num_classes,
+            matrix: vec![vec![0; num_classes + 1]; num_classes + 1],
+        }
+    }
+
+    /// 背景所在的行/列索引
+    pub fn background(&self) -> usize {
+        self.num_classes
+    }
+
+    /// 按 IoU 不区分类别地一对一匹配，统计单张图片的结果
+    pub fn update(
+        &mut self,
+        detections: &[Detection],
+        ground_truths: &[GroundTruth],
+        conf_threshold: f32,
+        iou_threshold: f32,
+    ) {
+        let detections: Vec<&Detection> = detections
+            .iter()
+            .filter(|d| d.score >= conf_threshold && d.class_id < self.num_classes)
+            .collect();
+        let crowds = crowd_regions(ground_truths.iter());
+        let ground_truths: Vec<&GroundTruth> = ground_truths
+            .iter()
+            .filter(|g| !g.iscrowd && g.class_id < self.num_classes)
+            .collect();
+
+        let preds: Vec<[f32; 4]> = detections.iter().map(|d| d.xyxy).collect();
+        let gts: Vec<[f32; 4]> = ground_truths.iter().map(|g| g.xyxy).collect();
+        let pairs = match_pairs_by_iou(&iou_matrix(&preds, &gts), iou_threshold);
+
+        let bg = self.background();
+        let mut pred_matched = vec![false; detections.len()];
+        let mut gt_matched = vec![false; ground_truths.len()];
+        for (i, j) in pairs {
+            pred_matched[i] = true;
+            gt_matched[j] = true;
+            self.matrix[detections[i].class_id][ground_truths[j].class_id] += 1;
+        }
+        for (j, g) in ground_truths.iter().enumerate() {
+            if !gt_matched[j] {
+                self.matrix[bg][g.class_id] += 1;
+            }
+        }
+        for (i, d) in detections.iter().enumerate() {
+            if !pred_matched[i] && !in_crowd(&d.xyxy, &crowds, iou_threshold) {
+                self.matrix[d.class_id][bg] += 1;
+            }
+        }
+    }
+
+    pub fn get(&self, pred: usize, truth: usize) -> u64 {
+        self.matrix[pred][truth]
+    }
+}
+
+/// 整体评估结果
+///
+/// 只有存在真值的类别会出现在 `per_class` 中并参与 mAP 计算。
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub per_class: Vec<ClassMetrics>,
+    pub map50: f32,
+    pub map75: f32,
+    pub map50_95: f32,
+    pub confusion: ConfusionMatrix,
+}
+
+impl EvalReport {
+    pub fn class(&self, class_id: usize) -> Option<&ClassMetrics> {
+        self.per_class.iter().find(|m| m.class_id == class_id)
+    }
+
+    /// 生成带类别名称的文本表格
+    pub fn table(&self, class_names: &[String]) -> String {
+        let name = |id: usize| {
+            class_names
+                .get(id)
+                .cloned()
+                .unwrap_or_else(|| format!("#{}", id))
+        };
+        let mut out = format!(
+            "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8} {:>10}\n",
+            "class", "gts", "preds", "P", "R", "AP50", "AP50-95"
+        );
+        for m in &self.per_class {
+            out.push_str(&format!(
+                "{:<20} {:>8} {:>8} {:>8.4} {:>8.4} {:>8.4} {:>10.4}\n",
+                name(m.class_id),
+                m.num_gts,
+                m.num_preds,
+                m.precision,
+                m.recall,
+                m.ap50,
+                m.ap50_95
+            ));
+        }
+        out.push_str(&format!(
+            "{:<20} {:>8} {:>8} {:>8} {:>8} {:>8.4} {:>10.4}\n",
+            "all", "", "", "", "", self.map50, self.map50_95
+        ));
+        out
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.table(&[]))
+    }
+}
+
+/// 单个预测框在各 IoU 阈值下的匹配结果
+#[derive(Debug, Clone, Copy)]
+struct Record {
+    score: f32,
+    tp: [bool; 10],
+    /// 未匹配但落在 crowd 区域内，不参与该阈值下的统计
+    ignored: [bool; 10],
+}
+
+/// 逐图累积预测与真值，最终计算 PR 曲线、AP 与混淆矩阵
+#[derive(Debug, Clone)]
+pub struct Evaluator {
+    num_classes: usize,
+    /// 每个类别的预测匹配记录
+    records: Vec<Vec<Record>>,
+    num_gts: Vec<usize>,
+    confusion: ConfusionMatrix,
+    confusion_conf: f32,
+    confusion_iou: f32,
+}
+
+impl Evaluator {
+    pub fn new(num_classes: usize) -> Self {
+        Self {
+            num_classes,
+            records: vec![Vec::new(); num_classes],
+            num_gts: vec![0; num_classes],
+            confusion: ConfusionMatrix::new(num_classes),
+            confusion_conf: 0.25,
+            confusion_iou: 0.45,
+        }
+    }
+
+    /// 设置混淆矩阵统计使用的置信度阈值与 IoU 阈值
+    pub fn with_confusion_thresholds(mut self, conf: f32, iou: f32) -> Self {
+        self.confusion_conf = conf;
+        self.confusion_iou = iou;
+        self
+    }
+
+    /// 累积单张图片的预测与真值
+    ///
+    /// 与 COCO 一致：预测先与普通真值匹配，未匹配的预测与同类 crowd 区域的交集
+    /// 占自身面积的比例不低于 IoU 阈值时忽略。
+    pub fn update(&mut self, detections: &[Detection], ground_truths: &[GroundTruth]) {
+        for class_id in 0..self.num_classes {
+            let mut preds: Vec<&Detection> = detections
+                .iter()
+                .filter(|d| d.class_id == class_id)
+                .collect();
+            let gts: Vec<[f32; 4]> = ground_truths
+                .iter()
+                .filter(|g| g.class_id == class_id && !g.iscrowd)
+                .map(|g| g.xyxy)
+                .collect();
+            let crowds = crowd_regions(ground_truths.iter().filter(|g| g.class_id == class_id));
+            self.num_gts[class_id] += gts.len();
+            if preds.is_empty() {
+                continue;
+            }
+
+            preds.sort_by(|a, b| b.score.total_cmp(&a.score));
+            let boxes: Vec<[f32; 4]> = preds.iter().map(|d| d.xyxy).collect();
+            let ious = iou_matrix(&boxes, &gts);
+
+            let mut records: Vec<Record> = preds
+                .iter()
+                .map(|d| Record {
+                    score: d.score,
+                    tp: [false; 10],
+                    ignored: [false; 10],
+                })
+                .collect();
+            for (t, &thr) in COCO_IOU_THRESHOLDS.iter().enumerate() {
+                for (i, m) in greedy_match(&ious, gts.len(), thr).into_iter().enumerate() {
+                    records[i].tp[t] = m.is_some();
+                    records[i].ignored[t] = m.is_none() && in_crowd(&boxes[i], &crowds, thr);
+                }
+            }
+            self.records[class_id].extend(records);
+        }
+
+        let out_of_range = detections
+            .iter()
+            .map(|d| d.class_id)
+            .chain(ground_truths.iter().map(|g| g.class_id))
+            .filter(|&c| c >= self.num_classes)
+            .count();
+        if out_of_range > 0 {
+            tracing::warn!(
+                "{} boxes have class id >= num_classes ({}), ignored",
+                out_of_range,
+                self.num_classes
+            );
+        }
+
+        self.confusion.update(
+            detections,
+            ground_truths,
+            self.confusion_conf,
+            self.confusion_iou,
+        );
+    }
+
+    /// 计算最终指标
+    pub fn compute(&self) -> EvalReport {
+        let per_class: Vec<ClassMetrics> = (0..self.num_classes)
+            .filter(|&c| self.num_gts[c] > 0)
+            .map(|c| self.class_metrics(c))
+            .collect();
+
+        let mean = |f: fn(&ClassMetrics) -> f32| {
+            if per_class.is_empty() {
+                0.0
+            } else {
+                per_class.iter().map(f).sum::<f32>() / per_class.len() as f32
+            }
+        };
+
+        EvalReport {
+            map50: mean(|m| m.ap50),
+            map75: mean(|m| m.ap75),
+            map50_95: mean(|m| m.ap50_95),
+            per_class,
+            confusion: self.confusion.clone(),
+        }
+    }
+
+    fn class_metrics(&self, class_id: usize) -> ClassMetrics {
+        let num_gts = self.num_gts[class_id];
+        let mut records = self.records[class_id].clone();
+        records.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+        let mut aps = [0f32; 10];
+        let mut pr_curve = PrCurve::default();
+        for (t, ap) in aps.iter_mut().enumerate() {
+            let kept = || records.iter().filter(move |r| !r.ignored[t]);
+            let (precision, recall) = precision_recall(kept().map(|r| r.tp[t]), num_gts);
+            *ap = average_precision(&recall, &precision);
+            if t == 0 {
+                pr_curve = PrCurve {
+                    scores: kept().map(|r| r.score).collect(),
+                    precision,
+                    recall,
+                };
+            }
+        }
+
+        let (precision, recall, f1) = pr_curve
+            .precision
+            .iter()
+            .zip(&pr_curve.recall)
+            .map(|(&p, &r)| {
+                let f1 = if p + r > 0.0 {
+                    2.0 * p * r / (p + r)
+                } else {
+                    0.0
+                };
+                (p, r, f1)
+            })
+            .fold(
+                (0.0, 0.0, 0.0),
+                |best, cur| if cur.2 > best.2 { cur } else { best },
+            );
+
+        ClassMetrics {
+            class_id,
+            num_gts,
+            num_preds: pr_curve.scores.len(),
+            ap50: aps[0],
+            ap75: aps[5],
+            ap50_95: aps.iter().sum::<f32>() / aps.len() as f32,
+            precision,
+            recall,
+            f1,
+            pr_curve,
+        }
+    }
+}
+
+fn crowd_regions<'a>(ground_truths: impl Iterator<Item = &'a GroundTruth>) -> Vec<BoxF> {
+    ground_truths
+        .filter(|g| g.iscrowd)
+        .map(|g| BoxF::from_xyxy(g.xyxy))
+        .collect()
+}
+
+/// 预测框与任一 crowd 区域的交集占预测框面积之比不低于阈值（COCO 对 crowd 的 IoU 定义）
+fn in_crowd(xyxy: &[f32; 4], crowds: &[BoxF], threshold: f32) -> bool {
+    let b = BoxF::from_xyxy(*xyxy);
+    let area = b.area();
+    area > 0.0
+        && crowds
+            .iter()
+            .any(|c| b.intersection_area(c) / area >= threshold)
+}
+
+/// 根据按置信度降序排列的 TP 标记计算累积精确率与召回率
+pub fn precision_recall(tps: impl Iterator<Item = bool>, num_gts: usize) -> (Vec<f32>, Vec<f32>) {
+    let mut tp = 0usize;
+    let mut precision = Vec::new();
+    let mut recall = Vec::new();
+    for (i, is_tp) in tps.enumerate() {
+        if is_tp {
+            tp += 1;
+        }
+        precision.push(tp as f32 / (i + 1) as f32);
+        recall.push(if num_gts == 0 {
+            0.0
+        } else {
+            tp as f32 / num_gts as f32
+        });
+    }
+    (precision, recall)
+}
+
+/// COCO 风格 101 点插值 AP
+///
+/// 先将精确率做单调递减包络，再在 0.00..=1.00 的召回率采样点上取值求平均。
+pub fn average_precision(recall: &[f32], precision: &[f32]) -> f32 {
+    if recall.is_empty() {
+        return 0.0;
+    }
+
+    let mut envelope = precision.to_vec();
+    for i in (0..envelope.len() - 1).rev() {
+        envelope[i] = envelope[i].max(envelope[i + 1]);
+    }
+
+    let sum: f32 = (0..RECALL_POINTS)
+        .map(|k| {
+            let r = k as f32 / (RECALL_POINTS - 1) as f32;
+            let idx = recall.partition_point(|&x| x < r);
+            envelope.get(idx).copied().unwrap_or(0.0)
+        })
+        .sum();
+    sum / RECALL_POINTS as f32
+}
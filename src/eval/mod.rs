@@ -0,0 +1,39 @@
+pub mod dataset;
+pub mod metrics;
+pub mod parity;
+pub mod runner;
+
+pub use dataset::{Dataset, Sample};
+pub use metrics::{ClassMetrics, ConfusionMatrix, EvalReport, Evaluator, PrCurve};
+pub use parity::{Goldens, ParityReport, ParityTolerance};
+
+use serde::{Deserialize, Serialize};
+
+use crate::postprocess;
+
+/// 真值框，坐标为像素 xyxy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroundTruth {
+    pub class_id: usize,
+    pub xyxy: [f32; 4],
+    /// COCO crowd 区域：不计入真值数量，落在其中未匹配的预测既不算 TP 也不算 FP
+    pub iscrowd: bool,
+}
+
+/// 模型预测框，坐标为像素 xyxy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Detection {
+    pub class_id: usize,
+    pub score: f32,
+    pub xyxy: [f32; 4],
+}
+
+impl From<&postprocess::Detection> for Detection {
+    fn from(d: &postprocess::Detection) -> Self {
+        Self {
+            class_id: d.class_id,
+            score: d.score,
+            xyxy: d.bbox.xyxy(),
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use anyhow::{anyhow, Context, Result};
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::Detection;
+use crate::backend::InferenceBackend;
+use crate::postprocess::detections_from_y;
+use crate::utils::math::calculate_iou_xyxy;
+
+/// 设置后 [`check_goldens`] 用当前输出覆盖金标准文件
+pub const UPDATE_GOLDENS_ENV: &str = "UPDATE_GOLDENS";
+
+/// 输出比对容差
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParityTolerance {
+    /// 同类别的两个框 IoU 达到该值才视为同一目标
+    pub match_iou: f32,
+    /// 已配对的框 IoU 低于该值记为漂移
+    pub iou: f32,
+    /// 已配对的框置信度差超过该值记为漂移
+    pub score: f32,
+    /// 置信度低于该值的未配对框只作提示，不算失败，避免阈值附近的框在不同后端间时有时无
+    pub ignore_below: f32,
+}
+
+impl Default for ParityTolerance {
+    fn default() -> Self {
+        Self {
+            match_iou: 0.5,
+            iou: 0.9,
+            score: 0.05,
+            ignore_below: 0.3,
+        }
+    }
+}
+
+/// 按图片文件名记录的检测结果，序列化为金标准 JSON
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Goldens {
+    pub images: BTreeMap<String, Vec<Detection>>,
+}
+
+impl Goldens {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read goldens: {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| format!("Invalid goldens: {:?}", path))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write goldens: {:?}", path))
+    }
+}
+
+/// 用模型跑一组图片，结果按文件名索引
+pub fn collect(model: &mut dyn InferenceBackend, images: &[impl AsRef<Path>]) -> Result<Goldens> {
+    let mut goldens = Goldens::default();
+    for chunk in images.chunks(model.batch().max(1)) {
+        let xs: Vec<DynamicImage> = chunk
+            .iter()
+            .map(|p| {
+                image::open(p.as_ref()).with_context(|| format!("Failed to open {:?}", p.as_ref()))
+            })
+            .collect::<Result<_>>()?;
+        let ys = model.forward(&xs)?;
+        for (path, y) in chunk.iter().zip(ys.iter()) {
+            let name = path
+                .as_ref()
+                .file_name()
+                .ok_or_else(|| anyhow!("Invalid image path: {:?}", path.as_ref()))?
+                .to_string_lossy()
+                .into_owned();
+            let detections = detections_from_y(y).iter().map(Detection::from).collect();
+            goldens.images.insert(name, detections);
+        }
+    }
+    Ok(goldens)
+}
+
+/// 一对配对成功的检测框
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatchedPair {
+    pub expected: Detection,
+    pub actual: Detection,
+    pub iou: f32,
+}
+
+impl MatchedPair {
+    pub fn drifted(&self, tolerance: &ParityTolerance) -> bool {
+        self.iou < tolerance.iou
+            || (self.expected.score - self.actual.score).abs() > tolerance.score
+    }
+}
+
+/// 单张图片的比对结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImageDiff {
+    pub image: String,
+    pub matched: Vec<MatchedPair>,
+    /// 期望中有、实际输出中没有
+    pub missing: Vec<Detection>,
+    /// 实际输出中多出的
+    pub extra: Vec<Detection>,
+}
+
+impl ImageDiff {
+    pub fn drifted<'a>(
+        &'a self,
+        tolerance: &'a ParityTolerance,
+    ) -> impl Iterator<Item = &'a MatchedPair> + 'a {
+        self.matched.iter().filter(|m| m.drifted(tolerance))
+    }
+
+    /// 超出容差的差异数
+    pub fn failures(&self, tolerance: &ParityTolerance) -> usize {
+        let significant = |d: &&Detection| d.score >= tolerance.ignore_below;
+        self.drifted(tolerance).count()
+            + self.missing.iter().filter(significant).count()
+            + self.extra.iter().filter(significant).count()
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.missing.is_empty()
+            && self.extra.is_empty()
+            && self.matched.iter().all(|m| m.expected == m.actual)
+    }
+}
+
+/// 按类别贪心配对：期望框按置信度从高到低，依次取 IoU 最大且未配对的实际框
+pub fn match_detections(
+    expected: &[Detection],
+    actual: &[Detection],
+    tolerance: &ParityTolerance,
+) -> ImageDiff {
+    let mut order: Vec<usize> = (0..expected.len()).collect();
+    order.sort_by(|&a, &b| expected[b].score.total_cmp(&expected[a].score));
+
+    let mut used = vec![false; actual.len()];
+    let mut diff = ImageDiff::default();
+    for i in order {
+        let e = expected[i];
+        let best = actual
+            .iter()
+            .enumerate()
+            .filter(|(j, a)| !used[*j] && a.class_id == e.class_id)
+            .map(|(j, a)| (j, calculate_iou_xyxy(&e.xyxy, &a.xyxy)))
+            .filter(|&(_, iou)| iou >= tolerance.match_iou)
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((j, iou)) => {
+                used[j] = true;
+                diff.matched.push(MatchedPair {
+                    expected: e,
+                    actual: actual[j],
+                    iou,
+                });
+            }
+            None => diff.missing.push(e),
+        }
+    }
+    diff.extra = actual
+        .iter()
+        .zip(used)
+        .filter(|(_, u)| !u)
+        .map(|(a, _)| *a)
+        .collect();
+    diff
+}
+
+/// 两组输出的比对报告
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParityReport {
+    pub tolerance: ParityTolerance,
+    pub images: Vec<ImageDiff>,
+    /// 只出现在期望中的图片
+    pub missing_images: Vec<String>,
+    /// 只出现在实际输出中的图片
+    pub extra_images: Vec<String>,
+}
+
+impl ParityReport {
+    pub fn failures(&self) -> usize {
+        self.images
+            .iter()
+            .map(|d| d.failures(&self.tolerance))
+            .sum::<usize>()
+            + self.missing_images.len()
+            + self.extra_images.len()
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+/// 比较两组输出，可以是金标准与当前模型，也可以是两个后端或两个模型版本
+pub fn compare(expected: &Goldens, actual: &Goldens, tolerance: ParityTolerance) -> ParityReport {
+    let mut report = ParityReport {
+        tolerance,
+        images: Vec::new(),
+        missing_images: Vec::new(),
+        extra_images: Vec::new(),
+    };
+    for (name, e) in &expected.images {
+        match actual.images.get(name) {
+            Some(a) => {
+                let mut diff = match_detections(e, a, &tolerance);
+                diff.image = name.clone();
+                report.images.push(diff);
+            }
+            None => report.missing_images.push(name.clone()),
+        }
+    }
+    report.extra_images = actual
+        .images
+        .keys()
+        .filter(|k| !expected.images.contains_key(*k))
+        .cloned()
+        .collect();
+    report
+}
+
+/// 在同一组图片上运行两个后端并比较输出，`expected` 作为基准
+pub fn compare_backends(
+    expected: &mut dyn InferenceBackend,
+    actual: &mut dyn InferenceBackend,
+    images: &[impl AsRef<Path>],
+    tolerance: ParityTolerance,
+) -> Result<ParityReport> {
+    Ok(compare(
+        &collect(expected, images)?,
+        &collect(actual, images)?,
+        tolerance,
+    ))
+}
+
+/// 与金标准文件比较；设置了 `UPDATE_GOLDENS` 环境变量时改为写入金标准
+pub fn check_goldens(
+    path: impl AsRef<Path>,
+    actual: &Goldens,
+    tolerance: ParityTolerance,
+) -> Result<ParityReport> {
+    let path = path.as_ref();
+    if std::env::var_os(UPDATE_GOLDENS_ENV).is_some() {
+        actual.save(path)?;
+        tracing::info!("Updated goldens: {:?}", path);
+        return Ok(compare(actual, actual, tolerance));
+    }
+    if !path.exists() {
+        return Err(anyhow!(
+            "Goldens not found: {:?}, rerun with {}=1 to create them",
+            path,
+            UPDATE_GOLDENS_ENV
+        ));
+    }
+    Ok(compare(&Goldens::load(path)?, actual, tolerance))
+}
+
+fn fmt_detection(d: &Detection) -> String {
+    let [x1, y1, x2, y2] = d.xyxy;
+    format!(
+        "class {:<3} score {:.3} [{:.1}, {:.1}, {:.1}, {:.1}]",
+        d.class_id, d.score, x1, y1, x2, y2
+    )
+}
+
+/// 只列出有差异的图片：`~` 漂移，`-` 缺失，`+` 多出，低于 `ignore_below` 的框标注 (ignored)
+impl fmt::Display for ParityReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tol = &self.tolerance;
+        let note = |d: &Detection| {
+            if d.score < tol.ignore_below {
+                " (ignored)"
+            } else {
+                ""
+            }
+        };
+        for diff in &self.images {
+            let drifted: Vec<&MatchedPair> = diff.drifted(tol).collect();
+            if drifted.is_empty() && diff.missing.is_empty() && diff.extra.is_empty() {
+                continue;
+            }
+            writeln!(
+                f,
+                "{}: {} matched, {} drifted, {} missing, {} extra",
+                diff.image,
+                diff.matched.len(),
+                drifted.len(),
+                diff.missing.len(),
+                diff.extra.len()
+            )?;
+            for m in drifted {
+                let [x1, y1, x2, y2] = m.actual.xyxy;
+                writeln!(
+                    f,
+                    "  ~ {} -> score {:.3} [{:.1}, {:.1}, {:.1}, {:.1}] iou {:.3}",
+                    fmt_detection(&m.expected),
+                    m.actual.score,
+                    x1,
+                    y1,
+                    x2,
+                    y2,
+                    m.iou
+                )?;
+            }
+            for d in &diff.missing {
+                writeln!(f, "  - {}{}", fmt_detection(d), note(d))?;
+            }
+            for d in &diff.extra {
+                writeln!(f, "  + {}{}", fmt_detection(d), note(d))?;
+            }
+        }
+        for name in &self.missing_images {
+            writeln!(f, "{}: missing from actual outputs", name)?;
+        }
+        for name in &self.extra_images {
+            writeln!(f, "{}: not in expected outputs", name)?;
+        }
+        write!(
+            f,
+            "{} images, {} failures (match iou {}, iou {}, score ±{})",
+            self.images.len(),
+            self.failures(),
+            tol.match_iou,
+            tol.iou,
+            tol.score
+        )
+    }
+}
@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use image::DynamicImage;
+
+use super::{Dataset, Detection, EvalReport, Evaluator};
+use crate::backend::InferenceBackend;
+use crate::postprocess::detections_from_y;
+
+/// 在数据集上运行模型并计算评估指标
+pub fn evaluate(
+    model: &mut dyn InferenceBackend,
+    dataset: &Dataset,
+    batch_size: usize,
+) -> Result<EvalReport> {
+    let mut evaluator = Evaluator::new(dataset.num_classes());
+
+    for (n, chunk) in dataset.samples.chunks(batch_size.max(1)).enumerate() {
+        let xs: Vec<DynamicImage> = chunk
+            .iter()
+            .map(|s| image::open(&s.image).with_context(|| format!("Failed to open {:?}", s.image)))
+            .collect::<Result<_>>()?;
+        let ys = model.forward(&xs)?;
+
+        for (sample, y) in chunk.iter().zip(ys.iter()) {
+            let detections: Vec<Detection> =
+                detections_from_y(y).iter().map(Detection::from).collect();
+            evaluator.update(&detections, &sample.ground_truths);
+        }
+        tracing::debug!("evaluated batch {} ({} images)", n, chunk.len());
+    }
+
+    Ok(evaluator.compute())
+}
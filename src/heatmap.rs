@@ -0,0 +1,415 @@
+use anyhow::{anyhow, Context, Result};
+use image::{imageops, DynamicImage, GrayImage, Luma, RgbImage};
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use usls::Y;
+
+use crate::utils::geometry::{Anchor, BoxF};
+
+/// 热力图的累加来源
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeatmapSource {
+    /// 检测框锚点，按高斯核扩散
+    #[default]
+    Anchor,
+    /// 检测框覆盖的区域
+    Box,
+    /// 分割掩码覆盖的区域
+    Mask,
+}
+
+/// 历史数据的衰减方式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HeatmapDecay {
+    /// 不衰减，一直累加
+    None,
+    /// 指数衰减，经过 half_life_secs 秒权重减半
+    Exponential { half_life_secs: f32 },
+    /// 固定时间窗口，每个窗口结束后清零
+    Window { secs: f32 },
+}
+
+impl Default for HeatmapDecay {
+    fn default() -> Self {
+        Self::Exponential {
+            half_life_secs: 300.0,
+        }
+    }
+}
+
+impl HeatmapDecay {
+    /// 检查时长为有限正数，且能表示为 [`Duration`]
+    pub fn validate(&self) -> Result<()> {
+        let secs = match *self {
+            Self::None => return Ok(()),
+            Self::Exponential { half_life_secs } => half_life_secs,
+            Self::Window { secs } => secs,
+        };
+        if secs > 0.0 && Duration::try_from_secs_f32(secs).is_ok() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Invalid heatmap decay {:?}: duration must be a finite number of seconds > 0",
+                self
+            ))
+        }
+    }
+}
+
+/// 热力图配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HeatmapConfig {
+    /// 网格大小 [宽, 高]，与画面分辨率无关
+    pub grid: [u32; 2],
+    pub source: HeatmapSource,
+    /// source 为 anchor 时使用的锚点
+    pub anchor: Anchor,
+    /// source 为 anchor 时高斯核的半径（网格单位），0 表示只累加所在网格
+    pub radius: f32,
+    pub decay: HeatmapDecay,
+    /// 参与统计的类别名，与 class_ids 都为空时统计全部类别
+    pub classes: Vec<String>,
+    pub class_ids: Vec<usize>,
+    /// 是否在输出画面上叠加热力图
+    pub overlay: bool,
+    /// 叠加的最大不透明度，0..=1
+    pub overlay_alpha: f32,
+    /// 定期保存快照的间隔（秒），不设置时不保存
+    pub snapshot_interval_secs: Option<u64>,
+    /// 快照保存目录
+    pub output_dir: String,
+    /// 保存快照时是否同时导出原始网格（.npy, float32）
+    pub export_raw: bool,
+}
+
+impl Default for HeatmapConfig {
+    fn default() -> Self {
+        Self {
+            grid: [160, 90],
+            source: HeatmapSource::Anchor,
+            anchor: Anchor::BottomCenter,
+            radius: 2.0,
+            decay: HeatmapDecay::default(),
+            classes: vec![],
+            class_ids: vec![],
+            overlay: true,
+            overlay_alpha: 0.5,
+            snapshot_interval_secs: None,
+            output_dir: "heatmaps".to_string(),
+            export_raw: true,
+        }
+    }
+}
+
+/// 单路视频流的热力图累加器
+pub struct Heatmap {
+    config: HeatmapConfig,
+    name: String,
+    grid: Vec<f32>,
+    last_update: Option<Instant>,
+    window_start: Option<Instant>,
+    last_snapshot: Option<Instant>,
+}
+
+impl Heatmap {
+    pub fn new(config: HeatmapConfig, name: &str) -> Result<Self> {
+        config.decay.validate()?;
+        let [w, h] = config.grid;
+        Ok(Self {
+            grid: vec![0.0; (w * h) as usize],
+            config,
+            name: name.to_string(),
+            last_update: None,
+            window_start: None,
+            last_snapshot: None,
+        })
+    }
+
+    pub fn config(&self) -> &HeatmapConfig {
+        &self.config
+    }
+
+    /// 网格大小 (宽, 高)
+    pub fn dims(&self) -> (u32, u32) {
+        (self.config.grid[0], self.config.grid[1])
+    }
+
+    /// 原始网格，行优先
+    pub fn grid(&self) -> &[f32] {
+        &self.grid
+    }
+
+    pub fn reset(&mut self) {
+        self.grid.fill(0.0);
+    }
+
+    fn is_target(&self, id: isize, name: Option<&str>) -> bool {
+        let c = &self.config;
+        (c.classes.is_empty() && c.class_ids.is_empty())
+            || (id >= 0 && c.class_ids.contains(&(id as usize)))
+            || name.is_some_and(|n| c.classes.iter().any(|x| x == n))
+    }
+
+    /// 累加一批帧，`sizes[i]` 为第 i 帧的 (宽, 高)
+    pub fn update(&mut self, ys: &[Y], sizes: &[(u32, u32)]) {
+        self.update_at(ys, sizes, Instant::now());
+    }
+
+    pub fn update_at(&mut self, ys: &[Y], sizes: &[(u32, u32)], now: Instant) {
+        self.decay(now);
+        for (y, &(w, h)) in ys.iter().zip(sizes) {
+            self.add(y, w, h);
+        }
+    }
+
+    /// 按距上次更新的时间衰减，固定窗口到期时清零
+    fn decay(&mut self, now: Instant) {
+        match self.config.decay {
+            HeatmapDecay::None => {}
+            HeatmapDecay::Exponential { half_life_secs } => {
+                if let Some(last) = self.last_update {
+                    let dt = now.duration_since(last).as_secs_f32();
+                    let factor = 0.5f32.powf(dt / half_life_secs);
+                    self.grid.iter_mut().for_each(|v| *v *= factor);
+                }
+            }
+            HeatmapDecay::Window { secs } => {
+                let start = *self.window_start.get_or_insert(now);
+                if now.duration_since(start) >= Duration::from_secs_f32(secs) {
+                    self.reset();
+                    self.window_start = Some(now);
+                }
+            }
+        }
+        self.last_update = Some(now);
+    }
+
+    /// 累加单帧的检测结果
+    pub fn add(&mut self, y: &Y, width: u32, height: u32) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let (gw, gh) = self.dims();
+        let (sx, sy) = (gw as f32 / width as f32, gh as f32 / height as f32);
+
+        match self.config.source {
+            HeatmapSource::Anchor | HeatmapSource::Box => {
+                for b in y.bboxes().unwrap_or_default() {
+                    if !self.is_target(b.id(), b.name().map(|n| n.as_str())) {
+                        continue;
+                    }
+                    let b = BoxF::new(b.xmin(), b.ymin(), b.xmax(), b.ymax()).scale(sx, sy);
+                    if self.config.source == HeatmapSource::Anchor {
+                        self.splat(b.anchor(self.config.anchor));
+                    } else {
+                        self.fill_box(&b);
+                    }
+                }
+            }
+            HeatmapSource::Mask => {
+                for m in y.masks().unwrap_or_default() {
+                    if self.is_target(m.id(), m.name().map(|n| n.as_str())) {
+                        self.fill_mask(m.mask());
+                    }
+                }
+            }
+        }
+    }
+
+    /// 以高斯核在网格上累加一个点（网格坐标）
+    fn splat(&mut self, (x, y): (f32, f32)) {
+        let (gw, gh) = self.dims();
+        let r = self.config.radius;
+        if r <= 0.0 {
+            if x >= 0.0 && y >= 0.0 && (x as u32) < gw && (y as u32) < gh {
+                self.grid[(y as u32 * gw + x as u32) as usize] += 1.0;
+            }
+            return;
+        }
+        let sigma = r / 2.0;
+        let (x1, x2) = (
+            (x - r).floor().max(0.0) as u32,
+            ((x + r).ceil() as u32).min(gw),
+        );
+        let (y1, y2) = (
+            (y - r).floor().max(0.0) as u32,
+            ((y + r).ceil() as u32).min(gh),
+        );
+        for gy in y1..y2 {
+            for gx in x1..x2 {
+                let dx = gx as f32 + 0.5 - x;
+                let dy = gy as f32 + 0.5 - y;
+                let d2 = dx * dx + dy * dy;
+                if d2 <= r * r {
+                    self.grid[(gy * gw + gx) as usize] += (-d2 / (2.0 * sigma * sigma)).exp();
+                }
+            }
+        }
+    }
+
+    /// 框覆盖的网格按覆盖比例累加（网格坐标）
+    fn fill_box(&mut self, b: &BoxF) {
+        let (gw, gh) = self.dims();
+        let b = b.clip(gw as f32, gh as f32);
+        for gy in b.y1.floor() as u32..(b.y2.ceil() as u32).min(gh) {
+            for gx in b.x1.floor() as u32..(b.x2.ceil() as u32).min(gw) {
+                let cell = BoxF::new(gx as f32, gy as f32, gx as f32 + 1.0, gy as f32 + 1.0);
+                self.grid[(gy * gw + gx) as usize] += b.intersection_area(&cell);
+            }
+        }
+    }
+
+    /// 掩码按每个网格内前景像素的比例累加
+    fn fill_mask(&mut self, mask: &GrayImage) {
+        let (gw, gh) = self.dims();
+        let (mw, mh) = mask.dimensions();
+        if mw == 0 || mh == 0 {
+            return;
+        }
+        let mut counts = vec![0u32; self.grid.len()];
+        for (x, y, p) in mask.enumerate_pixels() {
+            if p[0] > 0 {
+                let gx = (x as u64 * gw as u64 / mw as u64) as u32;
+                let gy = (y as u64 * gh as u64 / mh as u64) as u32;
+                counts[(gy * gw + gx) as usize] += 1;
+            }
+        }
+        let cell_pixels = (mw as f32 / gw as f32) * (mh as f32 / gh as f32);
+        for (v, &c) in self.grid.iter_mut().zip(&counts) {
+            *v += c as f32 / cell_pixels;
+        }
+    }
+
+    /// 归一化到 0..=1 的网格
+    pub fn normalized(&self) -> Vec<f32> {
+        let max = self.grid.iter().cloned().fold(0.0f32, f32::max);
+        if max <= 0.0 {
+            return vec![0.0; self.grid.len()];
+        }
+        self.grid.iter().map(|v| v / max).collect()
+    }
+
+    /// 放大到指定分辨率的灰度图（0..=255）
+    fn intensity(&self, width: u32, height: u32) -> GrayImage {
+        let (gw, gh) = self.dims();
+        let small = GrayImage::from_fn(gw, gh, {
+            let norm = self.normalized();
+            move |x, y| Luma([(norm[(y * gw + x) as usize] * 255.0).round() as u8])
+        });
+        imageops::resize(&small, width, height, imageops::FilterType::Triangle)
+    }
+
+    /// 将热力图半透明叠加到画面上，越热越不透明
+    pub fn blend(&self, img: &mut RgbImage, max_alpha: f32) {
+        let intensity = self.intensity(img.width(), img.height());
+        let max_alpha = max_alpha.clamp(0.0, 1.0);
+        for (p, v) in img.pixels_mut().zip(intensity.pixels()) {
+            let v = v[0];
+            if v == 0 {
+                continue;
+            }
+            let a = max_alpha * v as f32 / 255.0;
+            for (c, h) in p.0.iter_mut().zip(colormap(v)) {
+                *c = (*c as f32 * (1.0 - a) + h as f32 * a).round() as u8;
+            }
+        }
+    }
+
+    /// 为一批输出帧叠加热力图
+    pub fn apply(&self, xs: Vec<DynamicImage>) -> Vec<DynamicImage> {
+        xs.into_par_iter()
+            .map(|x| {
+                let mut img = match x {
+                    DynamicImage::ImageRgb8(img) => img,
+                    x => x.to_rgb8(),
+                };
+                self.blend(&mut img, self.config.overlay_alpha);
+                DynamicImage::ImageRgb8(img)
+            })
+            .collect()
+    }
+
+    /// 以背景帧生成快照
+    pub fn snapshot(&self, background: &DynamicImage) -> RgbImage {
+        let mut img = background.to_rgb8();
+        self.blend(&mut img, self.config.overlay_alpha.max(0.6));
+        img
+    }
+
+    /// 到达快照间隔时保存 PNG 快照（以及原始网格），返回保存的 PNG 路径
+    pub fn maybe_snapshot(&mut self, background: &DynamicImage) -> Result<Option<PathBuf>> {
+        self.maybe_snapshot_at(background, Instant::now())
+    }
+
+    pub fn maybe_snapshot_at(
+        &mut self,
+        background: &DynamicImage,
+        now: Instant,
+    ) -> Result<Option<PathBuf>> {
+        let Some(interval) = self.config.snapshot_interval_secs else {
+            return Ok(None);
+        };
+        let last = *self.last_snapshot.get_or_insert(now);
+        if now.duration_since(last) < Duration::from_secs(interval) {
+            return Ok(None);
+        }
+        self.last_snapshot = Some(now);
+
+        let dir = Path::new(&self.config.output_dir);
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create heatmap output dir: {:?}", dir))?;
+        let stem = format!(
+            "{}_{}",
+            self.name,
+            chrono::Local::now().format("%Y%m%d_%H%M%S")
+        );
+        let png = dir.join(format!("{}.png", stem));
+        self.snapshot(background)
+            .save(&png)
+            .with_context(|| format!("Failed to save heatmap snapshot: {:?}", png))?;
+        if self.config.export_raw {
+            self.save_npy(dir.join(format!("{}.npy", stem)))?;
+        }
+        Ok(Some(png))
+    }
+
+    /// 原始网格导出为 NumPy .npy（float32，形状为 (高, 宽)）
+    pub fn save_npy(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_npy())
+            .with_context(|| format!("Failed to write heatmap grid: {:?}", path))
+    }
+
+    pub fn to_npy(&self) -> Vec<u8> {
+        let (gw, gh) = self.dims();
+        let mut header = format!(
+            "{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            gh, gw
+        );
+        // 魔数(6) + 版本(2) + 头长度(2) + 头，总长对齐到 64 字节，以换行结尾
+        let total = (10 + header.len() + 1).div_ceil(64) * 64;
+        header.push_str(&" ".repeat(total - 10 - header.len() - 1));
+        header.push('\n');
+
+        let mut bytes = Vec::with_capacity(total + self.grid.len() * 4);
+        bytes.extend_from_slice(b"\x93NUMPY\x01\x00");
+        bytes.extend_from_slice(&(header.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(header.as_bytes());
+        for v in &self.grid {
+            bytes.extend_from_slice(&v.to_le_bytes());
+        }
+        bytes
+    }
+}
+
+/// Jet 色带，0 为蓝色，255 为红色
+pub fn colormap(v: u8) -> [u8; 3] {
+    let t = v as f32 / 255.0;
+    let channel = |offset: f32| ((1.5 - (4.0 * t - offset).abs()).clamp(0.0, 1.0) * 255.0) as u8;
+    [channel(3.0), channel(2.0), channel(1.0)]
+}
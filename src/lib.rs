@@ -1,2 +1,17 @@
+pub mod annotation;
 pub mod args;
+pub mod backend;
+pub mod config;
+pub mod ensemble;
+pub mod eval;
+pub mod heatmap;
+pub mod misc;
+pub mod postprocess;
+pub mod privacy_mask;
+pub mod redaction;
+pub mod stitching;
+pub mod tiling;
+pub mod tracker;
+pub mod trajectory;
+pub mod tta;
 pub mod utils;
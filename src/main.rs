@@ -8,11 +8,22 @@ use tokio::sync::mpsc;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
-use cv_convert::TryFromCv;
 use rsmedia::hwaccel::HWDeviceType;
-use rsmedia::{EncoderBuilder, Options, RawFrame};
-use usls::{models::YOLO, Annotator, DataLoader, Device};
+use rsmedia::{EncoderBuilder, Options};
+use rsmpeg::avutil::AVFrame;
+use usls::{DataLoader, Device};
+use yolo_vision::annotation::{Annotator, FpsMeter, Overlay, OverlayStats};
 use yolo_vision::args;
+use yolo_vision::ensemble::Ensemble;
+use yolo_vision::heatmap::Heatmap;
+use yolo_vision::misc::av_convert::{FrameConverter, PixelFormat, RgbConverter};
+use yolo_vision::misc::avio::FrameReader;
+use yolo_vision::privacy_mask::PrivacyMask;
+use yolo_vision::redaction::Redactor;
+use yolo_vision::stitching::{PanoramaStitcher, StitchedBatches};
+use yolo_vision::tiling::TiledInference;
+use yolo_vision::trajectory::{FrameClock, Trajectories};
+use yolo_vision::tta::Tta;
 
 /// run: RUST_LOG=debug cargo run -- --source 'rtmp://172.24.82.44/live/livestream1' \
 /// --output 'rtmp://172.24.82.44/live/livestream_dev' \
@@ -27,25 +38,124 @@ async fn main() -> Result<()> {
         .with_thread_ids(true)
         .init();
 
-    let options = args::build_options()?;
-
     // 将model包装在Arc<Mutex>中以支持可变访问
-    let model = Arc::new(Mutex::new(YOLO::try_from(options.commit()?)?));
+    let model = Arc::new(Mutex::new(args::build_backend()?));
+
+    let stream_config = args::stream_config()?;
+
+    // 切片推理（可选）
+    let tiler = args::tile_config()?.map(TiledInference::new);
+
+    // 测试时增强（可选）
+    let mut tta = args::tta_config()?.map(Tta::new).transpose()?;
+
+    // 静态隐私遮挡区域（可选）
+    let privacy_mask = stream_config.privacy_masks.clone().map(PrivacyMask::new);
+
+    // 隐私打码（可选）与标注开关
+    let redactor = stream_config.redaction.clone().map(Redactor::new);
+    let annotate = args::annotate();
+
+    // 多模型融合（可选）
+    let mut ensemble = stream_config
+        .ensemble
+        .clone()
+        .map(Ensemble::new)
+        .transpose()?;
 
-    // build dataloader
-    let dl = DataLoader::new(&args::input_source())?
-        .with_batch(model.lock().batch() as _)
-        .with_device(Device::Cuda(0))
-        .build()?;
+    // build dataloader，配置了多路拼接时每路一个 DataLoader，逐帧拼接成一路全景流
+    let batch = model.lock().batch();
+    // 每批的 RGB 帧（推理和标注用），以及 --native_decode 时对应的解码器原生帧
+    let batches: Box<dyn Iterator<Item = (Vec<DynamicImage>, Option<Vec<AVFrame>>)>> =
+        match &stream_config.stitching {
+            Some(cfg) => {
+                let sources = cfg
+                    .sources
+                    .iter()
+                    .map(|source| {
+                        Ok(DataLoader::new(source)?
+                            .with_batch(batch)
+                            .with_device(Device::Cuda(0))
+                            .build()?
+                            .into_iter()
+                            .map(|(xs, _paths)| xs))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                let stitcher = PanoramaStitcher::new(cfg.clone())?;
+
+                // 收到 SIGHUP 时重新标定（相机被碰动等情况）
+                #[cfg(unix)]
+                {
+                    let handle = stitcher.recalibrate_handle();
+                    let mut hup =
+                        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())?;
+                    tokio::spawn(async move {
+                        while hup.recv().await.is_some() {
+                            tracing::info!("SIGHUP received, stitching will be recalibrated");
+                            handle.request();
+                        }
+                    });
+                }
+
+                Box::new(StitchedBatches::new(sources, stitcher).map(|xs| (xs, None)))
+            }
+            // 保留解码器的原生帧，推理和标注用的 RGB 帧由它一次 sws_scale 得到
+            None if args::native_decode() => {
+                let mut to_rgb = RgbConverter::default();
+                Box::new(
+                    FrameReader::new(&args::input_source())?
+                        .with_batch(batch)
+                        .map(move |frames| {
+                            let (xs, frames): (Vec<_>, Vec<_>) = frames
+                                .into_iter()
+                                .filter_map(|frame| match to_rgb.convert(&frame) {
+                                    Ok(x) => Some((x, frame)),
+                                    Err(e) => {
+                                        tracing::warn!("Failed to convert decoded frame: {:?}", e);
+                                        None
+                                    }
+                                })
+                                .unzip();
+                            (xs, Some(frames))
+                        }),
+                )
+            }
+            // usls 的 DataLoader 解码后直接给出 RGB 帧，只在编码前用 FrameConverter 转换一次
+            None => Box::new(
+                DataLoader::new(&args::input_source())?
+                    .with_batch(batch)
+                    .with_device(Device::Cuda(0))
+                    .build()?
+                    .into_iter()
+                    .map(|(xs, _paths)| (xs, None)),
+            ),
+        };
 
     // build annotator
-    let annotator = Arc::new(
-        Annotator::default()
-            .with_skeletons(&usls::COCO_SKELETONS_16)
-            .without_masks(true)
-            .with_bboxes_thickness(3)
-            .with_saveout(model.lock().spec()),
-    );
+    let annotator = Annotator::new(stream_config.annotation.clone())?;
+
+    // 状态叠加层（可选）
+    let overlay = stream_config
+        .overlay
+        .clone()
+        .map(|c| Overlay::new(c, &args::stream_name(), &stream_config.annotation))
+        .transpose()?;
+    let mut fps_meter = FpsMeter::default();
+
+    // 检测热力图（可选）
+    let mut heatmap = stream_config
+        .heatmap
+        .clone()
+        .map(|c| Heatmap::new(c, &args::stream_name()))
+        .transpose()?;
+
+    // 目标跟踪与轨迹（可选）
+    let mut trajectories = stream_config
+        .trajectories
+        .clone()
+        .map(|c| Trajectories::new(c, &args::stream_name()));
+
+    let mut frame_clock: Option<FrameClock> = None;
 
     // let position = Arc::new(Mutex::new(Time::zero()));
     // let duration = Time::from_nth_of_a_second(24);
@@ -70,7 +180,7 @@ async fn main() -> Result<()> {
     let encoding_times_clone = Arc::clone(&encoding_times);
 
     // 创建用于帧处理的channel
-    let (frame_tx, mut frame_rx) = mpsc::channel::<DynamicImage>(32);
+    let (frame_tx, mut frame_rx) = mpsc::channel::<EncodeFrame>(32);
 
     // 启动编码任务
     let encode_handle = {
@@ -78,20 +188,23 @@ async fn main() -> Result<()> {
         // let position = Arc::clone(&position);
         // let duration = duration;
 
+        // 标注后的 RGB 帧或未修改的原生帧一次转换（含缩放）为编码器的 YUV420P，复用转换上下文和输出帧
+        let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+
         tokio::spawn(async move {
             while let Some(frame) = frame_rx.recv().await {
                 let encode_start = Instant::now();
 
-                let raw_frame = match RawFrame::try_from_cv(&frame.to_rgb8()) {
-                    Ok(rf) => rf,
-                    Err(e) => {
-                        tracing::error!("Failed to convert frame to AVFrame: {:?}", e);
-                        continue;
-                    }
-                };
-
                 tokio::task::block_in_place(|| {
-                    if let Err(e) = encoder.lock().encode_raw(&raw_frame) {
+                    let converted = match &frame {
+                        EncodeFrame::Native(frame) => converter.convert_frame(frame),
+                        EncodeFrame::Rgb(DynamicImage::ImageRgb8(rgb)) => converter.convert(rgb),
+                        EncodeFrame::Rgb(other) => converter.convert(&other.to_rgb8()),
+                    };
+                    let result = converted.and_then(|raw_frame| {
+                        encoder.lock().encode_raw(raw_frame).map_err(Into::into)
+                    });
+                    if let Err(e) = result {
                         tracing::error!("Failed to encode frame: {:?}", e);
                     }
                 });
@@ -102,13 +215,47 @@ async fn main() -> Result<()> {
         })
     };
 
+    // 画面不做任何修改时直接编码解码器的原生帧，省去 RGB 往返
+    let passthrough = !annotate
+        && privacy_mask.is_none()
+        && redactor.is_none()
+        && overlay.is_none()
+        && !heatmap.as_ref().is_some_and(|h| h.config().overlay)
+        && !trajectories.as_ref().is_some_and(|t| t.config().trails);
+
     // 主处理循环
     let mut batch_count = 0;
-    for (xs, _paths) in dl {
+    for (xs, native) in batches {
+        let batch_start = Instant::now();
+
+        // 推理前遮挡静态隐私区域，失败时丢弃整批帧
+        let xs = match &privacy_mask {
+            Some(mask) => match mask.apply(xs) {
+                Ok(xs) => xs,
+                Err(e) => {
+                    tracing::error!("Privacy mask failed, batch dropped: {:?}", e);
+                    continue;
+                }
+            },
+            None => xs,
+        };
+
         let inference_start = Instant::now();
-        let ys = match model.lock().forward(&xs) {
+        let result = if let Some(tiler) = &tiler {
+            tiler.forward(model.lock().as_mut(), &xs)
+        } else if let Some(tta) = tta.as_mut() {
+            tta.forward(model.lock().as_mut(), &xs)
+        } else {
+            model.lock().forward(&xs)
+        }
+        .and_then(|ys| match ensemble.as_mut() {
+            Some(ensemble) => ensemble.forward(&xs, ys),
+            None => Ok(ys),
+        });
+        let ys = match result {
             Ok(y) => {
                 inference_times.push(inference_start.elapsed());
+                fps_meter.record(y.len());
                 y
             }
             Err(e) => {
@@ -117,8 +264,105 @@ async fn main() -> Result<()> {
             }
         };
 
+        // 丢弃落在静态遮挡区域内的检测，避免进入热力图、轨迹和叠加统计
+        let ys = match &privacy_mask {
+            Some(mask) => {
+                let sizes: Vec<(u32, u32)> = xs.iter().map(|x| (x.width(), x.height())).collect();
+                mask.filter(&ys, &sizes)
+            }
+            None => ys,
+        };
+
         let annotation_start = Instant::now();
-        let frames = match annotator.plot(&xs, &ys, false) {
+
+        // 隐私打码，失败时丢弃整批帧，避免未打码的画面被推流
+        let xs = match &redactor {
+            Some(redactor) => match redactor.redact(xs, &ys) {
+                Ok(xs) => xs,
+                Err(e) => {
+                    tracing::error!("Frame redaction failed, batch dropped: {:?}", e);
+                    continue;
+                }
+            },
+            None => xs,
+        };
+
+        let sizes: Vec<_> = xs.iter().map(|x| (x.width(), x.height())).collect();
+        if let Some(heatmap) = heatmap.as_mut() {
+            heatmap.update(&ys, &sizes);
+            if let Some(background) = xs.last() {
+                match heatmap.maybe_snapshot(background) {
+                    Ok(Some(path)) => tracing::info!("Heatmap snapshot saved: {:?}", path),
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("Failed to save heatmap snapshot: {:?}", e),
+                }
+            }
+        }
+
+        // RedactOnly 模式下被打码目标不参与跟踪，其框、跟踪 ID 和轨迹既不绘制也不导出
+        let visible = redactor.as_ref().map(|r| r.visible(&ys));
+        let visible = visible.as_deref().unwrap_or(&ys);
+
+        // 按帧序号推算的时间戳，轨迹和叠加层的 PTS 共用
+        let clock = frame_clock.get_or_insert_with(|| {
+            FrameClock::new(
+                chrono::Utc::now().timestamp_millis(),
+                trajectories.as_ref().map_or(0.0, |t| t.config().fps),
+            )
+        });
+        let timestamps = clock.next(visible.len());
+        let pts: Vec<Duration> = timestamps.iter().map(|&t| clock.pts(t)).collect();
+
+        let tracked = trajectories.as_mut().map(|t| {
+            let update = t.update(visible, &sizes, &timestamps);
+            if let Err(e) = t.export(&update.finished) {
+                tracing::error!("Failed to export trajectories: {:?}", e);
+            }
+            update
+        });
+        let tracks = tracked
+            .as_ref()
+            .map(|u| u.tracks.clone())
+            .unwrap_or_default();
+
+        let plotted: Result<Vec<DynamicImage>> = if !annotate {
+            Ok(xs)
+        } else {
+            Ok(annotator.annotate(xs, visible, &tracks))
+        };
+        let plotted = match (&trajectories, &tracked) {
+            (Some(t), Some(update)) if t.config().trails => {
+                plotted.map(|frames| t.apply(frames, &update.trails))
+            }
+            _ => plotted,
+        };
+        let plotted = match &heatmap {
+            Some(heatmap) if heatmap.config().overlay => {
+                plotted.map(|frames| heatmap.apply(frames))
+            }
+            _ => plotted,
+        };
+        let plotted = match &overlay {
+            Some(overlay) => plotted.map(|frames| {
+                let (fps, latency) = (fps_meter.fps(), batch_start.elapsed());
+                let stats: Vec<OverlayStats> = pts
+                    .iter()
+                    .map(|&pts| OverlayStats {
+                        fps,
+                        latency,
+                        pts: Some(pts),
+                    })
+                    .collect();
+                overlay.apply(frames, visible, &stats)
+            }),
+            None => plotted,
+        };
+        // 标注后再次遮挡，避免框和标签画进遮挡区域
+        let plotted = match &privacy_mask {
+            Some(mask) => plotted.and_then(|frames| mask.apply(frames)),
+            None => plotted,
+        };
+        let frames = match plotted {
             Ok(f) => {
                 annotation_times.push(annotation_start.elapsed());
                 f
@@ -130,7 +374,10 @@ async fn main() -> Result<()> {
         };
 
         let batch_sender_start = Instant::now();
-        let processed_frames: Vec<_> = frames.into_par_iter().collect();
+        let processed_frames: Vec<EncodeFrame> = match native {
+            Some(native) if passthrough => native.into_iter().map(EncodeFrame::Native).collect(),
+            _ => frames.into_par_iter().map(EncodeFrame::Rgb).collect(),
+        };
         // 在异步上下文中发送帧
         for frame in processed_frames {
             if let Err(e) = frame_tx.send(frame).await {
@@ -166,6 +413,14 @@ async fn main() -> Result<()> {
         }
     }
 
+    // 导出仍在进行中的轨迹
+    if let Some(t) = trajectories.as_mut() {
+        let records = t.finish();
+        if let Err(e) = t.export(&records) {
+            tracing::error!("Failed to export trajectories: {:?}", e);
+        }
+    }
+
     // 关闭frame channel
     drop(frame_tx);
 
@@ -183,6 +438,14 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// 送入编码任务的帧
+enum EncodeFrame {
+    /// 标注后的 RGB 帧
+    Rgb(DynamicImage),
+    /// 未被修改的解码器原生帧
+    Native(AVFrame),
+}
+
 /// 从 SegQueue 中收集所有元素并计算统计信息
 fn collect_and_calculate_stats(queue: &SegQueue<Duration>) -> (Duration, Duration, Duration) {
     // 逐个弹出元素，直到队列为空
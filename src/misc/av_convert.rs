@@ -0,0 +1,481 @@
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, ImageBuffer, Rgb, RgbImage};
+use opencv::core::{Mat, Scalar, CV_8UC1, CV_8UC3};
+use opencv::{imgproc, prelude::*};
+use rsmpeg::avutil::AVFrame;
+use rsmpeg::ffi;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use std::ops::Deref;
+
+use crate::utils::cv::{mat_to_rgb_image, rgb_image_to_mat};
+
+/// 支持互相转换的像素格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// 三平面 Y、U、V，色度 2x2 下采样
+    Yuv420p,
+    /// Y 平面加 UV 交错平面，色度 2x2 下采样
+    Nv12,
+    /// 打包的 RGB
+    Rgb24,
+}
+
+impl PixelFormat {
+    pub fn from_av(format: ffi::AVPixelFormat) -> Option<Self> {
+        match format {
+            ffi::AV_PIX_FMT_YUV420P => Some(Self::Yuv420p),
+            ffi::AV_PIX_FMT_NV12 => Some(Self::Nv12),
+            ffi::AV_PIX_FMT_RGB24 => Some(Self::Rgb24),
+            _ => None,
+        }
+    }
+
+    pub fn to_av(self) -> ffi::AVPixelFormat {
+        match self {
+            Self::Yuv420p => ffi::AV_PIX_FMT_YUV420P,
+            Self::Nv12 => ffi::AV_PIX_FMT_NV12,
+            Self::Rgb24 => ffi::AV_PIX_FMT_RGB24,
+        }
+    }
+
+    /// 各平面每行的有效字节数与行数
+    fn planes(self, width: usize, height: usize) -> Vec<(usize, usize)> {
+        let (cw, ch) = (width.div_ceil(2), height.div_ceil(2));
+        match self {
+            Self::Yuv420p => vec![(width, height), (cw, ch), (cw, ch)],
+            Self::Nv12 => vec![(width, height), (cw * 2, ch)],
+            Self::Rgb24 => vec![(width * 3, height)],
+        }
+    }
+}
+
+/// 借用 AVFrame 缓冲区的 Mat，生命周期不超过帧本身，只读
+pub struct MatView<'a> {
+    mat: Mat,
+    _frame: PhantomData<&'a AVFrame>,
+}
+
+impl Deref for MatView<'_> {
+    type Target = Mat;
+
+    fn deref(&self) -> &Mat {
+        &self.mat
+    }
+}
+
+/// 帧的像素格式
+pub fn frame_format(frame: &AVFrame) -> Result<PixelFormat> {
+    PixelFormat::from_av(frame.format)
+        .ok_or_else(|| anyhow!("Unsupported pixel format: {}", frame.format))
+}
+
+/// 按 linesize 逐平面借用帧数据，返回 (数据, 行跨度, 每行有效字节, 行数)
+fn planes(frame: &AVFrame) -> Result<Vec<(&[u8], usize, usize, usize)>> {
+    let format = frame_format(frame)?;
+    if frame.width <= 0 || frame.height <= 0 {
+        return Err(anyhow!(
+            "Invalid frame size {}x{}",
+            frame.width,
+            frame.height
+        ));
+    }
+    format
+        .planes(frame.width as usize, frame.height as usize)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (row_bytes, rows))| {
+            let (data, linesize) = (frame.data[i], frame.linesize[i]);
+            if data.is_null() {
+                return Err(anyhow!("Plane {} of frame is not allocated", i));
+            }
+            // 负的 linesize 表示自下而上存储，这里不支持
+            if linesize < row_bytes as i32 {
+                return Err(anyhow!(
+                    "Plane {} linesize {} is smaller than row size {}",
+                    i,
+                    linesize,
+                    row_bytes
+                ));
+            }
+            let stride = linesize as usize;
+            let len = stride * (rows - 1) + row_bytes;
+            // SAFETY: 帧缓冲区至少包含 rows 行、每行 linesize 字节，最后一行只取有效部分
+            let data = unsafe { std::slice::from_raw_parts(data, len) };
+            Ok((data, stride, row_bytes, rows))
+        })
+        .collect()
+}
+
+/// 分配指定格式与尺寸的帧，linesize 由 FFmpeg 按对齐要求决定
+pub fn alloc_frame(width: i32, height: i32, format: PixelFormat) -> Result<AVFrame> {
+    if format != PixelFormat::Rgb24 && (width % 2 != 0 || height % 2 != 0) {
+        return Err(anyhow!(
+            "{:?} requires even frame size, got {}x{}",
+            format,
+            width,
+            height
+        ));
+    }
+    let mut frame = AVFrame::new();
+    frame.set_width(width);
+    frame.set_height(height);
+    frame.set_format(format.to_av());
+    frame.alloc_buffer()?;
+    Ok(frame)
+}
+
+/// 将紧密排列的平面数据逐行写入帧，处理 linesize 填充
+fn write_planes(frame: &mut AVFrame, sources: &[&[u8]]) -> Result<()> {
+    let format = frame_format(frame)?;
+    let layout = format.planes(frame.width as usize, frame.height as usize);
+    for (i, ((row_bytes, rows), src)) in layout.into_iter().zip(sources).enumerate() {
+        if src.len() != row_bytes * rows {
+            return Err(anyhow!(
+                "Plane {} expects {} bytes, got {}",
+                i,
+                row_bytes * rows,
+                src.len()
+            ));
+        }
+        let stride = frame.linesize[i] as usize;
+        // SAFETY: alloc_buffer 分配了 rows 行、每行 linesize 字节的缓冲区
+        let dst = unsafe { std::slice::from_raw_parts_mut(frame.data[i], stride * rows) };
+        for (d, s) in dst.chunks_mut(stride).zip(src.chunks(row_bytes)) {
+            d[..row_bytes].copy_from_slice(s);
+        }
+    }
+    Ok(())
+}
+
+/// RGB24 帧零拷贝包装为 CV_8UC3 的 Mat（RGB 通道顺序），行跨度沿用 linesize
+pub fn avframe_rgb24_as_mat(frame: &AVFrame) -> Result<MatView<'_>> {
+    if frame_format(frame)? != PixelFormat::Rgb24 {
+        return Err(anyhow!("Expected RGB24 frame, got format {}", frame.format));
+    }
+    let (data, stride, _, _) = planes(frame)?[0];
+    // SAFETY: Mat 不拥有数据，MatView 的生命周期绑定在帧上，且只提供只读访问
+    let mat = unsafe {
+        Mat::new_rows_cols_with_data_unsafe(
+            frame.height,
+            frame.width,
+            CV_8UC3,
+            data.as_ptr() as *mut c_void,
+            stride,
+        )?
+    };
+    Ok(MatView {
+        mat,
+        _frame: PhantomData,
+    })
+}
+
+/// RGB24 帧拷贝为连续的 Mat
+pub fn avframe_rgb24_to_mat(frame: &AVFrame) -> Result<Mat> {
+    Ok(avframe_rgb24_as_mat(frame)?.try_clone()?)
+}
+
+/// YUV 帧包装为 OpenCV 期望的单通道 (h * 3 / 2) x w 布局。
+/// 各平面紧密且首尾相接时直接借用缓冲区（返回的 Mat 只能在帧存活期间使用），否则按行拷贝
+fn yuv_mat(frame: &AVFrame) -> Result<Mat> {
+    let planes = planes(frame)?;
+    let (w, h) = (frame.width, frame.height);
+    let contiguous = planes
+        .iter()
+        .all(|&(_, stride, row_bytes, _)| stride == row_bytes)
+        && planes.windows(2).all(|p| {
+            let (prev, _, row_bytes, rows) = p[0];
+            prev.as_ptr().wrapping_add(row_bytes * rows) == p[1].0.as_ptr()
+        });
+    if contiguous {
+        // SAFETY: 调用方只在帧存活期间把它作为 cvt_color 的输入，不会写入
+        let mat = unsafe {
+            Mat::new_rows_cols_with_data_unsafe(
+                h * 3 / 2,
+                w,
+                CV_8UC1,
+                planes[0].0.as_ptr() as *mut c_void,
+                w as usize,
+            )?
+        };
+        return Ok(mat);
+    }
+
+    let mut mat = Mat::new_rows_cols_with_default(h * 3 / 2, w, CV_8UC1, Scalar::all(0.0))?;
+    let dst = mat.data_bytes_mut()?;
+    let mut offset = 0;
+    for (data, stride, row_bytes, rows) in planes {
+        for row in data.chunks(stride).take(rows) {
+            dst[offset..offset + row_bytes].copy_from_slice(&row[..row_bytes]);
+            offset += row_bytes;
+        }
+    }
+    Ok(mat)
+}
+
+/// 任意支持格式的帧转换为 CV_8UC3 的 Mat（RGB 通道顺序）
+pub fn avframe_to_mat(frame: &AVFrame) -> Result<Mat> {
+    let code = match frame_format(frame)? {
+        PixelFormat::Rgb24 => return avframe_rgb24_to_mat(frame),
+        PixelFormat::Yuv420p => imgproc::COLOR_YUV2RGB_I420,
+        PixelFormat::Nv12 => imgproc::COLOR_YUV2RGB_NV12,
+    };
+    if frame.width % 2 != 0 || frame.height % 2 != 0 {
+        return Err(anyhow!(
+            "YUV frame size must be even, got {}x{}",
+            frame.width,
+            frame.height
+        ));
+    }
+    let yuv = yuv_mat(frame)?;
+    let mut rgb = Mat::default();
+    imgproc::cvt_color_def(&yuv, &mut rgb, code)?;
+    Ok(rgb)
+}
+
+/// YUV420P/NV12 帧转换为 RGB24 帧
+pub fn avframe_yuv_to_rgb24(frame: &AVFrame) -> Result<AVFrame> {
+    let mut out = mat_to_avframe(&avframe_to_mat(frame)?, PixelFormat::Rgb24)?;
+    out.set_pts(frame.pts);
+    Ok(out)
+}
+
+/// RGB24 帧在行跨度紧密时零拷贝借用为 RGB 图像
+pub fn avframe_rgb24_as_image(frame: &AVFrame) -> Option<ImageBuffer<Rgb<u8>, &[u8]>> {
+    if frame_format(frame).ok()? != PixelFormat::Rgb24 {
+        return None;
+    }
+    let (data, stride, row_bytes, _) = planes(frame).ok()?[0];
+    if stride != row_bytes {
+        return None;
+    }
+    ImageBuffer::from_raw(frame.width as u32, frame.height as u32, data)
+}
+
+/// 任意支持格式的帧转换为 RGB 图像
+pub fn avframe_to_image(frame: &AVFrame) -> Result<DynamicImage> {
+    if let Some(view) = avframe_rgb24_as_image(frame) {
+        return Ok(DynamicImage::ImageRgb8(view.convert()));
+    }
+    Ok(DynamicImage::ImageRgb8(mat_to_rgb_image(&avframe_to_mat(
+        frame,
+    )?)?))
+}
+
+/// CV_8UC3 的 Mat（RGB 通道顺序）转换为指定格式的帧
+pub fn mat_to_avframe(mat: &Mat, format: PixelFormat) -> Result<AVFrame> {
+    if mat.typ() != CV_8UC3 {
+        return Err(anyhow!("Expected CV_8UC3 mat, got type {}", mat.typ()));
+    }
+    let owned;
+    let mat = if mat.is_continuous() {
+        mat
+    } else {
+        owned = mat.try_clone()?;
+        &owned
+    };
+    let (w, h) = (mat.cols(), mat.rows());
+    let mut frame = alloc_frame(w, h, format)?;
+    if format == PixelFormat::Rgb24 {
+        write_planes(&mut frame, &[mat.data_bytes()?])?;
+        return Ok(frame);
+    }
+
+    // I420 为 Y 平面后接 U、V 平面
+    let mut i420 = Mat::default();
+    imgproc::cvt_color_def(mat, &mut i420, imgproc::COLOR_RGB2YUV_I420)?;
+    let data = i420.data_bytes()?;
+    let (luma, chroma) = ((w * h) as usize, (w * h / 4) as usize);
+    let (y, rest) = data.split_at(luma);
+    let (u, v) = rest.split_at(chroma);
+    match format {
+        PixelFormat::Yuv420p => write_planes(&mut frame, &[y, u, v])?,
+        _ => {
+            let uv: Vec<u8> = u.iter().zip(v).flat_map(|(&u, &v)| [u, v]).collect();
+            write_planes(&mut frame, &[y, &uv])?;
+        }
+    }
+    Ok(frame)
+}
+
+/// RGB 图像转换为指定格式的帧
+pub fn image_to_avframe(image: &DynamicImage, format: PixelFormat) -> Result<AVFrame> {
+    let rgb;
+    let rgb: &RgbImage = match image.as_rgb8() {
+        Some(rgb) => rgb,
+        None => {
+            rgb = image.to_rgb8();
+            &rgb
+        }
+    };
+    if format == PixelFormat::Rgb24 {
+        let mut frame = alloc_frame(rgb.width() as i32, rgb.height() as i32, format)?;
+        write_planes(&mut frame, &[rgb.as_raw()])?;
+        return Ok(frame);
+    }
+    mat_to_avframe(&rgb_image_to_mat(rgb)?, format)
+}
+
+/// 到编码器像素格式的转换器：一次 `sws_scale` 同时完成缩放和颜色转换，
+/// 复用 SwsContext 和输出帧，避免每帧分配
+///
+/// 输入可以是标注后的 RGB 图像，也可以是解码器输出的原生帧（任意 FFmpeg 像素格式）。
+pub struct FrameConverter {
+    width: i32,
+    height: i32,
+    format: PixelFormat,
+    sws: *mut ffi::SwsContext,
+    frame: Option<AVFrame>,
+}
+
+// SwsContext 只在持有转换器的线程内使用
+unsafe impl Send for FrameConverter {}
+
+impl FrameConverter {
+    /// 输出 width x height、`format` 格式的帧，通常与编码器配置一致
+    pub fn new(width: i32, height: i32, format: PixelFormat) -> Self {
+        Self {
+            width,
+            height,
+            format,
+            sws: std::ptr::null_mut(),
+            frame: None,
+        }
+    }
+
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// 输出尺寸 (宽, 高)
+    pub fn size(&self) -> (i32, i32) {
+        (self.width, self.height)
+    }
+
+    /// 转换一帧 RGB 图像，返回的帧在下次调用前有效
+    pub fn convert<C>(&mut self, image: &ImageBuffer<Rgb<u8>, C>) -> Result<&AVFrame>
+    where
+        C: Deref<Target = [u8]>,
+    {
+        let src = [image.as_raw().as_ptr(), std::ptr::null()];
+        let src_stride = [image.width() as i32 * 3, 0];
+        self.scale(
+            image.width() as i32,
+            image.height() as i32,
+            ffi::AV_PIX_FMT_RGB24,
+            src.as_ptr(),
+            src_stride.as_ptr(),
+        )
+    }
+
+    /// 直接转换解码器输出的原生帧，不经过 RGB，返回的帧在下次调用前有效
+    pub fn convert_frame(&mut self, frame: &AVFrame) -> Result<&AVFrame> {
+        if frame.width <= 0 || frame.height <= 0 || frame.data[0].is_null() {
+            return Err(anyhow!(
+                "Invalid source frame {}x{}",
+                frame.width,
+                frame.height
+            ));
+        }
+        self.scale(
+            frame.width,
+            frame.height,
+            frame.format,
+            frame.data.as_ptr() as *const *const u8,
+            frame.linesize.as_ptr(),
+        )
+    }
+
+    /// `src` 与 `src_stride` 为 sws_scale 的源平面指针与行跨度，需在调用期间有效
+    fn scale(
+        &mut self,
+        src_w: i32,
+        src_h: i32,
+        src_format: ffi::AVPixelFormat,
+        src: *const *const u8,
+        src_stride: *const i32,
+    ) -> Result<&AVFrame> {
+        // 尺寸或格式不变时返回原上下文
+        self.sws = unsafe {
+            ffi::sws_getCachedContext(
+                self.sws,
+                src_w,
+                src_h,
+                src_format,
+                self.width,
+                self.height,
+                self.format.to_av(),
+                ffi::SWS_BILINEAR as i32,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null(),
+            )
+        };
+        if self.sws.is_null() {
+            return Err(anyhow!(
+                "Failed to create scaler {}x{} (format {}) -> {}x{} {:?}",
+                src_w,
+                src_h,
+                src_format,
+                self.width,
+                self.height,
+                self.format
+            ));
+        }
+
+        let frame = match self.frame.take() {
+            // 编码器仍持有上一帧的引用时，make_writable 会换一块新缓冲区
+            Some(mut frame) => {
+                let ret = unsafe { ffi::av_frame_make_writable(frame.as_mut_ptr()) };
+                if ret < 0 {
+                    return Err(anyhow!("Failed to make frame writable: {}", ret));
+                }
+                frame
+            }
+            None => alloc_frame(self.width, self.height, self.format)?,
+        };
+        let frame = self.frame.insert(frame);
+
+        let ret = unsafe {
+            ffi::sws_scale(
+                self.sws,
+                src,
+                src_stride,
+                0,
+                src_h,
+                frame.data.as_ptr(),
+                frame.linesize.as_ptr(),
+            )
+        };
+        if ret < 0 {
+            return Err(anyhow!("sws_scale failed: {}", ret));
+        }
+        Ok(frame)
+    }
+}
+
+impl Drop for FrameConverter {
+    fn drop(&mut self) {
+        unsafe { ffi::sws_freeContext(self.sws) };
+    }
+}
+
+/// 按原尺寸把解码器输出的帧转换为 RGB 图像（供推理和标注），源格式不限于 [`PixelFormat`]，
+/// 源尺寸变化时重建内部的转换器
+#[derive(Default)]
+pub struct RgbConverter {
+    converter: Option<FrameConverter>,
+}
+
+impl RgbConverter {
+    pub fn convert(&mut self, frame: &AVFrame) -> Result<DynamicImage> {
+        let size = (frame.width, frame.height);
+        if self.converter.as_ref().is_some_and(|c| c.size() != size) {
+            self.converter = None;
+        }
+        let converter = self
+            .converter
+            .get_or_insert_with(|| FrameConverter::new(size.0, size.1, PixelFormat::Rgb24));
+        avframe_to_image(converter.convert_frame(frame)?)
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Context, Result};
+use rsmpeg::avcodec::AVCodecContext;
+use rsmpeg::avformat::AVFormatContextInput;
+use rsmpeg::avutil::AVFrame;
+use rsmpeg::ffi;
+use std::ffi::{CStr, CString};
+
+/// 打开输入文件并创建视频解码器，返回 (视频流索引, 输入上下文, 解码上下文)
+pub fn open_input_file(path: &CStr) -> Result<(usize, AVFormatContextInput, AVCodecContext)> {
+    let input = AVFormatContextInput::open(path, None, &mut None)
+        .with_context(|| format!("Failed to open input: {:?}", path))?;
+    let (index, decoder) = input
+        .find_best_stream(ffi::AVMEDIA_TYPE_VIDEO)?
+        .ok_or_else(|| anyhow!("No video stream found in {:?}", path))?;
+
+    let stream = &input.streams()[index];
+    let mut decode_context = AVCodecContext::new(&decoder);
+    decode_context.apply_codecpar(&stream.codecpar())?;
+    decode_context.set_time_base(stream.time_base);
+    decode_context.open(None)?;
+    Ok((index, input, decode_context))
+}
+
+/// 解码视频文件的前 `limit` 帧，保持解码器输出的像素格式
+pub fn decode_frames(path: &str, limit: usize) -> Result<Vec<AVFrame>> {
+    let path = CString::new(path)?;
+    let (index, mut input, mut decode_context) = open_input_file(&path)?;
+
+    let mut frames = Vec::new();
+    while frames.len() < limit {
+        let packet = input.read_packet()?;
+        if let Some(packet) = &packet {
+            if packet.stream_index != index as i32 {
+                continue;
+            }
+        }
+        // 读到文件末尾时送入空包冲刷解码器
+        decode_context.send_packet(packet.as_ref())?;
+        while let Ok(frame) = decode_context.receive_frame() {
+            frames.push(frame);
+            if frames.len() >= limit {
+                break;
+            }
+        }
+        if packet.is_none() {
+            break;
+        }
+    }
+    Ok(frames)
+}
+
+/// 逐批解码视频，保持解码器输出的原生像素格式
+///
+/// 与 usls 的 DataLoader 不同，帧不在解码后立即转换为 RGB，画面未被修改时可以直接交给
+/// [`FrameConverter::convert_frame`](super::av_convert::FrameConverter::convert_frame) 编码。
+pub struct FrameReader {
+    index: usize,
+    input: AVFormatContextInput,
+    decode_context: AVCodecContext,
+    batch: usize,
+    eof: bool,
+}
+
+impl FrameReader {
+    pub fn new(path: &str) -> Result<Self> {
+        let (index, input, decode_context) = open_input_file(&CString::new(path)?)?;
+        Ok(Self {
+            index,
+            input,
+            decode_context,
+            batch: 1,
+            eof: false,
+        })
+    }
+
+    pub fn with_batch(mut self, x: usize) -> Self {
+        self.batch = x.max(1);
+        self
+    }
+
+    /// 读取下一批帧，文件结束时返回不足一批或空的结果
+    pub fn read(&mut self) -> Result<Vec<AVFrame>> {
+        let mut frames = Vec::with_capacity(self.batch);
+        while frames.len() < self.batch {
+            // 先取出解码器中已有的帧
+            if let Ok(frame) = self.decode_context.receive_frame() {
+                frames.push(frame);
+                continue;
+            }
+            if self.eof {
+                break;
+            }
+            let packet = self.input.read_packet()?;
+            if let Some(packet) = &packet {
+                if packet.stream_index != self.index as i32 {
+                    continue;
+                }
+            }
+            // 读到文件末尾时送入空包冲刷解码器
+            self.eof = packet.is_none();
+            self.decode_context.send_packet(packet.as_ref())?;
+        }
+        Ok(frames)
+    }
+}
+
+impl Iterator for FrameReader {
+    type Item = Vec<AVFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read() {
+            Ok(frames) if !frames.is_empty() => Some(frames),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::error!("Failed to decode frames: {:?}", e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,2 @@
+pub mod av_convert;
+pub mod avio;
@@ -0,0 +1,264 @@
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+
+use super::{nms, Detection, NmsConfig};
+use crate::utils::geometry::BoxF;
+
+/// 多来源结果的融合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FusionMethod {
+    /// Weighted Box Fusion：重叠框按置信度加权平均坐标
+    Wbf,
+    /// 直接合并所有来源后做同类 NMS
+    Nms,
+}
+
+/// WBF 融合后置信度的计算方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfType {
+    /// 簇内平均置信度，并按参与的来源数量折算
+    Avg,
+    /// 簇内最大置信度
+    Max,
+}
+
+/// 融合参数
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct FusionConfig {
+    pub method: FusionMethod,
+    pub iou_threshold: f32,
+    /// 低于该分数的框不参与融合
+    pub skip_box_threshold: f32,
+    pub conf_type: ConfType,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            method: FusionMethod::Wbf,
+            iou_threshold: 0.55,
+            skip_box_threshold: 0.0,
+            conf_type: ConfType::Avg,
+        }
+    }
+}
+
+/// 融合多个来源的检测结果，`weights` 与 `sources` 一一对应
+pub fn fuse(sources: &[Vec<Detection>], weights: &[f32], config: &FusionConfig) -> Vec<Detection> {
+    fuse_groups(sources, weights, config)
+        .into_iter()
+        .map(|(d, _)| d)
+        .collect()
+}
+
+/// 与 [`fuse`] 相同，同时返回每个融合结果的成员 `(来源编号, 来源内下标)`，按加权分数降序排列
+pub fn fuse_groups(
+    sources: &[Vec<Detection>],
+    weights: &[f32],
+    config: &FusionConfig,
+) -> Vec<(Detection, Vec<(usize, usize)>)> {
+    match config.method {
+        FusionMethod::Wbf => wbf_groups(sources, weights, config),
+        FusionMethod::Nms => {
+            let members: Vec<(usize, usize)> = sources
+                .iter()
+                .enumerate()
+                .flat_map(|(i, dets)| (0..dets.len()).map(move |k| (i, k)))
+                .filter(|&(i, k)| sources[i][k].score >= config.skip_box_threshold)
+                .collect();
+            // 暂用 source 记录候选下标
+            let merged: Vec<Detection> = members
+                .iter()
+                .enumerate()
+                .map(|(n, &(i, k))| sources[i][k].with_source(n))
+                .collect();
+            nms(
+                &merged,
+                &NmsConfig::default()
+                    .with_iou_threshold(config.iou_threshold)
+                    .with_top_k(None),
+            )
+            .into_iter()
+            .map(|d| {
+                let (i, k) = members[d.source];
+                (d.with_source(i), vec![(i, k)])
+            })
+            .collect()
+        }
+    }
+}
+
+/// Weighted Box Fusion
+///
+/// 按类别分组、置信度降序遍历，每个框并入与当前融合框 IoU 最大且超过阈值的簇，
+/// 簇的坐标为成员坐标按 `score * weight` 加权平均。
+pub fn weighted_boxes_fusion(
+    sources: &[Vec<Detection>],
+    weights: &[f32],
+    config: &FusionConfig,
+) -> Vec<Detection> {
+    wbf_groups(sources, weights, config)
+        .into_iter()
+        .map(|(d, _)| d)
+        .collect()
+}
+
+fn wbf_groups(
+    sources: &[Vec<Detection>],
+    weights: &[f32],
+    config: &FusionConfig,
+) -> Vec<(Detection, Vec<(usize, usize)>)> {
+    let weight_of = |i: usize| weights.get(i).copied().unwrap_or(1.0);
+    let weight_sum: f32 = (0..sources.len()).map(weight_of).sum();
+    let weight_max = (0..sources.len()).map(weight_of).fold(0.0, f32::max);
+
+    let mut by_class: BTreeMap<usize, Vec<(Detection, usize)>> = BTreeMap::new();
+    for (i, dets) in sources.iter().enumerate() {
+        for (k, d) in dets.iter().enumerate() {
+            if d.score < config.skip_box_threshold {
+                continue;
+            }
+            let mut d = d.with_source(i);
+            d.score *= weight_of(i);
+            by_class.entry(d.class_id).or_default().push((d, k));
+        }
+    }
+
+    let mut fused = Vec::new();
+    for (_, mut dets) in by_class {
+        dets.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
+
+        let mut clusters: Vec<Cluster> = Vec::new();
+        for (d, k) in dets {
+            let best = clusters
+                .iter()
+                .enumerate()
+                .map(|(c, x)| (c, x.fused.bbox.iou(&d.bbox)))
+                .filter(|&(_, iou)| iou > config.iou_threshold)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(c, _)| c);
+            match best {
+                Some(c) => {
+                    let x = &mut clusters[c];
+                    x.members.push(d);
+                    x.index.push((d.source, k));
+                    x.fused = fused_box(&x.members, config.conf_type);
+                }
+                None => clusters.push(Cluster {
+                    fused: d,
+                    members: vec![d],
+                    index: vec![(d.source, k)],
+                }),
+            }
+        }
+
+        for Cluster {
+            fused: mut f,
+            members,
+            index,
+        } in clusters
+        {
+            let mut models: Vec<usize> = members.iter().map(|d| d.source).collect();
+            models.sort_unstable();
+            models.dedup();
+            f.score = match config.conf_type {
+                ConfType::Avg if weight_sum > 0.0 => {
+                    f.score * models.len().min(sources.len()) as f32 / weight_sum
+                }
+                ConfType::Max if weight_max > 0.0 => f.score / weight_max,
+                _ => f.score,
+            }
+            .min(1.0);
+            fused.push((f, index));
+        }
+    }
+
+    fused.sort_by(|a, b| b.0.score.total_cmp(&a.0.score));
+    fused
+}
+
+/// WBF 簇：当前融合框、成员及成员的 `(来源编号, 来源内下标)`
+struct Cluster {
+    fused: Detection,
+    members: Vec<Detection>,
+    index: Vec<(usize, usize)>,
+}
+
+fn fused_box(members: &[Detection], conf_type: ConfType) -> Detection {
+    let total: f32 = members.iter().map(|d| d.score).sum();
+    let mut xyxy = [0f32; 4];
+    for d in members {
+        for (acc, v) in xyxy.iter_mut().zip(d.bbox.xyxy()) {
+            *acc += v * d.score;
+        }
+    }
+    if total > 0.0 {
+        xyxy.iter_mut().for_each(|v| *v /= total);
+    }
+    let score = match conf_type {
+        ConfType::Avg => total / members.len() as f32,
+        ConfType::Max => members.iter().map(|d| d.score).fold(0.0, f32::max),
+    };
+    Detection {
+        bbox: BoxF::from_xyxy(xyxy),
+        score,
+        ..members[0]
+    }
+}
+
+/// 统一的类别名称空间，用于对齐不同标签集的模型
+///
+/// 各模型的类别名称先经过 `class_map` 重映射，再按名称分配统一的类别编号。
+#[derive(Debug, Clone, Default)]
+pub struct LabelSpace {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl LabelSpace {
+    pub fn new(names: &[String]) -> Self {
+        let mut space = Self::default();
+        for name in names {
+            space.id_of(name);
+        }
+        space
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn name(&self, id: usize) -> Option<&str> {
+        self.names.get(id).map(|s| s.as_str())
+    }
+
+    /// 获取类别名称对应的统一编号，不存在时追加
+    pub fn id_of(&mut self, name: &str) -> usize {
+        if let Some(&id) = self.index.get(name) {
+            return id;
+        }
+        let id = self.names.len();
+        self.names.push(name.to_string());
+        self.index.insert(name.to_string(), id);
+        id
+    }
+
+    /// 将模型输出的类别名称重映射到统一编号
+    ///
+    /// 未出现在 `class_map` 中的类别在 `drop_unmapped` 为 true 时被丢弃，否则沿用原名称。
+    pub fn remap(
+        &mut self,
+        name: &str,
+        class_map: &HashMap<String, String>,
+        drop_unmapped: bool,
+    ) -> Option<usize> {
+        match class_map.get(name) {
+            Some(target) => Some(self.id_of(target)),
+            None if drop_unmapped => None,
+            None => Some(self.id_of(name)),
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use image::{imageops, GrayImage};
+use std::collections::HashMap;
+use usls::{Keypoint, Mask, Y};
+
+use super::Detection;
+use crate::utils::geometry::BoxF;
+
+/// 实例掩码：只保存检测框附近的部分及其在整帧中的位置，构建 `Y` 时再贴回整帧，
+/// 避免切片推理时为每个实例分配整帧大小的掩码
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstanceMask {
+    pub image: GrayImage,
+    /// `image` 左上角在整帧中的坐标
+    pub x: u32,
+    pub y: u32,
+    /// 整帧尺寸
+    pub width: u32,
+    pub height: u32,
+}
+
+impl InstanceMask {
+    /// 整帧大小的掩码
+    pub fn full(image: GrayImage) -> Self {
+        let (width, height) = image.dimensions();
+        Self {
+            image,
+            x: 0,
+            y: 0,
+            width,
+            height,
+        }
+    }
+
+    /// 整帧坐标处的值，保存范围之外为 0
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        match (x.checked_sub(self.x), y.checked_sub(self.y)) {
+            (Some(x), Some(y)) if x < self.image.width() && y < self.image.height() => {
+                self.image.get_pixel(x, y).0[0]
+            }
+            _ => 0,
+        }
+    }
+
+    /// 贴回整帧大小的画布
+    pub fn to_frame(&self) -> GrayImage {
+        let mut canvas = GrayImage::new(self.width, self.height);
+        imageops::replace(&mut canvas, &self.image, self.x as i64, self.y as i64);
+        canvas
+    }
+
+    /// 只保留 `bbox`（`image` 坐标）覆盖的部分
+    fn crop(&mut self, bbox: &BoxF) {
+        let (w, h) = self.image.dimensions();
+        let x0 = (bbox.x1.floor().max(0.0) as u32).min(w);
+        let y0 = (bbox.y1.floor().max(0.0) as u32).min(h);
+        let x1 = (bbox.x2.ceil().max(0.0) as u32).clamp(x0, w);
+        let y1 = (bbox.y2.ceil().max(0.0) as u32).clamp(y0, h);
+        self.image = imageops::crop_imm(&self.image, x0, y0, x1 - x0, y1 - y0).to_image();
+        self.x += x0;
+        self.y += y0;
+    }
+
+    /// 与另一掩码取并集，整帧尺寸不一致时保留自身
+    fn union(&mut self, other: &InstanceMask) {
+        if (self.width, self.height) != (other.width, other.height) {
+            return;
+        }
+        let x0 = self.x.min(other.x);
+        let y0 = self.y.min(other.y);
+        let x1 = (self.x + self.image.width()).max(other.x + other.image.width());
+        let y1 = (self.y + self.image.height()).max(other.y + other.image.height());
+        if (x0, y0, x1 - x0, y1 - y0) != (self.x, self.y, self.image.width(), self.image.height()) {
+            let mut image = GrayImage::new(x1 - x0, y1 - y0);
+            imageops::replace(
+                &mut image,
+                &self.image,
+                (self.x - x0) as i64,
+                (self.y - y0) as i64,
+            );
+            (self.image, self.x, self.y) = (image, x0, y0);
+        }
+        let (dx, dy) = (other.x - self.x, other.y - self.y);
+        for (x, y, q) in other.image.enumerate_pixels() {
+            let p = self.image.get_pixel_mut(x + dx, y + dy);
+            p.0[0] = p.0[0].max(q.0[0]);
+        }
+    }
+}
+
+/// 检测结果及其对应的分割掩码和关键点，合并多个来源时三者保持对应
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub detection: Detection,
+    pub mask: Option<InstanceMask>,
+    pub keypoints: Option<Vec<Keypoint>>,
+}
+
+impl Instance {
+    pub fn new(detection: Detection) -> Self {
+        Self {
+            detection,
+            mask: None,
+            keypoints: None,
+        }
+    }
+
+    /// 整帧（或整个切片）大小的掩码
+    pub fn with_mask(mut self, x: GrayImage) -> Self {
+        self.mask = Some(InstanceMask::full(x));
+        self
+    }
+
+    pub fn with_keypoints(mut self, x: Vec<Keypoint>) -> Self {
+        self.keypoints = Some(x);
+        self
+    }
+
+    /// 平移到整帧坐标：检测框和关键点加上偏移，掩码裁剪到检测框并记录在
+    /// `width` x `height` 整帧中的位置
+    pub fn translate(mut self, dx: u32, dy: u32, width: u32, height: u32) -> Self {
+        let (fx, fy) = (dx as f32, dy as f32);
+        if let Some(mask) = &mut self.mask {
+            mask.crop(&self.detection.bbox);
+            mask.x += dx;
+            mask.y += dy;
+            (mask.width, mask.height) = (width, height);
+        }
+        self.detection.bbox = self.detection.bbox.translate(fx, fy);
+        if let Some(kpts) = &mut self.keypoints {
+            for k in kpts.iter_mut() {
+                *k = k.clone().with_xy(k.x() + fx, k.y() + fy);
+            }
+        }
+        self
+    }
+
+    /// 与另一实例的掩码取并集，整帧尺寸不一致时保留自身掩码
+    pub fn union_mask(&mut self, other: &Instance) {
+        match (&mut self.mask, &other.mask) {
+            (Some(a), Some(b)) => a.union(b),
+            (None, Some(b)) => self.mask = Some(b.clone()),
+            _ => {}
+        }
+    }
+}
+
+/// 从 usls 输出中提取实例，丢弃未知类别
+///
+/// 掩码和关键点只在数量与检测框一致（按下标一一对应）时才会带上。
+pub fn instances_from_y(y: &Y) -> Vec<Instance> {
+    let bboxes = y.bboxes().unwrap_or_default();
+    let masks = y.masks().filter(|m| m.len() == bboxes.len());
+    let keypoints = y.keypoints().filter(|k| k.len() == bboxes.len());
+    if masks.is_none() && y.masks().is_some_and(|m| !m.is_empty()) {
+        tracing::warn!(
+            "{} masks do not match {} bboxes, masks are dropped",
+            y.masks().unwrap_or_default().len(),
+            bboxes.len()
+        );
+    }
+    if keypoints.is_none() && y.keypoints().is_some_and(|k| !k.is_empty()) {
+        tracing::warn!(
+            "{} keypoint sets do not match {} bboxes, keypoints are dropped",
+            y.keypoints().unwrap_or_default().len(),
+            bboxes.len()
+        );
+    }
+
+    bboxes
+        .iter()
+        .enumerate()
+        .filter_map(|(i, b)| {
+            Some(Instance {
+                detection: Detection::from_bbox(b)?,
+                mask: masks.map(|m| InstanceMask::full(m[i].mask().clone())),
+                keypoints: keypoints.map(|k| k[i].clone()),
+            })
+        })
+        .collect()
+}
+
+/// 由实例构造 usls 输出
+///
+/// 只要有一个实例带掩码（关键点），就为所有检测框输出掩码（关键点），
+/// 缺失的以空掩码（空关键点）补齐，保持与检测框下标对应。
+pub fn y_from_instances(instances: &[Instance], class_names: &HashMap<usize, String>) -> Y {
+    let name = |d: &Detection| class_names.get(&d.class_id).map(|s| s.as_str());
+    let bboxes: Vec<_> = instances
+        .iter()
+        .map(|x| x.detection.to_bbox(name(&x.detection)))
+        .collect();
+    let mut y = Y::default().with_bboxes(&bboxes);
+
+    if instances.iter().any(|x| x.keypoints.is_some()) {
+        let keypoints: Vec<Vec<Keypoint>> = instances
+            .iter()
+            .map(|x| x.keypoints.clone().unwrap_or_default())
+            .collect();
+        y = y.with_keypoints(&keypoints);
+    }
+
+    if let Some((w, h)) = instances
+        .iter()
+        .find_map(|x| x.mask.as_ref().map(|m| (m.width, m.height)))
+    {
+        let masks: Vec<Mask> = instances
+            .iter()
+            .map(|x| {
+                let m = Mask::default()
+                    .with_mask(
+                        x.mask
+                            .as_ref()
+                            .map(InstanceMask::to_frame)
+                            .unwrap_or_else(|| GrayImage::new(w, h)),
+                    )
+                    .with_id(x.detection.class_id as isize);
+                match name(&x.detection) {
+                    Some(name) => m.with_name(name),
+                    None => m,
+                }
+            })
+            .collect();
+        y = y.with_masks(&masks);
+    }
+    y
+}
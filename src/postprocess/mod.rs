@@ -0,0 +1,95 @@
+pub mod fusion;
+pub mod instance;
+pub mod nms;
+
+pub use fusion::{fuse, fuse_groups, FusionConfig, FusionMethod, LabelSpace};
+pub use instance::{instances_from_y, y_from_instances, Instance, InstanceMask};
+pub use nms::{greedy_nmm, greedy_nmm_groups, nms, NmsConfig, NmsKind, SoftNmsMethod};
+
+use std::collections::HashMap;
+use usls::{Bbox, Y};
+
+use crate::utils::geometry::BoxF;
+
+/// 后处理使用的检测结果，坐标为像素 xyxy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    pub bbox: BoxF,
+    pub score: f32,
+    pub class_id: usize,
+    /// 结果来源编号（多模型/多切片融合时区分来源）
+    pub source: usize,
+}
+
+impl Detection {
+    pub fn new(bbox: BoxF, score: f32, class_id: usize) -> Self {
+        Self {
+            bbox,
+            score,
+            class_id,
+            source: 0,
+        }
+    }
+
+    pub fn with_source(mut self, source: usize) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// 由 usls 检测框转换，未知类别（id < 0）返回 None
+    pub fn from_bbox(b: &Bbox) -> Option<Self> {
+        let class_id = usize::try_from(b.id()).ok()?;
+        Some(Self::new(
+            BoxF::new(b.xmin(), b.ymin(), b.xmax(), b.ymax()),
+            b.confidence(),
+            class_id,
+        ))
+    }
+
+    /// 转换为 usls 的检测框
+    pub fn to_bbox(&self, name: Option<&str>) -> Bbox {
+        let bbox = Bbox::default()
+            .with_xyxy(self.bbox.x1, self.bbox.y1, self.bbox.x2, self.bbox.y2)
+            .with_confidence(self.score)
+            .with_id(self.class_id as isize);
+        match name {
+            Some(name) => bbox.with_name(name),
+            None => bbox,
+        }
+    }
+}
+
+/// 从 usls 输出中提取检测结果，丢弃未知类别的框
+pub fn detections_from_y(y: &Y) -> Vec<Detection> {
+    y.bboxes()
+        .unwrap_or_default()
+        .iter()
+        .filter_map(Detection::from_bbox)
+        .collect()
+}
+
+/// 收集 usls 输出中出现过的类别名称
+pub fn class_names_from_ys<'a>(ys: impl IntoIterator<Item = &'a Y>) -> HashMap<usize, String> {
+    ys.into_iter()
+        .flat_map(|y| y.bboxes().unwrap_or_default())
+        .filter_map(|b| Some((usize::try_from(b.id()).ok()?, b.name()?.to_string())))
+        .collect()
+}
+
+/// 由检测结果构造 usls 输出，便于继续交给 Annotator 绘制
+pub fn y_from_detections(detections: &[Detection], class_names: &HashMap<usize, String>) -> Y {
+    let bboxes: Vec<Bbox> = detections
+        .iter()
+        .map(|d| d.to_bbox(class_names.get(&d.class_id).map(|s| s.as_str())))
+        .collect();
+    Y::default().with_bboxes(&bboxes)
+}
+
+/// 合并多个来源的检测结果，并为每个结果标记来源编号
+pub fn concat_sources(sources: &[&[Detection]]) -> Vec<Detection> {
+    sources
+        .iter()
+        .enumerate()
+        .flat_map(|(i, dets)| dets.iter().map(move |d| d.with_source(i)))
+        .collect()
+}
@@ -0,0 +1,306 @@
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+use super::Detection;
+use crate::utils::geometry::BoxF;
+
+/// Soft-NMS 的分数衰减方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SoftNmsMethod {
+    /// IoU 超过阈值时分数乘以 (1 - IoU)
+    Linear,
+    /// 分数乘以 exp(-IoU² / sigma)
+    Gaussian { sigma: f32 },
+}
+
+/// NMS 算法
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NmsKind {
+    /// 经典 NMS，按 IoU 抑制
+    Hard,
+    /// 按 DIoU 抑制，对中心点相距较远的遮挡目标更友好
+    DIoU,
+    /// Soft-NMS，重叠框降低分数而不是直接删除
+    Soft(SoftNmsMethod),
+    /// Matrix NMS（SOLOv2），一次性并行计算所有框的衰减系数；
+    /// 高斯衰减为 exp(-sigma × (IoU² - comp²))，论文默认 sigma = 2
+    Matrix { gaussian: bool, sigma: f32 },
+}
+
+/// NMS 配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NmsConfig {
+    pub kind: NmsKind,
+    /// Hard / DIoU / 线性 Soft-NMS 使用的重叠阈值
+    pub iou_threshold: f32,
+    /// 低于该分数的结果被丢弃（Soft/Matrix NMS 衰减后同样适用）
+    pub score_threshold: f32,
+    /// 为 true 时不区分类别统一抑制
+    pub class_agnostic: bool,
+    /// 最终最多保留的结果数
+    pub top_k: Option<usize>,
+    /// 每个类别参与 NMS 的最大候选数（按分数截断），用于限制 O(n²) 开销
+    pub pre_top_k: Option<usize>,
+}
+
+impl Default for NmsConfig {
+    fn default() -> Self {
+        Self {
+            kind: NmsKind::Hard,
+            iou_threshold: 0.45,
+            score_threshold: 0.0,
+            class_agnostic: false,
+            top_k: Some(300),
+            pre_top_k: None,
+        }
+    }
+}
+
+impl NmsConfig {
+    pub fn with_kind(mut self, kind: NmsKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn with_iou_threshold(mut self, x: f32) -> Self {
+        self.iou_threshold = x;
+        self
+    }
+
+    pub fn with_score_threshold(mut self, x: f32) -> Self {
+        self.score_threshold = x;
+        self
+    }
+
+    pub fn with_class_agnostic(mut self, x: bool) -> Self {
+        self.class_agnostic = x;
+        self
+    }
+
+    pub fn with_top_k(mut self, x: Option<usize>) -> Self {
+        self.top_k = x;
+        self
+    }
+
+    pub fn with_pre_top_k(mut self, x: Option<usize>) -> Self {
+        self.pre_top_k = x;
+        self
+    }
+}
+
+/// 按配置执行 NMS，结果按分数降序排列
+///
+/// 区分类别时各类别分组并行处理，输入可以来自多个模型或多个切片。
+pub fn nms(detections: &[Detection], config: &NmsConfig) -> Vec<Detection> {
+    let mut kept = if config.class_agnostic {
+        nms_single_group(detections.to_vec(), config)
+    } else {
+        let mut groups: BTreeMap<usize, Vec<Detection>> = BTreeMap::new();
+        for d in detections {
+            groups.entry(d.class_id).or_default().push(*d);
+        }
+        groups
+            .into_par_iter()
+            .flat_map_iter(|(_, group)| nms_single_group(group, config))
+            .collect()
+    };
+
+    sort_by_score(&mut kept);
+    if let Some(k) = config.top_k {
+        kept.truncate(k);
+    }
+    kept
+}
+
+fn nms_single_group(mut detections: Vec<Detection>, config: &NmsConfig) -> Vec<Detection> {
+    detections.retain(|d| d.score >= config.score_threshold);
+    sort_by_score(&mut detections);
+    if let Some(k) = config.pre_top_k {
+        detections.truncate(k);
+    }
+
+    match config.kind {
+        NmsKind::Hard => hard_nms(&detections, config.iou_threshold, config.top_k, BoxF::iou),
+        NmsKind::DIoU => hard_nms(&detections, config.iou_threshold, config.top_k, BoxF::diou),
+        NmsKind::Soft(method) => soft_nms(
+            detections,
+            method,
+            config.iou_threshold,
+            config.score_threshold,
+            config.top_k,
+        ),
+        NmsKind::Matrix { gaussian, sigma } => {
+            matrix_nms(detections, gaussian, sigma, config.score_threshold)
+        }
+    }
+}
+
+/// 贪心 NMS，`overlap` 为重叠度量（IoU / DIoU 等），输入需按分数降序排列
+pub fn hard_nms(
+    sorted: &[Detection],
+    threshold: f32,
+    top_k: Option<usize>,
+    overlap: fn(&BoxF, &BoxF) -> f32,
+) -> Vec<Detection> {
+    let limit = top_k.unwrap_or(usize::MAX);
+    let mut suppressed = vec![false; sorted.len()];
+    let mut kept = Vec::new();
+    for i in 0..sorted.len() {
+        if suppressed[i] {
+            continue;
+        }
+        kept.push(sorted[i]);
+        if kept.len() >= limit {
+            break;
+        }
+        let a = &sorted[i].bbox;
+        for j in (i + 1)..sorted.len() {
+            if !suppressed[j] && overlap(a, &sorted[j].bbox) > threshold {
+                suppressed[j] = true;
+            }
+        }
+    }
+    kept
+}
+
+/// Soft-NMS：每轮取出最高分，衰减其余框的分数
+pub fn soft_nms(
+    mut detections: Vec<Detection>,
+    method: SoftNmsMethod,
+    iou_threshold: f32,
+    score_threshold: f32,
+    top_k: Option<usize>,
+) -> Vec<Detection> {
+    let limit = top_k.unwrap_or(usize::MAX);
+    let mut kept = Vec::new();
+    while !detections.is_empty() && kept.len() < limit {
+        let best = detections
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.score.total_cmp(&b.1.score))
+            .map(|(i, _)| i)
+            .unwrap();
+        let top = detections.swap_remove(best);
+        for d in detections.iter_mut() {
+            let iou = top.bbox.iou(&d.bbox);
+            d.score *= match method {
+                SoftNmsMethod::Linear if iou > iou_threshold => 1.0 - iou,
+                SoftNmsMethod::Linear => 1.0,
+                SoftNmsMethod::Gaussian { sigma } => (-(iou * iou) / sigma).exp(),
+            };
+        }
+        detections.retain(|d| d.score >= score_threshold);
+        kept.push(top);
+    }
+    kept
+}
+
+/// Matrix NMS：输入需按分数降序排列
+///
+/// 对每个框 j，衰减系数为 min_{i<j} f(iou_ij) / f(comp_i)，
+/// 其中 comp_i 为框 i 与所有更高分框的最大 IoU。
+pub fn matrix_nms(
+    sorted: Vec<Detection>,
+    gaussian: bool,
+    sigma: f32,
+    score_threshold: f32,
+) -> Vec<Detection> {
+    let n = sorted.len();
+    if n == 0 {
+        return sorted;
+    }
+
+    // 上三角 IoU：ious[j][i] 为 j 与更高分框 i (i < j) 的 IoU
+    let ious: Vec<Vec<f32>> = (0..n)
+        .into_par_iter()
+        .map(|j| {
+            (0..j)
+                .map(|i| sorted[i].bbox.iou(&sorted[j].bbox))
+                .collect()
+        })
+        .collect();
+    let compensate: Vec<f32> = ious
+        .iter()
+        .map(|row| row.iter().copied().fold(0.0, f32::max))
+        .collect();
+
+    let decay = |iou: f32, comp: f32| {
+        if gaussian {
+            (-sigma * (iou * iou - comp * comp)).exp()
+        } else {
+            (1.0 - iou) / (1.0 - comp).max(f32::EPSILON)
+        }
+    };
+
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(j, mut d)| {
+            let coef = ious[j]
+                .iter()
+                .enumerate()
+                .map(|(i, &iou)| decay(iou, compensate[i]))
+                .fold(1.0f32, f32::min);
+            d.score *= coef;
+            d
+        })
+        .filter(|d| d.score >= score_threshold)
+        .collect()
+}
+
+/// 贪心非极大值合并（SAHI 的 GREEDYNMM）
+///
+/// 按分数降序，每个保留框吸收与其重叠度超过阈值的同类框：合并后的框为外接框，
+/// 分数取最大值。`use_ios` 为 true 时以交集/较小框面积衡量重叠，
+/// 适合合并被切片边界截断的目标。
+pub fn greedy_nmm(
+    detections: &[Detection],
+    threshold: f32,
+    use_ios: bool,
+    class_agnostic: bool,
+) -> Vec<Detection> {
+    greedy_nmm_groups(detections, threshold, use_ios, class_agnostic)
+        .into_iter()
+        .map(|(d, _)| d)
+        .collect()
+}
+
+/// 与 [`greedy_nmm`] 相同，同时返回每个合并结果吸收的输入下标，第一个为分数最高的框
+pub fn greedy_nmm_groups(
+    detections: &[Detection],
+    threshold: f32,
+    use_ios: bool,
+    class_agnostic: bool,
+) -> Vec<(Detection, Vec<usize>)> {
+    let mut order: Vec<usize> = (0..detections.len()).collect();
+    order.sort_by(|&a, &b| detections[b].score.total_cmp(&detections[a].score));
+
+    let overlap = |a: &BoxF, b: &BoxF| if use_ios { a.ios(b) } else { a.iou(b) };
+    let mut merged_into = vec![false; order.len()];
+    let mut kept = Vec::new();
+    for i in 0..order.len() {
+        if merged_into[i] {
+            continue;
+        }
+        let first = &detections[order[i]];
+        let mut merged = *first;
+        let mut members = vec![order[i]];
+        for j in (i + 1)..order.len() {
+            let other = &detections[order[j]];
+            if merged_into[j] || (!class_agnostic && other.class_id != merged.class_id) {
+                continue;
+            }
+            if overlap(&first.bbox, &other.bbox) > threshold {
+                merged_into[j] = true;
+                merged.bbox = merged.bbox.enclosing(&other.bbox);
+                members.push(order[j]);
+            }
+        }
+        kept.push((merged, members));
+    }
+    kept
+}
+
+fn sort_by_score(detections: &mut [Detection]) {
+    detections.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
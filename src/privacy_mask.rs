@@ -0,0 +1,238 @@
+use anyhow::Result;
+use image::{DynamicImage, RgbImage};
+use opencv::core::{Mat, Rect, Scalar, Size, CV_8UC1};
+use opencv::{imgproc, prelude::*};
+use parking_lot::Mutex;
+use rayon::prelude::*;
+use serde::Deserialize;
+use std::sync::Arc;
+use usls::{Bbox, Keypoint, Mask, Polygon, Y};
+
+use crate::utils::cv::{mat_to_rgb_image, rgb_image_to_mat};
+use crate::utils::geometry::{rasterize_polygons, Anchor, BoxF};
+
+/// 静态遮挡区域的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PrivacyMaskMethod {
+    /// 纯色填充（RGB），输出画面中遮挡区域像素恒定
+    Fill {
+        #[serde(default)]
+        color: [u8; 3],
+    },
+    /// 高斯模糊
+    Blur {
+        #[serde(default = "default_ksize")]
+        ksize: i32,
+    },
+}
+
+impl Default for PrivacyMaskMethod {
+    fn default() -> Self {
+        Self::Fill { color: [0, 0, 0] }
+    }
+}
+
+fn default_ksize() -> i32 {
+    51
+}
+
+/// 每路摄像头固定的隐私遮挡区域配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct PrivacyMaskConfig {
+    /// 多边形顶点，归一化坐标 [x, y]，取值 0..=1
+    pub polygons: Vec<Vec<[f32; 2]>>,
+    #[serde(default)]
+    pub method: PrivacyMaskMethod,
+    /// 过滤检测结果时判断目标是否落在遮挡区域内使用的锚点
+    #[serde(default)]
+    pub anchor: Anchor,
+}
+
+/// 栅格化后的掩码，按分辨率缓存
+struct Raster {
+    width: u32,
+    height: u32,
+    mask: Arc<Vec<u8>>,
+    /// 掩码的外接矩形
+    bounds: Option<Rect>,
+}
+
+/// 静态隐私遮挡：推理前对遮挡区域涂黑或模糊，推理后丢弃落在其中的检测结果；
+/// 标注后再对输出帧处理一次，保证框和标签也不会画进遮挡区域
+pub struct PrivacyMask {
+    config: PrivacyMaskConfig,
+    raster: Mutex<Option<Raster>>,
+}
+
+impl PrivacyMask {
+    pub fn new(config: PrivacyMaskConfig) -> Self {
+        Self {
+            config,
+            raster: Mutex::new(None),
+        }
+    }
+
+    /// 指定分辨率下的二值掩码（255 为遮挡区域）
+    pub fn mask(&self, width: u32, height: u32) -> Arc<Vec<u8>> {
+        self.raster(width, height).0
+    }
+
+    fn raster(&self, width: u32, height: u32) -> (Arc<Vec<u8>>, Option<Rect>) {
+        let mut raster = self.raster.lock();
+        match raster.as_ref() {
+            Some(r) if r.width == width && r.height == height => (r.mask.clone(), r.bounds),
+            _ => {
+                let polygons: Vec<Vec<(f32, f32)>> = self
+                    .config
+                    .polygons
+                    .iter()
+                    .map(|p| {
+                        p.iter()
+                            .map(|[x, y]| (x * width as f32, y * height as f32))
+                            .collect()
+                    })
+                    .collect();
+                let mask = Arc::new(rasterize_polygons(&polygons, width, height));
+                let bounds = mask_bounds(&mask, width, height);
+                *raster = Some(Raster {
+                    width,
+                    height,
+                    mask: mask.clone(),
+                    bounds,
+                });
+                (mask, bounds)
+            }
+        }
+    }
+
+    /// 对一批帧应用遮挡
+    pub fn apply(&self, xs: Vec<DynamicImage>) -> Result<Vec<DynamicImage>> {
+        xs.into_par_iter().map(|x| self.apply_image(x)).collect()
+    }
+
+    pub fn apply_image(&self, x: DynamicImage) -> Result<DynamicImage> {
+        let mut rgb = match x {
+            DynamicImage::ImageRgb8(rgb) => rgb,
+            x => x.to_rgb8(),
+        };
+        let (mask, bounds) = self.raster(rgb.width(), rgb.height());
+        let Some(bounds) = bounds else {
+            return Ok(DynamicImage::ImageRgb8(rgb));
+        };
+
+        match self.config.method {
+            PrivacyMaskMethod::Fill { color } => {
+                for (pixel, &m) in rgb.pixels_mut().zip(mask.iter()) {
+                    if m > 0 {
+                        pixel.0 = color;
+                    }
+                }
+            }
+            PrivacyMaskMethod::Blur { ksize } => {
+                rgb = blur_masked(&rgb, &mask, bounds, ksize)?;
+            }
+        }
+        Ok(DynamicImage::ImageRgb8(rgb))
+    }
+
+    /// 丢弃锚点或整个检测框落在遮挡区域内的结果，`sizes` 为各帧的 (width, height)
+    ///
+    /// 模糊遮挡后模型仍可能在遮挡区域内给出检测，它们不应进入热力图、轨迹和叠加统计。
+    /// 掩码、关键点和多边形与检测框数量一致时按下标一并过滤，否则原样保留。
+    pub fn filter(&self, ys: &[Y], sizes: &[(u32, u32)]) -> Vec<Y> {
+        ys.iter()
+            .zip(sizes)
+            .map(|(y, &(width, height))| self.filter_y(y, width, height))
+            .collect()
+    }
+
+    fn filter_y(&self, y: &Y, width: u32, height: u32) -> Y {
+        let (mask, bounds) = self.raster(width, height);
+        let bboxes = y.bboxes().unwrap_or_default();
+        if bounds.is_none() || bboxes.is_empty() {
+            return y.clone();
+        }
+        let keep: Vec<bool> = bboxes
+            .iter()
+            .map(|b| {
+                let b = BoxF::new(b.xmin(), b.ymin(), b.xmax(), b.ymax());
+                !self.is_masked(&mask, width, height, &b)
+            })
+            .collect();
+        if keep.iter().all(|&k| k) {
+            return y.clone();
+        }
+
+        let mut out = y.clone().with_bboxes(&retain::<Bbox>(bboxes, &keep));
+        if let Some(keypoints) = y.keypoints().filter(|x| x.len() == keep.len()) {
+            out = out.with_keypoints(&retain::<Vec<Keypoint>>(keypoints, &keep));
+        }
+        if let Some(masks) = y.masks().filter(|x| x.len() == keep.len()) {
+            out = out.with_masks(&retain::<Mask>(masks, &keep));
+        }
+        if let Some(polygons) = y.polygons().filter(|x| x.len() == keep.len()) {
+            out = out.with_polygons(&retain::<Polygon>(polygons, &keep));
+        }
+        out
+    }
+
+    /// 锚点在遮挡区域内，或检测框（裁剪到画面内）完全被遮挡
+    fn is_masked(&self, mask: &[u8], width: u32, height: u32, b: &BoxF) -> bool {
+        let at = |x: u32, y: u32| mask[(y * width + x) as usize] > 0;
+        let (ax, ay) = b.anchor(self.config.anchor);
+        let inside = ax >= 0.0 && ay >= 0.0 && ax <= width as f32 && ay <= height as f32;
+        // 底边中点等锚点可能正好落在画面右、下边缘上
+        if inside && at((ax as u32).min(width - 1), (ay as u32).min(height - 1)) {
+            return true;
+        }
+
+        let b = b.clip(width as f32, height as f32);
+        let (x1, y1) = (b.x1.floor() as u32, b.y1.floor() as u32);
+        let (x2, y2) = (
+            (b.x2.ceil() as u32).min(width),
+            (b.y2.ceil() as u32).min(height),
+        );
+        x2 > x1 && y2 > y1 && (y1..y2).all(|y| (x1..x2).all(|x| at(x, y)))
+    }
+}
+
+fn retain<T: Clone>(xs: &[T], keep: &[bool]) -> Vec<T> {
+    xs.iter()
+        .zip(keep)
+        .filter(|(_, &k)| k)
+        .map(|(x, _)| x.clone())
+        .collect()
+}
+
+fn blur_masked(rgb: &RgbImage, mask: &[u8], bounds: Rect, ksize: i32) -> Result<RgbImage> {
+    let mut mat = rgb_image_to_mat(rgb)?;
+    let mut mask_mat = Mat::new_rows_cols_with_default(
+        rgb.height() as i32,
+        rgb.width() as i32,
+        CV_8UC1,
+        Scalar::all(0.0),
+    )?;
+    mask_mat.data_bytes_mut()?.copy_from_slice(mask);
+
+    let k = ksize.max(3) | 1;
+    let mut blurred = Mat::default();
+    imgproc::gaussian_blur_def(&mat.roi(bounds)?, &mut blurred, Size::new(k, k), 0.0)?;
+    let mask_roi = mask_mat.roi(bounds)?;
+    let mut roi = mat.roi_mut(bounds)?;
+    blurred.copy_to_masked(&mut roi, &mask_roi)?;
+    mat_to_rgb_image(&mat)
+}
+
+fn mask_bounds(mask: &[u8], width: u32, height: u32) -> Option<Rect> {
+    let (w, h) = (width as i32, height as i32);
+    let (mut x1, mut y1, mut x2, mut y2) = (w, h, -1, -1);
+    for (i, _) in mask.iter().enumerate().filter(|(_, &m)| m > 0) {
+        let (x, y) = ((i as i32) % w, (i as i32) / w);
+        x1 = x1.min(x);
+        y1 = y1.min(y);
+        x2 = x2.max(x);
+        y2 = y2.max(y);
+    }
+    (x2 >= x1 && y2 >= y1).then(|| Rect::new(x1, y1, x2 - x1 + 1, y2 - y1 + 1))
+}
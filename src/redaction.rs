@@ -0,0 +1,331 @@
+use anyhow::Result;
+use image::DynamicImage;
+use opencv::core::{Mat, Rect, Scalar, Size, CV_8UC1, CV_8UC3};
+use opencv::{imgproc, prelude::*};
+use rayon::prelude::*;
+use serde::Deserialize;
+use usls::{Bbox, Keypoint, Mask, Mbr, Polygon, Y};
+
+use crate::utils::cv::{clip_rect, dynamic_image_to_mat, mat_to_rgb_image};
+
+/// 打码方式
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RedactMethod {
+    /// 高斯模糊，核大小为目标短边乘以 `ratio`
+    Blur {
+        #[serde(default = "default_blur_ratio")]
+        ratio: f32,
+    },
+    /// 马赛克，`block` 为色块边长（像素）
+    Pixelate {
+        #[serde(default = "default_block")]
+        block: i32,
+    },
+    /// 纯色填充（RGB）
+    Fill {
+        #[serde(default)]
+        color: [u8; 3],
+    },
+}
+
+/// 打码区域
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactRegion {
+    /// 整个检测框
+    Box,
+    /// 分割掩码，目标没有掩码时退化为检测框
+    Mask,
+}
+
+/// 打码与标注的关系
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactMode {
+    /// 先打码，再照常绘制所有目标的框和标签
+    RedactThenAnnotate,
+    /// 只打码，被打码的目标不再绘制框和标签
+    RedactOnly,
+}
+
+/// 隐私打码配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RedactionConfig {
+    /// 需要打码的类别名称
+    #[serde(default)]
+    pub classes: Vec<String>,
+    /// 需要打码的类别编号
+    #[serde(default)]
+    pub class_ids: Vec<usize>,
+    pub method: RedactMethod,
+    #[serde(default = "default_region")]
+    pub region: RedactRegion,
+    #[serde(default = "default_mode")]
+    pub mode: RedactMode,
+    /// 检测框向外扩展的比例，避免边缘漏出
+    #[serde(default)]
+    pub padding: f32,
+}
+
+fn default_blur_ratio() -> f32 {
+    0.5
+}
+
+fn default_block() -> i32 {
+    16
+}
+
+fn default_region() -> RedactRegion {
+    RedactRegion::Box
+}
+
+fn default_mode() -> RedactMode {
+    RedactMode::RedactThenAnnotate
+}
+
+/// 隐私打码：位于推理与编码之间，对指定类别的目标做模糊、马赛克或填充
+///
+/// 打码与是否开启标注无关，所有输出帧都会经过这一步。
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    config: RedactionConfig,
+}
+
+impl Redactor {
+    pub fn new(config: RedactionConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &RedactionConfig {
+        &self.config
+    }
+
+    /// 是否为需要打码的目标
+    pub fn is_target(&self, id: isize, name: Option<&str>) -> bool {
+        (id >= 0 && self.config.class_ids.contains(&(id as usize)))
+            || name.is_some_and(|n| self.config.classes.iter().any(|c| c == n))
+    }
+
+    fn is_target_bbox(&self, b: &Bbox) -> bool {
+        self.is_target(b.id(), b.name().map(|n| n.as_str()))
+    }
+
+    /// 对一批帧打码
+    pub fn redact(&self, xs: Vec<DynamicImage>, ys: &[Y]) -> Result<Vec<DynamicImage>> {
+        xs.into_par_iter()
+            .zip(ys.par_iter())
+            .map(|(x, y)| self.redact_image(x, y))
+            .collect()
+    }
+
+    /// 对单帧打码，没有目标时原样返回
+    pub fn redact_image(&self, x: DynamicImage, y: &Y) -> Result<DynamicImage> {
+        let has_target = y
+            .bboxes()
+            .unwrap_or_default()
+            .iter()
+            .any(|b| self.is_target_bbox(b));
+        if !has_target {
+            return Ok(x);
+        }
+
+        let mut mat = dynamic_image_to_mat(&x)?;
+        self.redact_mat(&mut mat, y)?;
+        Ok(DynamicImage::ImageRgb8(mat_to_rgb_image(&mat)?))
+    }
+
+    /// 在 RGB 通道顺序的 Mat 上原地打码
+    pub fn redact_mat(&self, mat: &mut Mat, y: &Y) -> Result<()> {
+        let (cols, rows) = (mat.cols(), mat.rows());
+        let mask = match self.config.region {
+            RedactRegion::Mask => self.union_mask(y, cols, rows)?,
+            RedactRegion::Box => None,
+        };
+
+        for b in y
+            .bboxes()
+            .unwrap_or_default()
+            .iter()
+            .filter(|b| self.is_target_bbox(b))
+        {
+            let (pw, ph) = (
+                b.width() * self.config.padding,
+                b.height() * self.config.padding,
+            );
+            let rect = Rect::new(
+                (b.xmin() - pw).floor() as i32,
+                (b.ymin() - ph).floor() as i32,
+                (b.width() + 2.0 * pw).ceil() as i32,
+                (b.height() + 2.0 * ph).ceil() as i32,
+            );
+            let Some(rect) = clip_rect(rect, cols, rows) else {
+                continue;
+            };
+
+            // 框内有掩码像素时只处理掩码区域
+            let mask_roi = match &mask {
+                Some(m) => {
+                    let roi = m.roi(rect)?.try_clone()?;
+                    (opencv::core::count_non_zero(&roi)? > 0).then_some(roi)
+                }
+                None => None,
+            };
+            self.apply(mat, rect, mask_roi.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// 被打码目标之外、仍需绘制的结果
+    ///
+    /// `RedactOnly` 模式下去掉打码目标的检测框及其关键点、掩码、多边形和旋转框。
+    pub fn visible(&self, ys: &[Y]) -> Vec<Y> {
+        match self.config.mode {
+            RedactMode::RedactThenAnnotate => ys.to_vec(),
+            RedactMode::RedactOnly => ys.iter().map(|y| self.visible_y(y)).collect(),
+        }
+    }
+
+    fn visible_y(&self, y: &Y) -> Y {
+        let bboxes = y.bboxes().unwrap_or_default();
+        let keep: Vec<bool> = bboxes.iter().map(|b| !self.is_target_bbox(b)).collect();
+        let kept: Vec<Bbox> = bboxes
+            .iter()
+            .zip(&keep)
+            .filter(|(_, &k)| k)
+            .map(|(b, _)| b.clone())
+            .collect();
+        let mut out = Y::default().with_bboxes(&kept);
+
+        // 关键点与检测框按下标对应，对应不上时全部丢弃，避免泄露打码目标
+        if let Some(keypoints) = y.keypoints() {
+            if keypoints.len() == keep.len() {
+                let kept: Vec<Vec<Keypoint>> = keypoints
+                    .iter()
+                    .zip(&keep)
+                    .filter(|(_, &k)| k)
+                    .map(|(x, _)| x.clone())
+                    .collect();
+                out = out.with_keypoints(&kept);
+            }
+        }
+
+        let masks: Vec<Mask> = y
+            .masks()
+            .unwrap_or_default()
+            .iter()
+            .filter(|m| !self.is_target(m.id(), m.name().map(|n| n.as_str())))
+            .cloned()
+            .collect();
+        let polygons: Vec<Polygon> = y
+            .polygons()
+            .unwrap_or_default()
+            .iter()
+            .filter(|p| !self.is_target(p.id(), p.name().map(|n| n.as_str())))
+            .cloned()
+            .collect();
+        let mbrs: Vec<Mbr> = y
+            .mbrs()
+            .unwrap_or_default()
+            .iter()
+            .filter(|m| !self.is_target(m.id(), m.name().map(|n| n.as_str())))
+            .cloned()
+            .collect();
+        out.with_masks(&masks)
+            .with_polygons(&polygons)
+            .with_mbrs(&mbrs)
+    }
+
+    /// 与 [`Self::visible`] 对应的跟踪 ID，`tracks[i][j]` 为第 i 帧第 j 个检测框的跟踪 ID
+    pub fn visible_tracks(&self, ys: &[Y], tracks: &[Vec<Option<u64>>]) -> Vec<Vec<Option<u64>>> {
+        match self.config.mode {
+            RedactMode::RedactThenAnnotate => tracks.to_vec(),
+            RedactMode::RedactOnly => ys
+                .iter()
+                .zip(tracks)
+                .map(|(y, ids)| {
+                    y.bboxes()
+                        .unwrap_or_default()
+                        .iter()
+                        .zip(ids)
+                        .filter(|(b, _)| !self.is_target_bbox(b))
+                        .map(|(_, id)| *id)
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    /// 所有打码目标掩码的并集，没有掩码时返回 None
+    fn union_mask(&self, y: &Y, cols: i32, rows: i32) -> Result<Option<Mat>> {
+        let masks: Vec<_> = y
+            .masks()
+            .unwrap_or_default()
+            .iter()
+            .filter(|m| self.is_target(m.id(), m.name().map(|n| n.as_str())))
+            .map(|m| m.mask())
+            .filter(|m| m.width() as i32 == cols && m.height() as i32 == rows)
+            .collect();
+        if masks.is_empty() {
+            return Ok(None);
+        }
+
+        let mut union = Mat::new_rows_cols_with_default(rows, cols, CV_8UC1, Scalar::all(0.0))?;
+        let data = union.data_bytes_mut()?;
+        for m in masks {
+            for (u, &v) in data.iter_mut().zip(m.as_raw()) {
+                if v > 0 {
+                    *u = 255;
+                }
+            }
+        }
+        Ok(Some(union))
+    }
+
+    fn apply(&self, mat: &mut Mat, rect: Rect, mask: Option<&Mat>) -> Result<()> {
+        let mut effect = Mat::default();
+        {
+            let roi = mat.roi(rect)?;
+            match self.config.method {
+                RedactMethod::Blur { ratio } => {
+                    let k = ((rect.width.min(rect.height) as f32 * ratio) as i32 | 1).clamp(3, 255);
+                    imgproc::gaussian_blur_def(&roi, &mut effect, Size::new(k, k), 0.0)?;
+                }
+                RedactMethod::Pixelate { block } => {
+                    let block = block.max(1);
+                    let mut small = Mat::default();
+                    imgproc::resize(
+                        &roi,
+                        &mut small,
+                        Size::new((rect.width / block).max(1), (rect.height / block).max(1)),
+                        0.0,
+                        0.0,
+                        imgproc::INTER_LINEAR,
+                    )?;
+                    imgproc::resize(
+                        &small,
+                        &mut effect,
+                        rect.size(),
+                        0.0,
+                        0.0,
+                        imgproc::INTER_NEAREST,
+                    )?;
+                }
+                RedactMethod::Fill { color } => {
+                    effect = Mat::new_size_with_default(
+                        rect.size(),
+                        CV_8UC3,
+                        Scalar::new(color[0] as f64, color[1] as f64, color[2] as f64, 0.0),
+                    )?;
+                }
+            }
+        }
+
+        let mut roi = mat.roi_mut(rect)?;
+        match mask {
+            Some(mask) => effect.copy_to_masked(&mut roi, mask)?,
+            None => effect.copy_to(&mut roi)?,
+        }
+        Ok(())
+    }
+}
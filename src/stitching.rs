@@ -0,0 +1,550 @@
+use anyhow::{anyhow, Context, Result};
+use image::{imageops, DynamicImage};
+use opencv::core::{self, Mat, Point, Ptr, Rect, Scalar, Size, UMat, Vector};
+use opencv::prelude::*;
+use opencv::stitching::{
+    Detail_CameraParams, Detail_FeatherBlender, PyRotationWarper, Stitcher, Stitcher_Mode,
+    Stitcher_Status,
+};
+use opencv::{imgcodecs, imgproc};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::utils::crop::mask_valid_rect;
+use crate::utils::cv::{dynamic_image_to_mat, mat_to_rgb_image};
+
+/// 拼接模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StitchMode {
+    /// 相机绕光心旋转拍摄的全景
+    #[default]
+    Panorama,
+    /// 平移拍摄的平面场景（仿射模型）
+    Scans,
+}
+
+/// 多路相机实时拼接配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StitchConfig {
+    /// 参与拼接的视频源，按从左到右的顺序
+    pub sources: Vec<String>,
+    pub mode: StitchMode,
+    /// 配准分辨率（百万像素）
+    pub registration_resol: f64,
+    /// 接缝估计分辨率（百万像素）
+    pub seam_estimation_resol: f64,
+    /// 合成分辨率（百万像素），小于 0 时使用原始分辨率
+    pub compositing_resol: f64,
+    /// 图像匹配的置信度阈值
+    pub confidence_thresh: f64,
+    pub wave_correction: bool,
+    /// 标定用的静态图像（每路一张），为空时使用视频流最开始的帧
+    pub calibration_images: Vec<String>,
+    /// 用视频帧标定时最多尝试的帧组数
+    pub calibration_attempts: usize,
+    /// 是否裁掉黑边，只保留最大的有效矩形
+    pub crop: bool,
+    /// 输出尺寸 [宽, 高]，不设置时保持拼接结果的尺寸
+    pub output_size: Option<[u32; 2]>,
+}
+
+impl Default for StitchConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            mode: StitchMode::Panorama,
+            registration_resol: 0.6,
+            seam_estimation_resol: 0.1,
+            compositing_resol: -1.0,
+            confidence_thresh: 1.0,
+            wave_correction: true,
+            calibration_images: Vec::new(),
+            calibration_attempts: 25,
+            crop: true,
+            output_size: None,
+        }
+    }
+}
+
+/// 按相机参数构建某一缩放比例下的重映射表，返回表和在全景中的位置
+fn build_maps(
+    warper: &mut PyRotationWarper,
+    camera: &Detail_CameraParams,
+    aspect: f64,
+    size: Size,
+) -> Result<(Mat, Mat, Rect)> {
+    let f = camera.focal() * aspect;
+    let k = Mat::from_slice_2d(&[
+        [f as f32, 0.0, (camera.ppx() * aspect) as f32],
+        [
+            0.0,
+            (f * camera.aspect()) as f32,
+            (camera.ppy() * aspect) as f32,
+        ],
+        [0.0, 0.0, 1.0],
+    ])?;
+    let mut r = Mat::default();
+    camera.r().convert_to(&mut r, core::CV_32F, 1.0, 0.0)?;
+    let (mut xmap, mut ymap) = (Mat::default(), Mat::default());
+    let roi = warper.build_maps(size, &k, &r, &mut xmap, &mut ymap)?;
+    Ok((xmap, ymap, roi))
+}
+
+/// 整帧有效掩码经重映射后的结果
+fn warp_mask(size: Size, xmap: &Mat, ymap: &Mat) -> Result<Mat> {
+    let full = Mat::new_size_with_default(size, core::CV_8U, Scalar::all(255.0))?;
+    let mut mask = Mat::default();
+    imgproc::remap(
+        &full,
+        &mut mask,
+        xmap,
+        ymap,
+        imgproc::INTER_NEAREST,
+        core::BORDER_CONSTANT,
+        Scalar::default(),
+    )?;
+    Ok(mask)
+}
+
+/// 请求重新标定的句柄，可在其它线程或任务中触发
+#[derive(Debug, Clone, Default)]
+pub struct RecalibrateHandle(Arc<AtomicBool>);
+
+impl RecalibrateHandle {
+    pub fn request(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// 单路相机的缓存变换：从原始帧到全景的重映射表和归一化的融合权重
+struct View {
+    /// 在输入帧中的下标
+    index: usize,
+    xmap: Mat,
+    ymap: Mat,
+    /// 在全景图中的位置
+    roi: Rect,
+    /// 三通道 CV_32F 羽化权重，重叠区域内各路之和为 1
+    weight: Mat,
+}
+
+/// 标定得到的变换，标定之后的帧只做重映射与加权融合
+struct Calibration {
+    sizes: Vec<Size>,
+    views: Vec<View>,
+    size: Size,
+    crop: Option<Rect>,
+}
+
+/// 多路相机全景拼接：用一组标定帧估计一次相机变换并缓存，之后逐帧变换、融合
+pub struct PanoramaStitcher {
+    config: StitchConfig,
+    stitcher: Ptr<Stitcher>,
+    calibration: Option<Calibration>,
+    recalibrate: RecalibrateHandle,
+    attempts: usize,
+}
+
+impl PanoramaStitcher {
+    pub fn new(config: StitchConfig) -> Result<Self> {
+        let mode = match config.mode {
+            StitchMode::Panorama => Stitcher_Mode::PANORAMA,
+            StitchMode::Scans => Stitcher_Mode::SCANS,
+        };
+        let mut stitcher = Stitcher::create(mode)?;
+        stitcher.set_registration_resol(config.registration_resol)?;
+        stitcher.set_seam_estimation_resol(config.seam_estimation_resol)?;
+        stitcher.set_compositing_resol(config.compositing_resol)?;
+        stitcher.set_panorama_confidence_thresh(config.confidence_thresh)?;
+        stitcher.set_wave_correction(config.wave_correction)?;
+
+        let mut this = Self {
+            config,
+            stitcher,
+            calibration: None,
+            recalibrate: RecalibrateHandle::default(),
+            attempts: 0,
+        };
+        if !this.config.calibration_images.is_empty() {
+            let frames = this
+                .config
+                .calibration_images
+                .iter()
+                .map(|path| {
+                    let mat = imgcodecs::imread(path, imgcodecs::IMREAD_COLOR)?;
+                    if mat.empty() {
+                        return Err(anyhow!("Failed to read calibration image: {:?}", path));
+                    }
+                    // imread 读入 BGR，与视频帧保持一致转为 RGB
+                    let mut rgb = Mat::default();
+                    imgproc::cvt_color_def(&mat, &mut rgb, imgproc::COLOR_BGR2RGB)?;
+                    Ok(rgb)
+                })
+                .collect::<Result<Vector<Mat>>>()?;
+            this.calibrate(&frames)?;
+        }
+        Ok(this)
+    }
+
+    pub fn config(&self) -> &StitchConfig {
+        &self.config
+    }
+
+    pub fn is_calibrated(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// 重新标定句柄，调用 `request()` 后下一组帧会重新估计变换
+    pub fn recalibrate_handle(&self) -> RecalibrateHandle {
+        self.recalibrate.clone()
+    }
+
+    /// 用一组同步帧（每路一帧）估计相机变换，并缓存重映射表、接缝和融合权重
+    ///
+    /// 流程与 `Stitcher::composePanorama` 一致（不做曝光补偿），但只在标定时执行一次，
+    /// 之后的帧只做重映射和羽化融合。
+    pub fn calibrate(&mut self, frames: &Vector<Mat>) -> Result<()> {
+        let status = self.stitcher.estimate_transform_def(frames)?;
+        if status != Stitcher_Status::OK {
+            return Err(anyhow!(
+                "Failed to estimate stitching transform: {:?}",
+                status
+            ));
+        }
+
+        let cameras = self.stitcher.cameras()?;
+        let component = self.stitcher.component()?;
+        let work_scale = self.stitcher.work_scale()?;
+        if cameras.is_empty() || cameras.len() != component.len() {
+            return Err(anyhow!(
+                "Stitching calibration produced {} cameras for {} images",
+                cameras.len(),
+                component.len()
+            ));
+        }
+        let sizes = frames
+            .iter()
+            .map(|m| m.size())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let area = sizes[0].area() as f64;
+        let scale_for = |resol: f64| {
+            if resol < 0.0 {
+                1.0
+            } else {
+                (resol * 1e6 / area).sqrt().min(1.0)
+            }
+        };
+        let seam_scale = scale_for(self.config.seam_estimation_resol);
+        let compose_scale = scale_for(self.config.compositing_resol);
+
+        // 与 Stitcher 一致，全景投影的尺度取焦距的中位数
+        let mut focals: Vec<f64> = cameras.iter().map(|c| c.focal()).collect();
+        focals.sort_by(f64::total_cmp);
+        let n = focals.len();
+        let warped_scale = if n % 2 == 1 {
+            focals[n / 2]
+        } else {
+            (focals[n / 2 - 1] + focals[n / 2]) * 0.5
+        };
+        let kind = match self.config.mode {
+            StitchMode::Panorama => "spherical",
+            StitchMode::Scans => "affine",
+        };
+
+        // 低分辨率下估计接缝
+        let seam_aspect = seam_scale / work_scale;
+        let mut warper = PyRotationWarper::new(kind, (warped_scale * seam_aspect) as f32)?;
+        let mut images = Vector::<UMat>::new();
+        let mut seams = Vector::<UMat>::new();
+        let mut corners = Vector::<Point>::new();
+        for (index, camera) in component.iter().zip(cameras.iter()) {
+            let mut small = Mat::default();
+            imgproc::resize(
+                &frames.get(index as usize)?,
+                &mut small,
+                Size::default(),
+                seam_scale,
+                seam_scale,
+                imgproc::INTER_LINEAR_EXACT,
+            )?;
+            let (xmap, ymap, roi) = build_maps(&mut warper, &camera, seam_aspect, small.size()?)?;
+            let mut warped = Mat::default();
+            imgproc::remap(
+                &small,
+                &mut warped,
+                &xmap,
+                &ymap,
+                imgproc::INTER_LINEAR,
+                core::BORDER_REFLECT,
+                Scalar::default(),
+            )?;
+            let mut image = UMat::new_def();
+            warped.convert_to(&mut image, core::CV_32F, 1.0, 0.0)?;
+            images.push(image);
+            let mut seam = UMat::new_def();
+            warp_mask(small.size()?, &xmap, &ymap)?.copy_to(&mut seam)?;
+            seams.push(seam);
+            corners.push(roi.tl());
+        }
+        self.stitcher
+            .seam_finder()?
+            .find(&images, &corners, &mut seams)?;
+
+        // 合成分辨率下的重映射表和融合掩码
+        let compose_aspect = compose_scale / work_scale;
+        let mut warper = PyRotationWarper::new(kind, (warped_scale * compose_aspect) as f32)?;
+        let mut views = Vec::with_capacity(component.len());
+        let mut masks = Vector::<UMat>::new();
+        let mut valid = Vec::with_capacity(component.len());
+        let mut corners = Vector::<Point>::new();
+        for (i, (index, camera)) in component.iter().zip(cameras.iter()).enumerate() {
+            let size = sizes[index as usize];
+            let scaled = Size::new(
+                (size.width as f64 * compose_scale).round() as i32,
+                (size.height as f64 * compose_scale).round() as i32,
+            );
+            let (xmap, ymap, roi) = build_maps(&mut warper, &camera, compose_aspect, scaled)?;
+            let mask = warp_mask(scaled, &xmap, &ymap)?;
+
+            // 接缝掩码膨胀后放大到合成分辨率，再与有效区域取交
+            let mut seam = Mat::default();
+            seams.get(i)?.copy_to(&mut seam)?;
+            let mut dilated = Mat::default();
+            imgproc::dilate_def(&seam, &mut dilated, &Mat::default())?;
+            let mut resized = Mat::default();
+            imgproc::resize(
+                &dilated,
+                &mut resized,
+                mask.size()?,
+                0.0,
+                0.0,
+                imgproc::INTER_LINEAR_EXACT,
+            )?;
+            let mut blend = UMat::new_def();
+            core::bitwise_and_def(&resized, &mask, &mut blend)?;
+
+            // 重映射表换算到原始分辨率，逐帧拼接时不再缩放输入帧
+            let (xmap, ymap) = if compose_scale < 1.0 {
+                let (mut x, mut y) = (Mat::default(), Mat::default());
+                xmap.convert_to(&mut x, core::CV_32F, 1.0 / compose_scale, 0.0)?;
+                ymap.convert_to(&mut y, core::CV_32F, 1.0 / compose_scale, 0.0)?;
+                (x, y)
+            } else {
+                (xmap, ymap)
+            };
+
+            masks.push(blend);
+            corners.push(roi.tl());
+            valid.push(mask);
+            views.push(View {
+                index: index as usize,
+                xmap,
+                ymap,
+                roi,
+                weight: Mat::default(),
+            });
+        }
+
+        let mut blender = Detail_FeatherBlender::new_def()?;
+        let mut weights = Vector::<UMat>::new();
+        let dst = blender.create_weight_maps(&masks, &corners, &mut weights)?;
+        let mut union = Mat::new_size_with_default(dst.size(), core::CV_8U, Scalar::all(0.0))?;
+        for (i, view) in views.iter_mut().enumerate() {
+            view.roi = Rect::new(
+                view.roi.x - dst.x,
+                view.roi.y - dst.y,
+                view.roi.width,
+                view.roi.height,
+            );
+            let mut weight = Mat::default();
+            weights.get(i)?.copy_to(&mut weight)?;
+            let channels =
+                Vector::<Mat>::from_iter([weight.try_clone()?, weight.try_clone()?, weight]);
+            core::merge(&channels, &mut view.weight)?;
+            valid[i].copy_to_masked(&mut union.roi_mut(view.roi)?, &valid[i])?;
+        }
+
+        let crop = if self.config.crop {
+            mask_valid_rect(&union)?
+        } else {
+            None
+        };
+
+        tracing::info!(
+            "Stitching calibrated with {} sources, panorama {}x{}, crop {:?}",
+            frames.len(),
+            dst.width,
+            dst.height,
+            crop
+        );
+        self.calibration = Some(Calibration {
+            sizes,
+            views,
+            size: dst.size(),
+            crop,
+        });
+        self.attempts = 0;
+        Ok(())
+    }
+
+    /// 拼接一组同步帧，未标定或请求了重新标定时先用这组帧标定
+    ///
+    /// 重新标定失败时继续使用原有标定；用静态图像标定后帧尺寸与标定图像不一致时返回错误，
+    /// 而不是改用视频帧重新标定。
+    pub fn stitch(&mut self, frames: &Vector<Mat>) -> Result<Mat> {
+        let sizes = frames
+            .iter()
+            .map(|m| m.size())
+            .collect::<Result<Vec<_>, _>>()?;
+        let resized = match &self.calibration {
+            Some(c) if c.sizes != sizes => {
+                if !self.config.calibration_images.is_empty() {
+                    return Err(anyhow!(
+                        "Frame sizes {:?} differ from the calibration images {:?}",
+                        sizes,
+                        c.sizes
+                    ));
+                }
+                tracing::warn!(
+                    "Frame sizes changed from {:?} to {:?}, recalibrating",
+                    c.sizes,
+                    sizes
+                );
+                true
+            }
+            _ => false,
+        };
+        if self.recalibrate.take() || resized || self.calibration.is_none() {
+            self.attempts += 1;
+            let result = self.calibrate(frames).with_context(|| {
+                format!(
+                    "Stitching calibration attempt {}/{} failed",
+                    self.attempts, self.config.calibration_attempts
+                )
+            });
+            if let Err(e) = result {
+                if resized || self.calibration.is_none() {
+                    // 原有标定与当前帧尺寸不符，无法继续使用
+                    self.calibration = None;
+                    return Err(e);
+                }
+                tracing::warn!("{:?}, keeping the previous calibration", e);
+            }
+        }
+        let calibration = self
+            .calibration
+            .as_ref()
+            .ok_or_else(|| anyhow!("Stitcher is not calibrated"))?;
+
+        // 逐帧只做重映射和加权累加
+        let mut acc =
+            Mat::new_size_with_default(calibration.size, core::CV_32FC3, Scalar::all(0.0))?;
+        for view in &calibration.views {
+            let mut warped = Mat::default();
+            imgproc::remap(
+                &frames.get(view.index)?,
+                &mut warped,
+                &view.xmap,
+                &view.ymap,
+                imgproc::INTER_LINEAR,
+                core::BORDER_REFLECT,
+                Scalar::default(),
+            )?;
+            let mut warped_f = Mat::default();
+            warped.convert_to(&mut warped_f, core::CV_32F, 1.0, 0.0)?;
+            imgproc::accumulate_product(
+                &warped_f,
+                &view.weight,
+                &mut acc.roi_mut(view.roi)?,
+                &core::no_array(),
+            )?;
+        }
+        let mut pano = Mat::default();
+        acc.convert_to(&mut pano, core::CV_8U, 1.0, 0.0)?;
+
+        match calibration.crop {
+            Some(rect) => Ok(pano.roi(rect)?.try_clone()?),
+            None => Ok(pano),
+        }
+    }
+
+    /// 拼接一组同步的 RGB 帧
+    pub fn stitch_images(&mut self, frames: &[DynamicImage]) -> Result<DynamicImage> {
+        let mats = frames
+            .iter()
+            .map(dynamic_image_to_mat)
+            .collect::<Result<Vector<Mat>>>()?;
+        let pano = DynamicImage::ImageRgb8(mat_to_rgb_image(&self.stitch(&mats)?)?);
+        Ok(match self.config.output_size {
+            Some([w, h]) if (w, h) != (pano.width(), pano.height()) => {
+                pano.resize_exact(w, h, imageops::FilterType::Triangle)
+            }
+            _ => pano,
+        })
+    }
+
+    /// 标定失败次数是否已超过上限
+    pub fn exhausted(&self) -> bool {
+        !self.is_calibrated() && self.attempts >= self.config.calibration_attempts
+    }
+}
+
+/// 将多路视频源的批次按顺序对齐，逐帧拼接成一路全景流
+pub struct StitchedBatches<I> {
+    sources: Vec<I>,
+    stitcher: PanoramaStitcher,
+}
+
+impl<I> StitchedBatches<I>
+where
+    I: Iterator<Item = Vec<DynamicImage>>,
+{
+    pub fn new(sources: Vec<I>, stitcher: PanoramaStitcher) -> Self {
+        Self { sources, stitcher }
+    }
+}
+
+impl<I> Iterator for StitchedBatches<I>
+where
+    I: Iterator<Item = Vec<DynamicImage>>,
+{
+    type Item = Vec<DynamicImage>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // 任意一路结束时整体结束
+            let batches = self
+                .sources
+                .iter_mut()
+                .map(|s| s.next())
+                .collect::<Option<Vec<_>>>()?;
+            let n = batches.iter().map(|b| b.len()).min().unwrap_or(0);
+
+            let mut panoramas = Vec::with_capacity(n);
+            for i in 0..n {
+                let frames: Vec<DynamicImage> = batches.iter().map(|b| b[i].clone()).collect();
+                match self.stitcher.stitch_images(&frames) {
+                    Ok(pano) => panoramas.push(pano),
+                    Err(e) => {
+                        tracing::warn!("Panorama stitching failed, frame dropped: {:?}", e);
+                        if self.stitcher.exhausted() {
+                            tracing::error!("Stitching calibration failed too many times");
+                            return None;
+                        }
+                    }
+                }
+            }
+            if !panoramas.is_empty() {
+                return Some(panoramas);
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, GenericImageView};
+use usls::Y;
+
+use crate::backend::InferenceBackend;
+use crate::postprocess::{
+    class_names_from_ys, greedy_nmm_groups, instances_from_y, nms, y_from_instances, Detection,
+    Instance, NmsConfig,
+};
+
+/// 切片结果的合并方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TileMerge {
+    /// 同类 NMS，保留分数最高的框
+    Nms,
+    /// 贪心非极大值合并（按交集/较小框面积），可以拼接被切片边界截断的目标
+    Nmm,
+}
+
+impl TryFrom<&str> for TileMerge {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "nms" => Ok(Self::Nms),
+            "nmm" => Ok(Self::Nmm),
+            x => Err(anyhow!("Unsupported tile merge: {}", x)),
+        }
+    }
+}
+
+/// 切片推理配置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TileConfig {
+    pub tile_width: u32,
+    pub tile_height: u32,
+    /// 相邻切片的重叠比例
+    pub overlap_ratio: f32,
+    /// 是否额外运行一次整帧推理，用于保留大目标
+    pub full_frame: bool,
+    pub merge: TileMerge,
+    /// 合并时的重叠阈值
+    pub match_threshold: f32,
+}
+
+impl Default for TileConfig {
+    fn default() -> Self {
+        Self {
+            tile_width: 640,
+            tile_height: 640,
+            overlap_ratio: 0.2,
+            full_frame: true,
+            merge: TileMerge::Nmm,
+            match_threshold: 0.5,
+        }
+    }
+}
+
+impl TileConfig {
+    pub fn with_tile_size(mut self, width: u32, height: u32) -> Self {
+        self.tile_width = width;
+        self.tile_height = height;
+        self
+    }
+
+    pub fn with_overlap_ratio(mut self, x: f32) -> Self {
+        self.overlap_ratio = x;
+        self
+    }
+
+    pub fn with_full_frame(mut self, x: bool) -> Self {
+        self.full_frame = x;
+        self
+    }
+
+    pub fn with_merge(mut self, x: TileMerge) -> Self {
+        self.merge = x;
+        self
+    }
+
+    pub fn with_match_threshold(mut self, x: f32) -> Self {
+        self.match_threshold = x;
+        self
+    }
+}
+
+/// 切片在原图中的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tile {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 计算覆盖整幅图像的切片网格
+///
+/// 切片按 `overlap_ratio` 重叠排列，最后一行/列贴齐图像边缘；
+/// 图像小于切片尺寸的方向只有一个切片。
+pub fn tile_grid(width: u32, height: u32, config: &TileConfig) -> Vec<Tile> {
+    let xs = axis_starts(width, config.tile_width, config.overlap_ratio);
+    let ys = axis_starts(height, config.tile_height, config.overlap_ratio);
+    ys.iter()
+        .flat_map(|&y| {
+            xs.iter().map(move |&x| Tile {
+                x,
+                y,
+                width: config.tile_width.min(width),
+                height: config.tile_height.min(height),
+            })
+        })
+        .collect()
+}
+
+fn axis_starts(size: u32, tile: u32, overlap_ratio: f32) -> Vec<u32> {
+    if size <= tile {
+        return vec![0];
+    }
+    let step = (tile as f32 * (1.0 - overlap_ratio.clamp(0.0, 0.95)))
+        .round()
+        .max(1.0) as u32;
+    let mut starts = Vec::new();
+    let mut start = 0;
+    while start + tile < size {
+        starts.push(start);
+        start += step;
+    }
+    starts.push(size - tile);
+    starts
+}
+
+/// 切片推理（SAHI）：将每帧拆分为模型输入尺寸的重叠切片批量推理，
+/// 再把检测框映射回整帧坐标并跨切片合并
+///
+/// 掩码贴回整帧位置，合并时取并集；关键点取合并组中分数最高的结果。
+#[derive(Debug, Clone)]
+pub struct TiledInference {
+    config: TileConfig,
+}
+
+impl TiledInference {
+    pub fn new(config: TileConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &TileConfig {
+        &self.config
+    }
+
+    pub fn forward(&self, model: &mut dyn InferenceBackend, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        let mut per_frame: Vec<Vec<Instance>> = vec![Vec::new(); xs.len()];
+        let mut class_names = std::collections::HashMap::new();
+
+        // 整帧推理
+        if self.config.full_frame {
+            let ys = model.forward(xs)?;
+            class_names.extend(class_names_from_ys(&ys));
+            for (instances, y) in per_frame.iter_mut().zip(ys.iter()) {
+                instances.extend(instances_from_y(y));
+            }
+        }
+
+        // 所有帧的切片统一按模型 batch 大小送入推理
+        let mut crops = Vec::new();
+        let mut origins = Vec::new();
+        for (i, x) in xs.iter().enumerate() {
+            let (width, height) = x.dimensions();
+            for t in tile_grid(width, height, &self.config) {
+                crops.push(x.crop_imm(t.x, t.y, t.width, t.height));
+                origins.push((i, t.x, t.y));
+            }
+        }
+
+        let batch = model.batch().max(1);
+        for (chunk, origin_chunk) in crops.chunks(batch).zip(origins.chunks(batch)) {
+            let ys = model.forward(chunk)?;
+            class_names.extend(class_names_from_ys(&ys));
+            for (y, &(i, dx, dy)) in ys.iter().zip(origin_chunk) {
+                let (width, height) = xs[i].dimensions();
+                per_frame[i].extend(
+                    instances_from_y(y)
+                        .into_iter()
+                        .map(|x| x.translate(dx, dy, width, height)),
+                );
+            }
+        }
+
+        Ok(per_frame
+            .iter()
+            .map(|instances| y_from_instances(&self.merge_instances(instances), &class_names))
+            .collect())
+    }
+
+    /// 合并整帧坐标下的切片检测结果
+    pub fn merge(&self, detections: &[Detection]) -> Vec<Detection> {
+        self.merge_groups(detections)
+            .into_iter()
+            .map(|(d, _)| d)
+            .collect()
+    }
+
+    /// 合并整帧坐标下的切片实例，掩码取并集，关键点取分数最高的成员
+    pub fn merge_instances(&self, instances: &[Instance]) -> Vec<Instance> {
+        let detections: Vec<Detection> = instances.iter().map(|x| x.detection).collect();
+        self.merge_groups(&detections)
+            .into_iter()
+            .map(|(d, members)| {
+                let mut merged = instances[members[0]].clone();
+                for &k in &members[1..] {
+                    merged.union_mask(&instances[k]);
+                }
+                merged.detection = d;
+                merged
+            })
+            .collect()
+    }
+
+    /// 返回合并结果及其成员在输入中的下标，第一个成员分数最高
+    fn merge_groups(&self, detections: &[Detection]) -> Vec<(Detection, Vec<usize>)> {
+        match self.config.merge {
+            TileMerge::Nms => {
+                // 暂用 source 记录输入下标
+                let tagged: Vec<Detection> = detections
+                    .iter()
+                    .enumerate()
+                    .map(|(i, d)| d.with_source(i))
+                    .collect();
+                nms(
+                    &tagged,
+                    &NmsConfig::default()
+                        .with_iou_threshold(self.config.match_threshold)
+                        .with_top_k(None),
+                )
+                .into_iter()
+                .map(|d| (d.with_source(detections[d.source].source), vec![d.source]))
+                .collect()
+            }
+            TileMerge::Nmm => {
+                greedy_nmm_groups(detections, self.config.match_threshold, true, false)
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use serde::Deserialize;
+use usls::Y;
+
+use crate::utils::geometry::BoxF;
+use crate::utils::math::{iou_matrix, match_pairs_by_iou};
+
+/// IoU 跟踪器配置
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TrackerConfig {
+    /// 检测框与已有轨迹匹配的最小 IoU
+    pub iou_threshold: f32,
+    /// 连续丢失多少帧后结束轨迹
+    pub max_age: u32,
+    /// 是否只在同类别之间匹配
+    pub class_aware: bool,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            iou_threshold: 0.3,
+            max_age: 30,
+            class_aware: true,
+        }
+    }
+}
+
+/// 一条活动轨迹
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: u64,
+    pub class_id: isize,
+    pub name: Option<String>,
+    /// 最近一次匹配到的检测框
+    pub bbox: BoxF,
+    /// 累计匹配次数
+    pub hits: u32,
+    /// 连续丢失帧数
+    pub missed: u32,
+}
+
+/// 单帧跟踪结果
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackUpdate {
+    /// 每个检测框对应的跟踪 ID，顺序与 `Y::bboxes()` 一致
+    pub ids: Vec<Option<u64>>,
+    /// 本帧结束的轨迹
+    pub ended: Vec<Track>,
+}
+
+/// 基于相邻帧检测框 IoU 贪心匹配的轻量跟踪器
+#[derive(Debug, Clone, Default)]
+pub struct IouTracker {
+    config: TrackerConfig,
+    tracks: Vec<Track>,
+    next_id: u64,
+}
+
+impl IouTracker {
+    pub fn new(config: TrackerConfig) -> Self {
+        Self {
+            config,
+            tracks: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// 当前活动的轨迹
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// 按时间顺序输入一帧的检测结果
+    pub fn update(&mut self, y: &Y) -> TrackUpdate {
+        let bboxes = y.bboxes().unwrap_or_default();
+        let dets: Vec<[f32; 4]> = bboxes
+            .iter()
+            .map(|b| [b.xmin(), b.ymin(), b.xmax(), b.ymax()])
+            .collect();
+        let prev: Vec<[f32; 4]> = self.tracks.iter().map(|t| t.bbox.xyxy()).collect();
+
+        let mut ious = iou_matrix(&prev, &dets);
+        if self.config.class_aware {
+            for (t, row) in self.tracks.iter().zip(ious.iter_mut()) {
+                for (b, iou) in bboxes.iter().zip(row.iter_mut()) {
+                    if b.id() != t.class_id {
+                        *iou = 0.0;
+                    }
+                }
+            }
+        }
+
+        let mut ids = vec![None; bboxes.len()];
+        let mut matched = vec![false; self.tracks.len()];
+        for (t, d) in match_pairs_by_iou(&ious, self.config.iou_threshold) {
+            let track = &mut self.tracks[t];
+            track.bbox = BoxF::from_xyxy(dets[d]);
+            track.hits += 1;
+            track.missed = 0;
+            matched[t] = true;
+            ids[d] = Some(track.id);
+        }
+
+        for (track, matched) in self.tracks.iter_mut().zip(&matched) {
+            if !matched {
+                track.missed += 1;
+            }
+        }
+        let max_age = self.config.max_age;
+        let (ended, alive): (Vec<Track>, Vec<Track>) = std::mem::take(&mut self.tracks)
+            .into_iter()
+            .partition(|t| t.missed > max_age);
+        self.tracks = alive;
+
+        for (i, b) in bboxes.iter().enumerate() {
+            if ids[i].is_none() {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.tracks.push(Track {
+                    id,
+                    class_id: b.id(),
+                    name: b.name().cloned(),
+                    bbox: BoxF::from_xyxy(dets[i]),
+                    hits: 1,
+                    missed: 0,
+                });
+                ids[i] = Some(id);
+            }
+        }
+
+        TrackUpdate { ids, ended }
+    }
+
+    /// 结束所有活动轨迹（例如视频流结束时）
+    pub fn finish(&mut self) -> Vec<Track> {
+        std::mem::take(&mut self.tracks)
+    }
+}
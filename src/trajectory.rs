@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use image::{DynamicImage, Rgb, RgbImage};
+use imageproc::drawing::draw_antialiased_line_segment_mut;
+use imageproc::pixelops::interpolate;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+use usls::Y;
+
+use crate::annotation::PALETTE;
+use crate::tracker::{IouTracker, Track, TrackerConfig};
+use crate::utils::geometry::{simplify_polyline_indices, Anchor, BoxF};
+
+/// 轨迹配置
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct TrajectoryConfig {
+    pub tracker: TrackerConfig,
+    /// 记录轨迹使用的锚点
+    pub anchor: Anchor,
+    /// 画面上绘制的最近轨迹点数
+    pub history: usize,
+    /// 是否在输出画面上绘制轨迹
+    pub trails: bool,
+    /// 轨迹线宽
+    pub thickness: u32,
+    /// 越早的轨迹段越透明
+    pub fade: bool,
+    /// Douglas–Peucker 简化容差（归一化坐标）
+    pub epsilon: f32,
+    /// 单条轨迹在内存中保留的最大点数，超过时就地简化
+    pub max_points: usize,
+    /// 点数少于该值的轨迹不导出
+    pub min_points: usize,
+    /// 轨迹结束时追加写入的 JSON Lines 文件
+    pub export: Option<String>,
+    /// 输入帧率，用于按帧序号推算每帧的时间戳
+    pub fps: f64,
+}
+
+impl Default for TrajectoryConfig {
+    fn default() -> Self {
+        Self {
+            tracker: TrackerConfig::default(),
+            anchor: Anchor::BottomCenter,
+            history: 50,
+            trails: true,
+            thickness: 2,
+            fade: true,
+            epsilon: 0.002,
+            max_points: 5000,
+            min_points: 2,
+            export: None,
+            fps: 25.0,
+        }
+    }
+}
+
+/// 轨迹点：归一化坐标 x、y 与时间戳（Unix 毫秒），序列化为 `[x, y, t]`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryPoint(pub f32, pub f32, pub i64);
+
+/// 结束的轨迹，作为结构化结果输出
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryRecord {
+    pub stream: String,
+    pub track_id: u64,
+    pub class_id: isize,
+    pub class_name: Option<String>,
+    /// 轨迹开始和结束时间（Unix 毫秒）
+    pub start_ms: i64,
+    pub end_ms: i64,
+    /// 观测到的帧数
+    pub frames: u32,
+    /// 简化后的轨迹点
+    pub points: Vec<TrajectoryPoint>,
+}
+
+/// 按帧序号推算时间戳：第 n 帧为 `start_ms + n * 1000 / fps`，同一批内各帧时间戳不同
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameClock {
+    start_ms: i64,
+    fps: f64,
+    index: u64,
+}
+
+impl FrameClock {
+    pub fn new(start_ms: i64, fps: f64) -> Self {
+        Self {
+            start_ms,
+            fps: if fps > 0.0 { fps } else { 25.0 },
+            index: 0,
+        }
+    }
+
+    /// 接下来 `n` 帧的时间戳（Unix 毫秒）
+    pub fn next(&mut self, n: usize) -> Vec<i64> {
+        (0..n)
+            .map(|_| {
+                let t = self.start_ms + (self.index as f64 * 1000.0 / self.fps).round() as i64;
+                self.index += 1;
+                t
+            })
+            .collect()
+    }
+
+    /// 时间戳对应的显示时间戳（相对第一帧）
+    pub fn pts(&self, timestamp: i64) -> Duration {
+        Duration::from_millis((timestamp - self.start_ms).max(0) as u64)
+    }
+}
+
+/// 单帧中一条轨迹最近的路径（归一化坐标）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trail {
+    pub track_id: u64,
+    pub points: Vec<(f32, f32)>,
+}
+
+/// 一批帧的轨迹更新结果
+#[derive(Debug, Clone, Default)]
+pub struct TrajectoryUpdate {
+    /// `tracks[i][j]` 为第 i 帧第 j 个检测框的跟踪 ID
+    pub tracks: Vec<Vec<Option<u64>>>,
+    /// 每帧需要绘制的轨迹
+    pub trails: Vec<Vec<Trail>>,
+    /// 本批结束的轨迹
+    pub finished: Vec<TrajectoryRecord>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrackPath {
+    points: Vec<TrajectoryPoint>,
+    frames: u32,
+}
+
+/// 跟踪目标并记录轨迹
+pub struct Trajectories {
+    config: TrajectoryConfig,
+    stream: String,
+    tracker: IouTracker,
+    paths: HashMap<u64, TrackPath>,
+}
+
+impl Trajectories {
+    pub fn new(config: TrajectoryConfig, stream: &str) -> Self {
+        Self {
+            tracker: IouTracker::new(config.tracker),
+            config,
+            stream: stream.to_string(),
+            paths: HashMap::new(),
+        }
+    }
+
+    pub fn config(&self) -> &TrajectoryConfig {
+        &self.config
+    }
+
+    /// 按时间顺序处理一批帧，`sizes[i]` 为第 i 帧的 (宽, 高)，`timestamps_ms[i]` 为第 i 帧的 Unix 毫秒
+    pub fn update(
+        &mut self,
+        ys: &[Y],
+        sizes: &[(u32, u32)],
+        timestamps_ms: &[i64],
+    ) -> TrajectoryUpdate {
+        let mut update = TrajectoryUpdate::default();
+        for ((y, &(w, h)), &timestamp_ms) in ys.iter().zip(sizes).zip(timestamps_ms) {
+            let tracked = self.tracker.update(y);
+            let bboxes = y.bboxes().unwrap_or_default();
+
+            let mut trails = Vec::new();
+            for (b, id) in bboxes.iter().zip(&tracked.ids) {
+                let Some(id) = *id else { continue };
+                let (x, y) =
+                    BoxF::new(b.xmin(), b.ymin(), b.xmax(), b.ymax()).anchor(self.config.anchor);
+                let path = self.paths.entry(id).or_default();
+                path.points.push(TrajectoryPoint(
+                    (x / w.max(1) as f32).clamp(0.0, 1.0),
+                    (y / h.max(1) as f32).clamp(0.0, 1.0),
+                    timestamp_ms,
+                ));
+                path.frames += 1;
+                if path.points.len() > self.config.max_points {
+                    path.points = simplify(&path.points, self.config.epsilon);
+                }
+
+                let start = path.points.len().saturating_sub(self.config.history);
+                trails.push(Trail {
+                    track_id: id,
+                    points: path.points[start..].iter().map(|p| (p.0, p.1)).collect(),
+                });
+            }
+
+            for track in tracked.ended {
+                update.finished.extend(self.record(track));
+            }
+            update.tracks.push(tracked.ids);
+            update.trails.push(trails);
+        }
+        update
+    }
+
+    /// 结束所有活动轨迹（例如视频流结束时）
+    pub fn finish(&mut self) -> Vec<TrajectoryRecord> {
+        self.tracker
+            .finish()
+            .into_iter()
+            .filter_map(|t| self.record(t))
+            .collect()
+    }
+
+    fn record(&mut self, track: Track) -> Option<TrajectoryRecord> {
+        let path = self.paths.remove(&track.id)?;
+        if path.points.len() < self.config.min_points {
+            return None;
+        }
+        Some(TrajectoryRecord {
+            stream: self.stream.clone(),
+            track_id: track.id,
+            class_id: track.class_id,
+            class_name: track.name,
+            start_ms: path.points.first()?.2,
+            end_ms: path.points.last()?.2,
+            frames: path.frames,
+            points: simplify(&path.points, self.config.epsilon),
+        })
+    }
+
+    /// 将结束的轨迹追加写入导出文件
+    pub fn export(&self, records: &[TrajectoryRecord]) -> Result<()> {
+        let Some(path) = &self.config.export else {
+            return Ok(());
+        };
+        if records.is_empty() {
+            return Ok(());
+        }
+        let path = Path::new(path);
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open trajectory export: {:?}", path))?;
+        let mut buf = Vec::new();
+        for r in records {
+            serde_json::to_writer(&mut buf, r)?;
+            buf.push(b'\n');
+        }
+        file.write_all(&buf)
+            .with_context(|| format!("Failed to write trajectory export: {:?}", path))
+    }
+
+    /// 为一批输出帧绘制轨迹
+    pub fn apply(&self, xs: Vec<DynamicImage>, trails: &[Vec<Trail>]) -> Vec<DynamicImage> {
+        xs.into_par_iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let mut img = match x {
+                    DynamicImage::ImageRgb8(img) => img,
+                    x => x.to_rgb8(),
+                };
+                for trail in trails.get(i).map(|t| t.as_slice()).unwrap_or_default() {
+                    self.draw_trail(&mut img, trail);
+                }
+                DynamicImage::ImageRgb8(img)
+            })
+            .collect()
+    }
+
+    /// 绘制一条渐隐折线
+    pub fn draw_trail(&self, img: &mut RgbImage, trail: &Trail) {
+        let (w, h) = (img.width() as f32, img.height() as f32);
+        let color = Rgb(PALETTE[trail.track_id as usize % PALETTE.len()]);
+        let n = trail.points.len().saturating_sub(1);
+        let t = self.config.thickness.max(1) as i32;
+        for (k, pair) in trail.points.windows(2).enumerate() {
+            let alpha = if self.config.fade {
+                (k + 1) as f32 / n as f32
+            } else {
+                1.0
+            };
+            let (x1, y1) = ((pair[0].0 * w) as i32, (pair[0].1 * h) as i32);
+            let (x2, y2) = ((pair[1].0 * w) as i32, (pair[1].1 * h) as i32);
+            // 线宽通过沿法向偏移多条线实现，水平、竖直方向分别偏移
+            let horizontal = (x2 - x1).abs() >= (y2 - y1).abs();
+            for o in -(t / 2)..t - t / 2 {
+                let (dx, dy) = if horizontal { (0, o) } else { (o, 0) };
+                draw_antialiased_line_segment_mut(
+                    img,
+                    (x1 + dx, y1 + dy),
+                    (x2 + dx, y2 + dy),
+                    color,
+                    |line, original, weight| interpolate(line, original, weight * alpha),
+                );
+            }
+        }
+    }
+}
+
+fn simplify(points: &[TrajectoryPoint], epsilon: f32) -> Vec<TrajectoryPoint> {
+    let xy: Vec<(f32, f32)> = points.iter().map(|p| (p.0, p.1)).collect();
+    simplify_polyline_indices(&xy, epsilon)
+        .into_iter()
+        .map(|i| points[i])
+        .collect()
+}
@@ -0,0 +1,303 @@
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+use std::collections::HashMap;
+use usls::{Keypoint, Y};
+
+use crate::args::{self, BackendOverrides};
+use crate::backend::InferenceBackend;
+use crate::postprocess::{class_names_from_ys, fuse_groups, Detection, FusionConfig};
+use crate::utils::geometry::BoxF;
+
+/// COCO 17 点人体关键点水平翻转时需要互换的左右索引
+pub const COCO_KEYPOINT_FLIP_PAIRS: [(usize, usize); 8] = [
+    (1, 2),
+    (3, 4),
+    (5, 6),
+    (7, 8),
+    (9, 10),
+    (11, 12),
+    (13, 14),
+    (15, 16),
+];
+
+/// 测试时增强配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct TtaConfig {
+    /// 水平翻转
+    pub flip: bool,
+    /// 额外的正方形输入尺寸，每个尺寸对应一个模型实例
+    pub sizes: Vec<isize>,
+    /// 亮度偏移量
+    pub brightness: Vec<i32>,
+    /// 翻转时互换的左右关键点索引，None 时只支持 COCO 17 点模型
+    pub flip_pairs: Option<Vec<(usize, usize)>>,
+    /// 单次推理的最大 batch（动态 batch 维度上限）
+    pub max_batch: usize,
+    pub fusion: FusionConfig,
+}
+
+impl Default for TtaConfig {
+    fn default() -> Self {
+        Self {
+            flip: true,
+            sizes: Vec::new(),
+            brightness: Vec::new(),
+            flip_pairs: None,
+            max_batch: 4,
+            fusion: FusionConfig::default(),
+        }
+    }
+}
+
+impl TtaConfig {
+    /// 适用于 `num_keypoints` 点模型的翻转互换索引
+    ///
+    /// 未配置时仅在 17 点模型上使用 [`COCO_KEYPOINT_FLIP_PAIRS`]，其它点数的模型
+    /// 无法得知左右对应关系，返回错误而不是静默地错换关键点。
+    pub fn flip_pairs_for(&self, num_keypoints: usize) -> Result<&[(usize, usize)]> {
+        match &self.flip_pairs {
+            Some(pairs) => {
+                if let Some(&(l, r)) = pairs
+                    .iter()
+                    .find(|&&(l, r)| l >= num_keypoints || r >= num_keypoints)
+                {
+                    return Err(anyhow!(
+                        "Flip pair ({}, {}) out of range for a {}-keypoint model",
+                        l,
+                        r,
+                        num_keypoints
+                    ));
+                }
+                Ok(pairs)
+            }
+            None if num_keypoints == 0 => Ok(&[]),
+            None if num_keypoints == 17 => Ok(&COCO_KEYPOINT_FLIP_PAIRS),
+            None => Err(anyhow!(
+                "Flip TTA on a {}-keypoint model needs explicit flip pairs (--tta_flip_pairs) or --tta_flip false",
+                num_keypoints
+            )),
+        }
+    }
+}
+
+/// 单个增强版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Augment {
+    Identity,
+    FlipH,
+    Brightness(i32),
+}
+
+impl Augment {
+    pub fn apply(&self, x: &DynamicImage) -> DynamicImage {
+        match *self {
+            Self::Identity => x.clone(),
+            Self::FlipH => x.fliph(),
+            Self::Brightness(v) => x.brighten(v),
+        }
+    }
+}
+
+/// 检测框及其关键点 (x, y, confidence)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub detection: Detection,
+    pub keypoints: Vec<[f32; 3]>,
+}
+
+/// 将翻转图像上的检测框映射回原图
+pub fn flip_box(b: &BoxF, width: f32) -> BoxF {
+    BoxF::new(width - b.x2, b.y1, width - b.x1, b.y2)
+}
+
+/// 将翻转图像上的关键点映射回原图，并互换左右关键点
+pub fn flip_keypoints(kpts: &[[f32; 3]], width: f32, pairs: &[(usize, usize)]) -> Vec<[f32; 3]> {
+    let mut out: Vec<[f32; 3]> = kpts.iter().map(|&[x, y, c]| [width - x, y, c]).collect();
+    for &(l, r) in pairs {
+        if l < out.len() && r < out.len() {
+            out.swap(l, r);
+        }
+    }
+    out
+}
+
+/// 融合多个增强版本的结果
+///
+/// 检测框按配置融合；关键点取融合进该框的候选按置信度加权平均。
+pub fn merge(sources: &[Vec<Candidate>], config: &FusionConfig) -> Vec<Candidate> {
+    let boxes: Vec<Vec<Detection>> = sources
+        .iter()
+        .map(|s| s.iter().map(|c| c.detection).collect())
+        .collect();
+    let weights = vec![1.0; sources.len()];
+
+    fuse_groups(&boxes, &weights, config)
+        .into_iter()
+        .map(|(f, members)| {
+            let members: Vec<&Candidate> = members.iter().map(|&(i, k)| &sources[i][k]).collect();
+            Candidate {
+                detection: f,
+                keypoints: average_keypoints(&members),
+            }
+        })
+        .collect()
+}
+
+fn average_keypoints(members: &[&Candidate]) -> Vec<[f32; 3]> {
+    let n = members.iter().map(|c| c.keypoints.len()).max().unwrap_or(0);
+    (0..n)
+        .map(|k| {
+            let kpts: Vec<[f32; 3]> = members
+                .iter()
+                .filter_map(|c| c.keypoints.get(k).copied())
+                .collect();
+            let conf_sum: f32 = kpts.iter().map(|p| p[2]).sum();
+            if conf_sum <= 0.0 {
+                return [0.0, 0.0, 0.0];
+            }
+            let x = kpts.iter().map(|p| p[0] * p[2]).sum::<f32>() / conf_sum;
+            let y = kpts.iter().map(|p| p[1] * p[2]).sum::<f32>() / conf_sum;
+            [x, y, conf_sum / kpts.len() as f32]
+        })
+        .collect()
+}
+
+/// 测试时增强推理（离线审计用，精度优先）
+///
+/// 每帧生成原图、水平翻转、亮度偏移等版本，所有帧的全部版本按动态 batch 维度批量推理；
+/// 多尺度通过额外的模型实例实现（每个实例使用不同的输入尺寸），结果逆变换回原图后融合。
+pub struct Tta {
+    config: TtaConfig,
+    augments: Vec<Augment>,
+    scaled: Vec<Box<dyn InferenceBackend>>,
+}
+
+impl Tta {
+    pub fn new(config: TtaConfig) -> Result<Self> {
+        let scaled = config
+            .sizes
+            .iter()
+            .map(|&size| {
+                let model =
+                    args::build_backend_with(&BackendOverrides::default().with_size(size, size))?;
+                tracing::info!("tta model loaded with input size {}", size);
+                Ok(model)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut augments = vec![Augment::Identity];
+        if config.flip {
+            augments.push(Augment::FlipH);
+        }
+        augments.extend(config.brightness.iter().map(|&v| Augment::Brightness(v)));
+
+        Ok(Self {
+            config,
+            augments,
+            scaled,
+        })
+    }
+
+    pub fn forward(
+        &mut self,
+        model: &mut dyn InferenceBackend,
+        xs: &[DynamicImage],
+    ) -> Result<Vec<Y>> {
+        // (帧索引, 增强方式)
+        let plan: Vec<(usize, Augment)> = (0..xs.len())
+            .flat_map(|i| self.augments.iter().map(move |&a| (i, a)))
+            .collect();
+        let inputs: Vec<DynamicImage> = plan.iter().map(|&(i, a)| a.apply(&xs[i])).collect();
+
+        let mut sources: Vec<Vec<Vec<Candidate>>> = vec![Vec::new(); xs.len()];
+        let mut class_names = HashMap::new();
+        let batch = self.config.max_batch.max(1);
+        let scaled = self
+            .scaled
+            .iter_mut()
+            .map(|m| m.as_mut() as &mut dyn InferenceBackend);
+        for model in std::iter::once(model).chain(scaled) {
+            for (chunk, plan_chunk) in inputs.chunks(batch).zip(plan.chunks(batch)) {
+                let ys = model.forward(chunk)?;
+                class_names.extend(class_names_from_ys(&ys));
+                for (y, &(i, augment)) in ys.iter().zip(plan_chunk) {
+                    let width = xs[i].width() as f32;
+                    let pairs = match augment {
+                        Augment::FlipH => {
+                            let nk = y.keypoints().and_then(|k| k.first()).map_or(0, |k| k.len());
+                            self.config.flip_pairs_for(nk)?
+                        }
+                        _ => &[],
+                    };
+                    sources[i].push(candidates(y, augment, width, pairs));
+                }
+            }
+        }
+
+        Ok(sources
+            .iter()
+            .map(|frame| to_y(&merge(frame, &self.config.fusion), &class_names))
+            .collect())
+    }
+}
+
+/// 提取候选结果并做逆变换
+fn candidates(
+    y: &Y,
+    augment: Augment,
+    width: f32,
+    flip_pairs: &[(usize, usize)],
+) -> Vec<Candidate> {
+    let keypoints = y.keypoints().unwrap_or_default();
+    y.bboxes()
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .filter_map(|(k, b)| {
+            let mut detection = Detection::from_bbox(b)?;
+            let mut kpts: Vec<[f32; 3]> = keypoints
+                .get(k)
+                .map(|v| v.iter().map(|p| [p.x(), p.y(), p.confidence()]).collect())
+                .unwrap_or_default();
+            if augment == Augment::FlipH {
+                detection.bbox = flip_box(&detection.bbox, width);
+                kpts = flip_keypoints(&kpts, width, flip_pairs);
+            }
+            Some(Candidate {
+                detection,
+                keypoints: kpts,
+            })
+        })
+        .collect()
+}
+
+fn to_y(candidates: &[Candidate], class_names: &HashMap<usize, String>) -> Y {
+    let bboxes: Vec<_> = candidates
+        .iter()
+        .map(|c| {
+            c.detection
+                .to_bbox(class_names.get(&c.detection.class_id).map(|s| s.as_str()))
+        })
+        .collect();
+    let y = Y::default().with_bboxes(&bboxes);
+    if candidates.iter().all(|c| c.keypoints.is_empty()) {
+        return y;
+    }
+
+    let keypoints: Vec<Vec<Keypoint>> = candidates
+        .iter()
+        .map(|c| {
+            c.keypoints
+                .iter()
+                .enumerate()
+                .map(|(id, &[x, y, conf])| {
+                    Keypoint::default()
+                        .with_xy(x, y)
+                        .with_confidence(conf)
+                        .with_id(id as isize)
+                })
+                .collect()
+        })
+        .collect();
+    y.with_keypoints(&keypoints)
+}
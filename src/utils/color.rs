@@ -0,0 +1,801 @@
+use anyhow::{anyhow, Result};
+
+/// 定点系数的小数位数
+const SHIFT: i32 = 14;
+const HALF: i32 = 1 << (SHIFT - 1);
+
+/// YUV 矩阵标准
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Matrix {
+    /// 标清，OpenCV 的 YUV 转换使用该标准
+    Bt601,
+    /// 高清
+    Bt709,
+}
+
+/// 取值范围
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Range {
+    /// Y 为 16..=235，UV 为 16..=240
+    Limited,
+    /// Y、UV 均为 0..=255
+    Full,
+}
+
+/// 矩阵与范围的组合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorSpace {
+    pub matrix: Matrix,
+    pub range: Range,
+}
+
+impl ColorSpace {
+    pub const BT601_LIMITED: Self = Self::new(Matrix::Bt601, Range::Limited);
+    pub const BT601_FULL: Self = Self::new(Matrix::Bt601, Range::Full);
+    pub const BT709_LIMITED: Self = Self::new(Matrix::Bt709, Range::Limited);
+    pub const BT709_FULL: Self = Self::new(Matrix::Bt709, Range::Full);
+
+    pub const fn new(matrix: Matrix, range: Range) -> Self {
+        Self { matrix, range }
+    }
+
+    /// 亮度系数 (Kr, Kb)
+    fn kr_kb(&self) -> (f64, f64) {
+        match self.matrix {
+            Matrix::Bt601 => (0.299, 0.114),
+            Matrix::Bt709 => (0.2126, 0.0722),
+        }
+    }
+
+    /// (Y 缩放, UV 缩放, Y 偏移)
+    fn scales(&self) -> (f64, f64, i32) {
+        match self.range {
+            Range::Limited => (219.0 / 255.0, 224.0 / 255.0, 16),
+            Range::Full => (1.0, 1.0, 0),
+        }
+    }
+}
+
+/// 转换实现
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// 逐像素标量实现
+    Scalar,
+    /// AVX2（x86_64，运行时检测）或 NEON（aarch64）实现，其它平台等同于标量实现
+    Simd,
+}
+
+impl Backend {
+    pub fn detect() -> Self {
+        if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            Self::Simd
+        } else {
+            Self::Scalar
+        }
+    }
+}
+
+/// YUV -> RGB 定点系数
+#[derive(Debug, Clone, Copy)]
+struct Decode {
+    y_off: i32,
+    y_mul: i32,
+    rv: i32,
+    gu: i32,
+    gv: i32,
+    bu: i32,
+}
+
+/// RGB -> YUV 定点系数
+#[derive(Debug, Clone, Copy)]
+struct Encode {
+    y_off: i32,
+    y: [i32; 3],
+    u: [i32; 3],
+    v: [i32; 3],
+}
+
+fn fixed(x: f64) -> i32 {
+    (x * (1 << SHIFT) as f64).round() as i32
+}
+
+impl Decode {
+    fn new(space: ColorSpace) -> Self {
+        let (kr, kb) = space.kr_kb();
+        let kg = 1.0 - kr - kb;
+        let (ys, cs, y_off) = space.scales();
+        Self {
+            y_off,
+            y_mul: fixed(1.0 / ys),
+            rv: fixed(2.0 * (1.0 - kr) / cs),
+            gu: fixed(2.0 * (1.0 - kb) * kb / kg / cs),
+            gv: fixed(2.0 * (1.0 - kr) * kr / kg / cs),
+            bu: fixed(2.0 * (1.0 - kb) / cs),
+        }
+    }
+
+    #[inline(always)]
+    fn pixel(&self, y: u8, u: u8, v: u8) -> [u8; 3] {
+        let y = self.y_mul * (y as i32 - self.y_off) + HALF;
+        let (u, v) = (u as i32 - 128, v as i32 - 128);
+        [
+            ((y + self.rv * v) >> SHIFT).clamp(0, 255) as u8,
+            ((y - self.gu * u - self.gv * v) >> SHIFT).clamp(0, 255) as u8,
+            ((y + self.bu * u) >> SHIFT).clamp(0, 255) as u8,
+        ]
+    }
+}
+
+impl Encode {
+    fn new(space: ColorSpace) -> Self {
+        let (kr, kb) = space.kr_kb();
+        let kg = 1.0 - kr - kb;
+        let (ys, cs, y_off) = space.scales();
+        let (su, sv) = (cs / (2.0 * (1.0 - kb)), cs / (2.0 * (1.0 - kr)));
+        Self {
+            y_off,
+            y: [fixed(kr * ys), fixed(kg * ys), fixed(kb * ys)],
+            u: [fixed(-kr * su), fixed(-kg * su), fixed((1.0 - kb) * su)],
+            v: [fixed((1.0 - kr) * sv), fixed(-kg * sv), fixed(-kb * sv)],
+        }
+    }
+
+    #[inline(always)]
+    fn luma(&self, r: i32, g: i32, b: i32) -> u8 {
+        (((self.y[0] * r + self.y[1] * g + self.y[2] * b + HALF) >> SHIFT) + self.y_off)
+            .clamp(0, 255) as u8
+    }
+
+    #[inline(always)]
+    fn chroma(&self, r: i32, g: i32, b: i32) -> (u8, u8) {
+        let u = ((self.u[0] * r + self.u[1] * g + self.u[2] * b + HALF) >> SHIFT) + 128;
+        let v = ((self.v[0] * r + self.v[1] * g + self.v[2] * b + HALF) >> SHIFT) + 128;
+        (u.clamp(0, 255) as u8, v.clamp(0, 255) as u8)
+    }
+}
+
+/// 标量行内核：`u`、`v` 为水平 2 倍下采样的色度行
+mod scalar {
+    use super::{Decode, Encode};
+
+    pub fn yuv_to_rgb(k: &Decode, y: &[u8], u: &[u8], v: &[u8], rgb: &mut [u8]) {
+        for (x, (&y, px)) in y.iter().zip(rgb.chunks_exact_mut(3)).enumerate() {
+            px.copy_from_slice(&k.pixel(y, u[x / 2], v[x / 2]));
+        }
+    }
+
+    pub fn rgb_to_y(k: &Encode, rgb: &[u8], y: &mut [u8]) {
+        for (px, y) in rgb.chunks_exact(3).zip(y.iter_mut()) {
+            *y = k.luma(px[0] as i32, px[1] as i32, px[2] as i32);
+        }
+    }
+
+    /// 两行 RGB 每 2x2 块取平均后计算色度，`rgb1` 与 `rgb0` 相同时为 2x1 块
+    pub fn rgb_to_uv(k: &Encode, rgb0: &[u8], rgb1: &[u8], u: &mut [u8], v: &mut [u8]) {
+        let blocks = rgb0.chunks_exact(6).zip(rgb1.chunks_exact(6));
+        for ((a, b), (u, v)) in blocks.zip(u.iter_mut().zip(v.iter_mut())) {
+            let avg =
+                |c: usize| (a[c] as i32 + a[c + 3] as i32 + b[c] as i32 + b[c + 3] as i32 + 2) >> 2;
+            (*u, *v) = k.chroma(avg(0), avg(1), avg(2));
+        }
+    }
+}
+
+/// SIMD 行内核：x86_64 上运行时检测 AVX2，aarch64 上使用 NEON，其它平台退回标量内核。
+/// 每次处理 8 个像素，定点运算与标量内核逐位一致，不足 8 个像素的尾部交给标量内核
+mod simd {
+    use super::{scalar, Decode, Encode};
+
+    pub fn yuv_to_rgb(k: &Decode, y: &[u8], u: &[u8], v: &[u8], rgb: &mut [u8]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: 已在运行时确认 CPU 支持 AVX2
+            return unsafe { avx2::yuv_to_rgb(k, y, u, v, rgb) };
+        }
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: aarch64 必定支持 NEON
+        unsafe {
+            neon::yuv_to_rgb(k, y, u, v, rgb);
+        }
+        #[cfg(not(target_arch = "aarch64"))]
+        scalar::yuv_to_rgb(k, y, u, v, rgb);
+    }
+
+    pub fn rgb_to_y(k: &Encode, rgb: &[u8], y: &mut [u8]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: 已在运行时确认 CPU 支持 AVX2
+            return unsafe { avx2::rgb_to_y(k, rgb, y) };
+        }
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: aarch64 必定支持 NEON
+        unsafe {
+            neon::rgb_to_y(k, rgb, y);
+        }
+        #[cfg(not(target_arch = "aarch64"))]
+        scalar::rgb_to_y(k, rgb, y);
+    }
+
+    pub fn rgb_to_uv(k: &Encode, rgb0: &[u8], rgb1: &[u8], u: &mut [u8], v: &mut [u8]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: 已在运行时确认 CPU 支持 AVX2
+            return unsafe { avx2::rgb_to_uv(k, rgb0, rgb1, u, v) };
+        }
+        #[cfg(target_arch = "aarch64")]
+        // SAFETY: aarch64 必定支持 NEON
+        unsafe {
+            neon::rgb_to_uv(k, rgb0, rgb1, u, v);
+        }
+        #[cfg(not(target_arch = "aarch64"))]
+        scalar::rgb_to_uv(k, rgb0, rgb1, u, v);
+    }
+
+    /// 8 x i32 一组；结果用 packus 饱和打包，等价于标量内核的 clamp(0, 255)
+    #[cfg(target_arch = "x86_64")]
+    mod avx2 {
+        use super::super::{scalar, Decode, Encode, HALF, SHIFT};
+        use std::arch::x86_64::*;
+
+        const X: i8 = -1;
+        /// 从 24 字节打包 RGB 的前 16 字节（lo）和后 16 字节（hi，偏移 8）中挑出各分量
+        const R_LO: [i8; 16] = [0, 3, 6, 9, 12, 15, X, X, X, X, X, X, X, X, X, X];
+        const R_HI: [i8; 16] = [X, X, X, X, X, X, 10, 13, X, X, X, X, X, X, X, X];
+        const G_LO: [i8; 16] = [1, 4, 7, 10, 13, X, X, X, X, X, X, X, X, X, X, X];
+        const G_HI: [i8; 16] = [X, X, X, X, X, 8, 11, 14, X, X, X, X, X, X, X, X];
+        const B_LO: [i8; 16] = [2, 5, 8, 11, 14, X, X, X, X, X, X, X, X, X, X, X];
+        const B_HI: [i8; 16] = [X, X, X, X, X, 9, 12, 15, X, X, X, X, X, X, X, X];
+        /// 由 R、G（同一寄存器的低、高 8 字节）和 B 交错出 24 字节打包 RGB
+        const OUT_RG_LO: [i8; 16] = [0, 8, X, 1, 9, X, 2, 10, X, 3, 11, X, 4, 12, X, 5];
+        const OUT_B_LO: [i8; 16] = [X, X, 0, X, X, 1, X, X, 2, X, X, 3, X, X, 4, X];
+        const OUT_RG_HI: [i8; 16] = [13, X, 6, 14, X, 7, 15, X, X, X, X, X, X, X, X, X];
+        const OUT_B_HI: [i8; 16] = [X, 5, X, X, 6, X, X, 7, X, X, X, X, X, X, X, X];
+
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn mask(m: &[i8; 16]) -> __m128i {
+            _mm_loadu_si128(m.as_ptr() as *const __m128i)
+        }
+
+        /// 读取 8 个像素（24 字节）并拆成 R、G、B 三组 i32
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn load_rgb(p: &[u8]) -> (__m256i, __m256i, __m256i) {
+            debug_assert!(p.len() >= 24);
+            let lo = _mm_loadu_si128(p.as_ptr() as *const __m128i);
+            let hi = _mm_loadu_si128(p.as_ptr().add(8) as *const __m128i);
+            let pick = |a: &[i8; 16], b: &[i8; 16]| {
+                _mm256_cvtepu8_epi32(_mm_or_si128(
+                    _mm_shuffle_epi8(lo, mask(a)),
+                    _mm_shuffle_epi8(hi, mask(b)),
+                ))
+            };
+            (pick(&R_LO, &R_HI), pick(&G_LO, &G_HI), pick(&B_LO, &B_HI))
+        }
+
+        /// 8 个 i32 饱和打包到低 8 字节
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn pack8(x: __m256i) -> __m128i {
+            let w = _mm256_permute4x64_epi64::<0b1000>(_mm256_packus_epi32(x, x));
+            let w = _mm256_castsi256_si128(w);
+            _mm_packus_epi16(w, w)
+        }
+
+        /// 写入 8 个像素（24 字节）
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn store_rgb(p: &mut [u8], r: __m256i, g: __m256i, b: __m256i) {
+            debug_assert!(p.len() >= 24);
+            let rg = _mm_unpacklo_epi64(pack8(r), pack8(g));
+            let b = pack8(b);
+            let lo = _mm_or_si128(
+                _mm_shuffle_epi8(rg, mask(&OUT_RG_LO)),
+                _mm_shuffle_epi8(b, mask(&OUT_B_LO)),
+            );
+            let hi = _mm_or_si128(
+                _mm_shuffle_epi8(rg, mask(&OUT_RG_HI)),
+                _mm_shuffle_epi8(b, mask(&OUT_B_HI)),
+            );
+            _mm_storeu_si128(p.as_mut_ptr() as *mut __m128i, lo);
+            _mm_storel_epi64(p.as_mut_ptr().add(16) as *mut __m128i, hi);
+        }
+
+        /// 4 个色度样本各复制一次，对应 8 个像素
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn load_chroma(c: &[u8]) -> __m256i {
+            let x = _mm_cvtsi32_si128(i32::from_le_bytes([c[0], c[1], c[2], c[3]]));
+            _mm256_sub_epi32(
+                _mm256_cvtepu8_epi32(_mm_unpacklo_epi8(x, x)),
+                _mm256_set1_epi32(128),
+            )
+        }
+
+        /// `a * x + b * y + c * z`
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn dot(k: &[i32; 3], x: __m256i, y: __m256i, z: __m256i) -> __m256i {
+            _mm256_add_epi32(
+                _mm256_add_epi32(
+                    _mm256_mullo_epi32(_mm256_set1_epi32(k[0]), x),
+                    _mm256_mullo_epi32(_mm256_set1_epi32(k[1]), y),
+                ),
+                _mm256_mullo_epi32(_mm256_set1_epi32(k[2]), z),
+            )
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn yuv_to_rgb(k: &Decode, y: &[u8], u: &[u8], v: &[u8], rgb: &mut [u8]) {
+            let n = y.len() / 8 * 8;
+            let (y_off, y_mul) = (_mm256_set1_epi32(k.y_off), _mm256_set1_epi32(k.y_mul));
+            let (rv, gu, gv, bu) = (
+                _mm256_set1_epi32(k.rv),
+                _mm256_set1_epi32(k.gu),
+                _mm256_set1_epi32(k.gv),
+                _mm256_set1_epi32(k.bu),
+            );
+            let half = _mm256_set1_epi32(HALF);
+            for i in (0..n).step_by(8) {
+                let yy = _mm256_cvtepu8_epi32(_mm_loadl_epi64(y.as_ptr().add(i) as *const _));
+                let yy =
+                    _mm256_add_epi32(_mm256_mullo_epi32(y_mul, _mm256_sub_epi32(yy, y_off)), half);
+                let (uu, vv) = (load_chroma(&u[i / 2..]), load_chroma(&v[i / 2..]));
+                let r = _mm256_add_epi32(yy, _mm256_mullo_epi32(rv, vv));
+                let g = _mm256_sub_epi32(
+                    _mm256_sub_epi32(yy, _mm256_mullo_epi32(gu, uu)),
+                    _mm256_mullo_epi32(gv, vv),
+                );
+                let b = _mm256_add_epi32(yy, _mm256_mullo_epi32(bu, uu));
+                store_rgb(
+                    &mut rgb[i * 3..],
+                    _mm256_srai_epi32::<SHIFT>(r),
+                    _mm256_srai_epi32::<SHIFT>(g),
+                    _mm256_srai_epi32::<SHIFT>(b),
+                );
+            }
+            scalar::yuv_to_rgb(
+                k,
+                &y[n..],
+                &u[n / 2..],
+                &v[n / 2..],
+                &mut rgb[n * 3..y.len() * 3],
+            );
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn rgb_to_y(k: &Encode, rgb: &[u8], y: &mut [u8]) {
+            let n = y.len() / 8 * 8;
+            let (half, y_off) = (_mm256_set1_epi32(HALF), _mm256_set1_epi32(k.y_off));
+            for i in (0..n).step_by(8) {
+                let (r, g, b) = load_rgb(&rgb[i * 3..]);
+                let yy = _mm256_srai_epi32::<SHIFT>(_mm256_add_epi32(dot(&k.y, r, g, b), half));
+                let out = pack8(_mm256_add_epi32(yy, y_off));
+                _mm_storel_epi64(y.as_mut_ptr().add(i) as *mut __m128i, out);
+            }
+            scalar::rgb_to_y(k, &rgb[n * 3..], &mut y[n..]);
+        }
+
+        /// 两行各 16 个像素的 2x2 块平均，得到 8 个色度位置的 R、G、B
+        #[inline]
+        #[target_feature(enable = "avx2")]
+        unsafe fn average(rgb0: &[u8], rgb1: &[u8]) -> (__m256i, __m256i, __m256i) {
+            let (r0, g0, b0) = load_rgb(rgb0);
+            let (r1, g1, b1) = load_rgb(&rgb0[24..]);
+            let (r2, g2, b2) = load_rgb(rgb1);
+            let (r3, g3, b3) = load_rgb(&rgb1[24..]);
+            let two = _mm256_set1_epi32(2);
+            let avg = |a: __m256i, b: __m256i, c: __m256i, d: __m256i| {
+                // hadd 按 128 位通道交错，重排回像素顺序
+                let s = _mm256_hadd_epi32(_mm256_add_epi32(a, c), _mm256_add_epi32(b, d));
+                let s = _mm256_permute4x64_epi64::<0b11011000>(s);
+                _mm256_srai_epi32::<2>(_mm256_add_epi32(s, two))
+            };
+            (
+                avg(r0, r1, r2, r3),
+                avg(g0, g1, g2, g3),
+                avg(b0, b1, b2, b3),
+            )
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn rgb_to_uv(k: &Encode, rgb0: &[u8], rgb1: &[u8], u: &mut [u8], v: &mut [u8]) {
+            let n = u.len().min(v.len()).min(rgb0.len() / 6).min(rgb1.len() / 6) / 8 * 8;
+            let (half, mid) = (_mm256_set1_epi32(HALF), _mm256_set1_epi32(128));
+            for i in (0..n).step_by(8) {
+                let (r, g, b) = average(&rgb0[i * 6..], &rgb1[i * 6..]);
+                let uu = _mm256_srai_epi32::<SHIFT>(_mm256_add_epi32(dot(&k.u, r, g, b), half));
+                let vv = _mm256_srai_epi32::<SHIFT>(_mm256_add_epi32(dot(&k.v, r, g, b), half));
+                _mm_storel_epi64(
+                    u.as_mut_ptr().add(i) as *mut __m128i,
+                    pack8(_mm256_add_epi32(uu, mid)),
+                );
+                _mm_storel_epi64(
+                    v.as_mut_ptr().add(i) as *mut __m128i,
+                    pack8(_mm256_add_epi32(vv, mid)),
+                );
+            }
+            scalar::rgb_to_uv(k, &rgb0[n * 6..], &rgb1[n * 6..], &mut u[n..], &mut v[n..]);
+        }
+    }
+
+    /// 8 个像素拆成两组 4 x i32；vqmovun/vqmovn 饱和收窄，等价于标量内核的 clamp(0, 255)
+    #[cfg(target_arch = "aarch64")]
+    mod neon {
+        use super::super::{scalar, Decode, Encode, HALF, SHIFT};
+        use std::arch::aarch64::*;
+
+        #[inline]
+        unsafe fn widen(x: uint8x8_t) -> [int32x4_t; 2] {
+            widen16(vmovl_u8(x))
+        }
+
+        #[inline]
+        unsafe fn widen16(x: uint16x8_t) -> [int32x4_t; 2] {
+            [
+                vreinterpretq_s32_u32(vmovl_u16(vget_low_u16(x))),
+                vreinterpretq_s32_u32(vmovl_high_u16(x)),
+            ]
+        }
+
+        #[inline]
+        unsafe fn narrow(x: [int32x4_t; 2]) -> uint8x8_t {
+            vqmovn_u16(vcombine_u16(vqmovun_s32(x[0]), vqmovun_s32(x[1])))
+        }
+
+        /// `(a * x + b * y + c * z + HALF) >> SHIFT + offset`
+        #[inline]
+        unsafe fn dot(
+            k: &[i32; 3],
+            offset: i32,
+            x: int32x4_t,
+            y: int32x4_t,
+            z: int32x4_t,
+        ) -> int32x4_t {
+            let acc = vmlaq_n_s32(vdupq_n_s32(HALF), x, k[0]);
+            let acc = vmlaq_n_s32(vmlaq_n_s32(acc, y, k[1]), z, k[2]);
+            vaddq_s32(vshrq_n_s32::<SHIFT>(acc), vdupq_n_s32(offset))
+        }
+
+        #[target_feature(enable = "neon")]
+        pub unsafe fn yuv_to_rgb(k: &Decode, y: &[u8], u: &[u8], v: &[u8], rgb: &mut [u8]) {
+            let n = y.len() / 8 * 8;
+            // 4 个色度样本各复制一次，对应 8 个像素
+            let chroma = |c: &[u8]| {
+                let x =
+                    vreinterpret_u8_u32(vdup_n_u32(u32::from_le_bytes([c[0], c[1], c[2], c[3]])));
+                let c = widen(vzip1_u8(x, x));
+                [
+                    vsubq_s32(c[0], vdupq_n_s32(128)),
+                    vsubq_s32(c[1], vdupq_n_s32(128)),
+                ]
+            };
+            for i in (0..n).step_by(8) {
+                let yy = widen(vld1_u8(y.as_ptr().add(i)));
+                let (uu, vv) = (chroma(&u[i / 2..]), chroma(&v[i / 2..]));
+                let (mut r, mut g, mut b) = (
+                    [vdupq_n_s32(0); 2],
+                    [vdupq_n_s32(0); 2],
+                    [vdupq_n_s32(0); 2],
+                );
+                for h in 0..2 {
+                    let yh = vmlaq_n_s32(
+                        vdupq_n_s32(HALF),
+                        vsubq_s32(yy[h], vdupq_n_s32(k.y_off)),
+                        k.y_mul,
+                    );
+                    r[h] = vshrq_n_s32::<SHIFT>(vmlaq_n_s32(yh, vv[h], k.rv));
+                    g[h] = vshrq_n_s32::<SHIFT>(vmlsq_n_s32(
+                        vmlsq_n_s32(yh, uu[h], k.gu),
+                        vv[h],
+                        k.gv,
+                    ));
+                    b[h] = vshrq_n_s32::<SHIFT>(vmlaq_n_s32(yh, uu[h], k.bu));
+                }
+                vst3_u8(
+                    rgb.as_mut_ptr().add(i * 3),
+                    uint8x8x3_t(narrow(r), narrow(g), narrow(b)),
+                );
+            }
+            scalar::yuv_to_rgb(
+                k,
+                &y[n..],
+                &u[n / 2..],
+                &v[n / 2..],
+                &mut rgb[n * 3..y.len() * 3],
+            );
+        }
+
+        #[target_feature(enable = "neon")]
+        pub unsafe fn rgb_to_y(k: &Encode, rgb: &[u8], y: &mut [u8]) {
+            let n = y.len() / 8 * 8;
+            for i in (0..n).step_by(8) {
+                let px = vld3_u8(rgb.as_ptr().add(i * 3));
+                let (r, g, b) = (widen(px.0), widen(px.1), widen(px.2));
+                let out = [
+                    dot(&k.y, k.y_off, r[0], g[0], b[0]),
+                    dot(&k.y, k.y_off, r[1], g[1], b[1]),
+                ];
+                vst1_u8(y.as_mut_ptr().add(i), narrow(out));
+            }
+            scalar::rgb_to_y(k, &rgb[n * 3..], &mut y[n..]);
+        }
+
+        #[target_feature(enable = "neon")]
+        pub unsafe fn rgb_to_uv(k: &Encode, rgb0: &[u8], rgb1: &[u8], u: &mut [u8], v: &mut [u8]) {
+            let n = u.len().min(v.len()).min(rgb0.len() / 6).min(rgb1.len() / 6) / 8 * 8;
+            // 两行各 16 个像素：行内相邻两个像素相加，再累加下一行，得到 2x2 块平均
+            let average = |a: uint8x16_t, b: uint8x16_t| {
+                let sum = vpadalq_u8(vpaddlq_u8(a), b);
+                widen16(vshrq_n_u16::<2>(vaddq_u16(sum, vdupq_n_u16(2))))
+            };
+            for i in (0..n).step_by(8) {
+                let p0 = vld3q_u8(rgb0.as_ptr().add(i * 6));
+                let p1 = vld3q_u8(rgb1.as_ptr().add(i * 6));
+                let (r, g, b) = (
+                    average(p0.0, p1.0),
+                    average(p0.1, p1.1),
+                    average(p0.2, p1.2),
+                );
+                let uu = [
+                    dot(&k.u, 128, r[0], g[0], b[0]),
+                    dot(&k.u, 128, r[1], g[1], b[1]),
+                ];
+                let vv = [
+                    dot(&k.v, 128, r[0], g[0], b[0]),
+                    dot(&k.v, 128, r[1], g[1], b[1]),
+                ];
+                vst1_u8(u.as_mut_ptr().add(i), narrow(uu));
+                vst1_u8(v.as_mut_ptr().add(i), narrow(vv));
+            }
+            scalar::rgb_to_uv(k, &rgb0[n * 6..], &rgb1[n * 6..], &mut u[n..], &mut v[n..]);
+        }
+    }
+}
+
+/// RGB（打包 RGB24）与 I420、NV12、YUYV 之间的转换，4:2:0 与 4:2:2 格式要求宽（和高）为偶数
+///
+/// ```ignore
+/// let converter = YuvConverter::new(ColorSpace::BT709_LIMITED);
+/// let rgb = converter.nv12_to_rgb(&buffer, 1920, 1080)?;
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct YuvConverter {
+    space: ColorSpace,
+    backend: Backend,
+    decode: Decode,
+    encode: Encode,
+}
+
+impl YuvConverter {
+    pub fn new(space: ColorSpace) -> Self {
+        Self {
+            space,
+            backend: Backend::detect(),
+            decode: Decode::new(space),
+            encode: Encode::new(space),
+        }
+    }
+
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn space(&self) -> ColorSpace {
+        self.space
+    }
+
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn row_to_rgb(&self, y: &[u8], u: &[u8], v: &[u8], rgb: &mut [u8]) {
+        match self.backend {
+            Backend::Scalar => scalar::yuv_to_rgb(&self.decode, y, u, v, rgb),
+            Backend::Simd => simd::yuv_to_rgb(&self.decode, y, u, v, rgb),
+        }
+    }
+
+    fn row_to_y(&self, rgb: &[u8], y: &mut [u8]) {
+        match self.backend {
+            Backend::Scalar => scalar::rgb_to_y(&self.encode, rgb, y),
+            Backend::Simd => simd::rgb_to_y(&self.encode, rgb, y),
+        }
+    }
+
+    fn rows_to_uv(&self, rgb0: &[u8], rgb1: &[u8], u: &mut [u8], v: &mut [u8]) {
+        match self.backend {
+            Backend::Scalar => scalar::rgb_to_uv(&self.encode, rgb0, rgb1, u, v),
+            Backend::Simd => simd::rgb_to_uv(&self.encode, rgb0, rgb1, u, v),
+        }
+    }
+
+    /// 三平面 I420（Y、U、V 依次紧密排列）转 RGB24
+    pub fn i420_to_rgb(&self, yuv: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size(
+            "I420",
+            width,
+            height,
+            true,
+            yuv.len(),
+            width * height * 3 / 2,
+        )?;
+        let (luma, chroma) = (width * height, width * height / 4);
+        let (y, rest) = yuv.split_at(luma);
+        let (u, v) = rest.split_at(chroma);
+
+        let cw = width / 2;
+        let mut rgb = vec![0u8; width * height * 3];
+        for (row, out) in rgb.chunks_exact_mut(width * 3).enumerate() {
+            let c = row / 2 * cw;
+            self.row_to_rgb(
+                &y[row * width..(row + 1) * width],
+                &u[c..c + cw],
+                &v[c..c + cw],
+                out,
+            );
+        }
+        Ok(rgb)
+    }
+
+    /// NV12（Y 平面后接 UV 交错平面）转 RGB24
+    pub fn nv12_to_rgb(&self, yuv: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size(
+            "NV12",
+            width,
+            height,
+            true,
+            yuv.len(),
+            width * height * 3 / 2,
+        )?;
+        let (y, uv) = yuv.split_at(width * height);
+
+        let cw = width / 2;
+        let (mut u, mut v) = (vec![0u8; cw], vec![0u8; cw]);
+        let mut rgb = vec![0u8; width * height * 3];
+        for (row, out) in rgb.chunks_exact_mut(width * 3).enumerate() {
+            if row.is_multiple_of(2) {
+                let c = row / 2 * width;
+                deinterleave(&uv[c..c + width], &mut u, &mut v);
+            }
+            self.row_to_rgb(&y[row * width..(row + 1) * width], &u, &v, out);
+        }
+        Ok(rgb)
+    }
+
+    /// YUYV（YUY2，每两个像素 Y0 U Y1 V）转 RGB24
+    pub fn yuyv_to_rgb(&self, yuv: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size("YUYV", width, height, false, yuv.len(), width * height * 2)?;
+        let cw = width / 2;
+        let (mut y, mut u, mut v) = (vec![0u8; width], vec![0u8; cw], vec![0u8; cw]);
+        let mut rgb = vec![0u8; width * height * 3];
+        for (src, out) in yuv
+            .chunks_exact(width * 2)
+            .zip(rgb.chunks_exact_mut(width * 3))
+        {
+            for (i, px) in src.chunks_exact(4).enumerate() {
+                y[2 * i] = px[0];
+                u[i] = px[1];
+                y[2 * i + 1] = px[2];
+                v[i] = px[3];
+            }
+            self.row_to_rgb(&y, &u, &v, out);
+        }
+        Ok(rgb)
+    }
+
+    /// RGB24 转三平面 I420，色度取 2x2 块的平均
+    pub fn rgb_to_i420(&self, rgb: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size("I420", width, height, true, rgb.len(), width * height * 3)?;
+        let (luma, chroma) = (width * height, width * height / 4);
+        let mut yuv = vec![0u8; luma + 2 * chroma];
+        let (y, rest) = yuv.split_at_mut(luma);
+        let (u, v) = rest.split_at_mut(chroma);
+        self.encode_420(rgb, width, y, |row, cu, cv| {
+            let cw = width / 2;
+            u[row * cw..(row + 1) * cw].copy_from_slice(cu);
+            v[row * cw..(row + 1) * cw].copy_from_slice(cv);
+        });
+        Ok(yuv)
+    }
+
+    /// RGB24 转 NV12，色度取 2x2 块的平均
+    pub fn rgb_to_nv12(&self, rgb: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size("NV12", width, height, true, rgb.len(), width * height * 3)?;
+        let luma = width * height;
+        let mut yuv = vec![0u8; luma * 3 / 2];
+        let (y, uv) = yuv.split_at_mut(luma);
+        self.encode_420(rgb, width, y, |row, cu, cv| {
+            let out = &mut uv[row * width..(row + 1) * width];
+            for ((px, &u), &v) in out.chunks_exact_mut(2).zip(cu).zip(cv) {
+                px[0] = u;
+                px[1] = v;
+            }
+        });
+        Ok(yuv)
+    }
+
+    /// RGB24 转 YUYV，色度取水平相邻两个像素的平均
+    pub fn rgb_to_yuyv(&self, rgb: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
+        check_size("YUYV", width, height, false, rgb.len(), width * height * 3)?;
+        let cw = width / 2;
+        let (mut y, mut u, mut v) = (vec![0u8; width], vec![0u8; cw], vec![0u8; cw]);
+        let mut yuv = vec![0u8; width * height * 2];
+        for (src, out) in rgb
+            .chunks_exact(width * 3)
+            .zip(yuv.chunks_exact_mut(width * 2))
+        {
+            self.row_to_y(src, &mut y);
+            self.rows_to_uv(src, src, &mut u, &mut v);
+            for (i, px) in out.chunks_exact_mut(4).enumerate() {
+                px.copy_from_slice(&[y[2 * i], u[i], y[2 * i + 1], v[i]]);
+            }
+        }
+        Ok(yuv)
+    }
+
+    /// 4:2:0 编码的公共部分：逐行写 Y，每两行得到一行色度交给 `write_chroma`
+    fn encode_420(
+        &self,
+        rgb: &[u8],
+        width: usize,
+        y: &mut [u8],
+        mut write_chroma: impl FnMut(usize, &[u8], &[u8]),
+    ) {
+        let cw = width / 2;
+        let (mut u, mut v) = (vec![0u8; cw], vec![0u8; cw]);
+        for (row, (src, out)) in rgb
+            .chunks_exact(width * 6)
+            .zip(y.chunks_exact_mut(width * 2))
+            .enumerate()
+        {
+            let (rgb0, rgb1) = src.split_at(width * 3);
+            let (y0, y1) = out.split_at_mut(width);
+            self.row_to_y(rgb0, y0);
+            self.row_to_y(rgb1, y1);
+            self.rows_to_uv(rgb0, rgb1, &mut u, &mut v);
+            write_chroma(row, &u, &v);
+        }
+    }
+}
+
+fn deinterleave(uv: &[u8], u: &mut [u8], v: &mut [u8]) {
+    for ((px, u), v) in uv.chunks_exact(2).zip(u.iter_mut()).zip(v.iter_mut()) {
+        *u = px[0];
+        *v = px[1];
+    }
+}
+
+fn check_size(
+    format: &str,
+    width: usize,
+    height: usize,
+    even_height: bool,
+    len: usize,
+    expected: usize,
+) -> Result<()> {
+    if width == 0
+        || height == 0
+        || !width.is_multiple_of(2)
+        || (even_height && !height.is_multiple_of(2))
+    {
+        return Err(anyhow!(
+            "Invalid {} frame size {}x{}",
+            format,
+            width,
+            height
+        ));
+    }
+    if len != expected {
+        return Err(anyhow!(
+            "{} buffer of {}x{} expects {} bytes, got {}",
+            format,
+            width,
+            height,
+            expected,
+            len
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, RgbImage};
+use opencv::core::{Mat, Rect, CV_8U};
+use opencv::prelude::*;
+use std::collections::VecDeque;
+
+/// 二值掩码中只包含有效像素（非 0）的面积最大的轴对齐矩形
+///
+/// `mask` 为行优先的 width x height 掩码，没有有效像素时返回 None。
+/// 逐行累计每列连续有效像素的高度，再用单调栈求直方图中的最大矩形，复杂度 O(width * height)。
+pub fn largest_valid_rect(mask: &[u8], width: usize, height: usize) -> Option<Rect> {
+    assert_eq!(mask.len(), width * height, "mask size mismatch");
+    let mut heights = vec![0usize; width];
+    let mut stack: Vec<usize> = Vec::with_capacity(width + 1);
+    let mut best: Option<(usize, Rect)> = None;
+
+    for row in 0..height {
+        for (x, h) in heights.iter_mut().enumerate() {
+            *h = if mask[row * width + x] > 0 { *h + 1 } else { 0 };
+        }
+
+        stack.clear();
+        for x in 0..=width {
+            let h = if x < width { heights[x] } else { 0 };
+            while let Some(&top) = stack.last() {
+                if heights[top] <= h {
+                    break;
+                }
+                stack.pop();
+                let rect_h = heights[top];
+                let left = stack.last().map_or(0, |&l| l + 1);
+                let area = rect_h * (x - left);
+                if best.is_none_or(|(a, _)| area > a) {
+                    let rect = Rect::new(
+                        left as i32,
+                        (row + 1 - rect_h) as i32,
+                        (x - left) as i32,
+                        rect_h as i32,
+                    );
+                    best = Some((area, rect));
+                }
+            }
+            stack.push(x);
+        }
+    }
+    best.map(|(_, rect)| rect)
+}
+
+/// 标记边框像素：从图像四周出发，与边缘连通且各通道都不超过 `tolerance` 的像素视为黑边
+///
+/// `pixels` 为行优先、每像素 `channels` 字节的数据。返回 width x height 的掩码，有效像素为 255、黑边为 0。
+/// 只剔除与边缘连通的暗区，画面内部的暗色物体不会被当成黑边；`tolerance` 用于吸收 JPEG 压缩噪声。
+pub fn border_mask(
+    pixels: &[u8],
+    width: usize,
+    height: usize,
+    channels: usize,
+    tolerance: u8,
+) -> Vec<u8> {
+    assert_eq!(
+        pixels.len(),
+        width * height * channels,
+        "pixel buffer size mismatch"
+    );
+    let dark = |i: usize| {
+        pixels[i * channels..(i + 1) * channels]
+            .iter()
+            .all(|&v| v <= tolerance)
+    };
+
+    let mut mask = vec![255u8; width * height];
+    if width == 0 || height == 0 {
+        return mask;
+    }
+    let mut queue = VecDeque::new();
+    let seed = |i: usize, mask: &mut [u8], queue: &mut VecDeque<usize>| {
+        if mask[i] != 0 && dark(i) {
+            mask[i] = 0;
+            queue.push_back(i);
+        }
+    };
+    for x in 0..width {
+        seed(x, &mut mask, &mut queue);
+        seed((height - 1) * width + x, &mut mask, &mut queue);
+    }
+    for y in 0..height {
+        seed(y * width, &mut mask, &mut queue);
+        seed(y * width + width - 1, &mut mask, &mut queue);
+    }
+
+    while let Some(i) = queue.pop_front() {
+        let (x, y) = (i % width, i / width);
+        if x > 0 {
+            seed(i - 1, &mut mask, &mut queue);
+        }
+        if x + 1 < width {
+            seed(i + 1, &mut mask, &mut queue);
+        }
+        if y > 0 {
+            seed(i - width, &mut mask, &mut queue);
+        }
+        if y + 1 < height {
+            seed(i + width, &mut mask, &mut queue);
+        }
+    }
+    mask
+}
+
+/// RGB 图像去掉黑边后的最大有效矩形
+pub fn valid_rect(image: &RgbImage, tolerance: u8) -> Option<Rect> {
+    let (w, h) = (image.width() as usize, image.height() as usize);
+    let mask = border_mask(image.as_raw(), w, h, 3, tolerance);
+    largest_valid_rect(&mask, w, h)
+}
+
+/// 裁掉图像四周的黑边，没有有效像素时返回原图
+pub fn crop_black_borders(image: &DynamicImage, tolerance: u8) -> DynamicImage {
+    let rgb = image.to_rgb8();
+    match valid_rect(&rgb, tolerance) {
+        Some(r) if (r.width as u32, r.height as u32) != (rgb.width(), rgb.height()) => {
+            image.crop_imm(r.x as u32, r.y as u32, r.width as u32, r.height as u32)
+        }
+        _ => image.clone(),
+    }
+}
+
+/// 8 位 Mat（单通道或多通道）去掉黑边后的最大有效矩形
+pub fn mat_valid_rect(image: &Mat, tolerance: u8) -> Result<Option<Rect>> {
+    if image.depth() != CV_8U {
+        return Err(anyhow!("Expected 8-bit mat, got type {}", image.typ()));
+    }
+    if image.empty() {
+        return Ok(None);
+    }
+    let owned;
+    let image = if image.is_continuous() {
+        image
+    } else {
+        owned = image.try_clone()?;
+        &owned
+    };
+    let (w, h) = (image.cols() as usize, image.rows() as usize);
+    let mask = border_mask(
+        image.data_bytes()?,
+        w,
+        h,
+        image.channels() as usize,
+        tolerance,
+    );
+    Ok(largest_valid_rect(&mask, w, h))
+}
+
+/// 拼接结果掩码（如 `Stitcher::result_mask`）中的最大有效矩形，比按像素值判断更准确
+pub fn mask_valid_rect(mask: &Mat) -> Result<Option<Rect>> {
+    if mask.empty() {
+        return Ok(None);
+    }
+    if mask.typ() != CV_8U {
+        return Err(anyhow!("Expected CV_8UC1 mask, got type {}", mask.typ()));
+    }
+    let owned;
+    let mask = if mask.is_continuous() {
+        mask
+    } else {
+        owned = mask.try_clone()?;
+        &owned
+    };
+    Ok(largest_valid_rect(
+        mask.data_bytes()?,
+        mask.cols() as usize,
+        mask.rows() as usize,
+    ))
+}
+
+/// 裁掉 Mat 的黑边；传入拼接掩码时按掩码裁剪，否则按 `tolerance` 判断黑边像素
+pub fn crop_black_borders_mat(image: &Mat, tolerance: u8, mask: Option<&Mat>) -> Result<Mat> {
+    let rect = match mask {
+        Some(mask) => {
+            if mask.size()? != image.size()? {
+                return Err(anyhow!(
+                    "Mask size {:?} does not match image size {:?}",
+                    mask.size()?,
+                    image.size()?
+                ));
+            }
+            mask_valid_rect(mask)?
+        }
+        None => mat_valid_rect(image, tolerance)?,
+    };
+    match rect {
+        Some(rect) => Ok(image.roi(rect)?.try_clone()?),
+        None => Ok(image.try_clone()?),
+    }
+}
@@ -0,0 +1,47 @@
+use anyhow::{anyhow, Result};
+use image::{DynamicImage, RgbImage};
+use opencv::core::{Mat, Rect, Scalar, CV_8UC3};
+use opencv::prelude::*;
+
+/// RGB 图像转换为 CV_8UC3 的 Mat，通道顺序保持 RGB
+pub fn rgb_image_to_mat(image: &RgbImage) -> Result<Mat> {
+    let mut mat = Mat::new_rows_cols_with_default(
+        image.height() as i32,
+        image.width() as i32,
+        CV_8UC3,
+        Scalar::all(0.0),
+    )?;
+    mat.data_bytes_mut()?.copy_from_slice(image.as_raw());
+    Ok(mat)
+}
+
+/// CV_8UC3 的 Mat（RGB 通道顺序）转换为 RGB 图像
+pub fn mat_to_rgb_image(mat: &Mat) -> Result<RgbImage> {
+    if mat.typ() != CV_8UC3 {
+        return Err(anyhow!("Expected CV_8UC3 mat, got type {}", mat.typ()));
+    }
+    let data = if mat.is_continuous() {
+        mat.data_bytes()?.to_vec()
+    } else {
+        mat.try_clone()?.data_bytes()?.to_vec()
+    };
+    RgbImage::from_raw(mat.cols() as u32, mat.rows() as u32, data)
+        .ok_or_else(|| anyhow!("Invalid mat buffer size"))
+}
+
+/// DynamicImage 转换为 RGB 通道顺序的 Mat
+pub fn dynamic_image_to_mat(image: &DynamicImage) -> Result<Mat> {
+    match image.as_rgb8() {
+        Some(rgb) => rgb_image_to_mat(rgb),
+        None => rgb_image_to_mat(&image.to_rgb8()),
+    }
+}
+
+/// 将矩形裁剪到图像范围内，没有交集时返回 None
+pub fn clip_rect(rect: Rect, cols: i32, rows: i32) -> Option<Rect> {
+    let x1 = rect.x.clamp(0, cols);
+    let y1 = rect.y.clamp(0, rows);
+    let x2 = (rect.x + rect.width).clamp(0, cols);
+    let y2 = (rect.y + rect.height).clamp(0, rows);
+    (x2 > x1 && y2 > y1).then(|| Rect::new(x1, y1, x2 - x1, y2 - y1))
+}
@@ -0,0 +1,482 @@
+use opencv::core::Rect;
+use serde::{Deserialize, Serialize};
+
+const EPS: f32 = 1e-7;
+
+/// COCO 17 个人体关键点的 OKS sigma
+pub const COCO_KEYPOINT_SIGMAS: [f32; 17] = [
+    0.026, 0.025, 0.025, 0.035, 0.035, 0.079, 0.079, 0.072, 0.072, 0.062, 0.062, 0.107, 0.107,
+    0.087, 0.087, 0.089, 0.089,
+];
+
+/// 检测框上用于计数、热力图、轨迹等的锚点
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    Center,
+    /// 底边中点，适合行人、车辆等贴地目标
+    #[default]
+    BottomCenter,
+    TopCenter,
+}
+
+/// 浮点轴对齐矩形框，内部以左上角 (x1, y1) 与右下角 (x2, y2) 表示
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BoxF {
+    pub x1: f32,
+    pub y1: f32,
+    pub x2: f32,
+    pub y2: f32,
+}
+
+impl BoxF {
+    pub fn new(x1: f32, y1: f32, x2: f32, y2: f32) -> Self {
+        Self { x1, y1, x2, y2 }
+    }
+
+    pub fn from_xyxy([x1, y1, x2, y2]: [f32; 4]) -> Self {
+        Self::new(x1, y1, x2, y2)
+    }
+
+    /// 由左上角与宽高构造
+    pub fn from_xywh([x, y, w, h]: [f32; 4]) -> Self {
+        Self::new(x, y, x + w, y + h)
+    }
+
+    /// 由中心点与宽高构造
+    pub fn from_cxcywh([cx, cy, w, h]: [f32; 4]) -> Self {
+        Self::new(cx - w / 2.0, cy - h / 2.0, cx + w / 2.0, cy + h / 2.0)
+    }
+
+    pub fn xyxy(&self) -> [f32; 4] {
+        [self.x1, self.y1, self.x2, self.y2]
+    }
+
+    pub fn xywh(&self) -> [f32; 4] {
+        [self.x1, self.y1, self.width(), self.height()]
+    }
+
+    pub fn cxcywh(&self) -> [f32; 4] {
+        let (cx, cy) = self.center();
+        [cx, cy, self.width(), self.height()]
+    }
+
+    /// 四舍五入转换为整数矩形
+    pub fn to_rect(&self) -> Rect {
+        let x = self.x1.round() as i32;
+        let y = self.y1.round() as i32;
+        Rect::new(x, y, self.x2.round() as i32 - x, self.y2.round() as i32 - y)
+    }
+
+    pub fn width(&self) -> f32 {
+        (self.x2 - self.x1).max(0.0)
+    }
+
+    pub fn height(&self) -> f32 {
+        (self.y2 - self.y1).max(0.0)
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width() * self.height()
+    }
+
+    pub fn center(&self) -> (f32, f32) {
+        ((self.x1 + self.x2) / 2.0, (self.y1 + self.y2) / 2.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.area() <= 0.0
+    }
+
+    pub fn anchor(&self, anchor: Anchor) -> (f32, f32) {
+        let cx = (self.x1 + self.x2) / 2.0;
+        match anchor {
+            Anchor::Center => self.center(),
+            Anchor::BottomCenter => (cx, self.y2),
+            Anchor::TopCenter => (cx, self.y1),
+        }
+    }
+
+    /// 交集框，不相交时返回 None
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let b = Self::new(
+            self.x1.max(other.x1),
+            self.y1.max(other.y1),
+            self.x2.min(other.x2),
+            self.y2.min(other.y2),
+        );
+        (b.x2 > b.x1 && b.y2 > b.y1).then_some(b)
+    }
+
+    /// 同时包含两个框的最小外接框
+    pub fn enclosing(&self, other: &Self) -> Self {
+        Self::new(
+            self.x1.min(other.x1),
+            self.y1.min(other.y1),
+            self.x2.max(other.x2),
+            self.y2.max(other.y2),
+        )
+    }
+
+    pub fn intersection_area(&self, other: &Self) -> f32 {
+        self.intersection(other).map_or(0.0, |b| b.area())
+    }
+
+    pub fn union_area(&self, other: &Self) -> f32 {
+        self.area() + other.area() - self.intersection_area(other)
+    }
+
+    /// 平移
+    pub fn translate(&self, dx: f32, dy: f32) -> Self {
+        Self::new(self.x1 + dx, self.y1 + dy, self.x2 + dx, self.y2 + dy)
+    }
+
+    /// 按 x/y 方向分别缩放坐标
+    pub fn scale(&self, sx: f32, sy: f32) -> Self {
+        Self::new(self.x1 * sx, self.y1 * sy, self.x2 * sx, self.y2 * sy)
+    }
+
+    /// 裁剪到 [0, width] x [0, height] 范围内
+    pub fn clip(&self, width: f32, height: f32) -> Self {
+        Self::new(
+            self.x1.clamp(0.0, width),
+            self.y1.clamp(0.0, height),
+            self.x2.clamp(0.0, width),
+            self.y2.clamp(0.0, height),
+        )
+    }
+
+    /// 交并比
+    pub fn iou(&self, other: &Self) -> f32 {
+        let union = self.union_area(other);
+        if union <= 0.0 {
+            return 0.0;
+        }
+        self.intersection_area(other) / union
+    }
+
+    /// 交集与较小框面积之比（Intersection over Smaller），用于判断包含关系
+    pub fn ios(&self, other: &Self) -> f32 {
+        let smaller = self.area().min(other.area());
+        if smaller <= 0.0 {
+            return 0.0;
+        }
+        self.intersection_area(other) / smaller
+    }
+
+    /// Generalized IoU，取值范围 [-1, 1]
+    pub fn giou(&self, other: &Self) -> f32 {
+        let union = self.union_area(other);
+        let enclosing = self.enclosing(other).area();
+        if union <= 0.0 || enclosing <= 0.0 {
+            return 0.0;
+        }
+        self.iou(other) - (enclosing - union) / enclosing
+    }
+
+    /// Distance IoU：在 IoU 基础上惩罚中心点距离，取值范围 [-1, 1]
+    pub fn diou(&self, other: &Self) -> f32 {
+        self.iou(other) - self.center_distance_penalty(other)
+    }
+
+    /// Complete IoU：在 DIoU 基础上惩罚宽高比差异
+    pub fn ciou(&self, other: &Self) -> f32 {
+        let iou = self.iou(other);
+        let v = (4.0 / (std::f32::consts::PI * std::f32::consts::PI))
+            * ((other.width() / (other.height() + EPS)).atan()
+                - (self.width() / (self.height() + EPS)).atan())
+            .powi(2);
+        let alpha = if v > 0.0 { v / (1.0 - iou + v) } else { 0.0 };
+        iou - self.center_distance_penalty(other) - alpha * v
+    }
+
+    /// 中心点距离平方与外接框对角线平方之比
+    fn center_distance_penalty(&self, other: &Self) -> f32 {
+        let (ax, ay) = self.center();
+        let (bx, by) = other.center();
+        let c = self.enclosing(other);
+        let diag = (c.x2 - c.x1).powi(2) + (c.y2 - c.y1).powi(2);
+        if diag <= EPS {
+            return 0.0;
+        }
+        ((ax - bx).powi(2) + (ay - by).powi(2)) / diag
+    }
+}
+
+impl From<Rect> for BoxF {
+    fn from(r: Rect) -> Self {
+        Self::from_xywh([r.x as f32, r.y as f32, r.width as f32, r.height as f32])
+    }
+}
+
+impl From<&Rect> for BoxF {
+    fn from(r: &Rect) -> Self {
+        Self::from(*r)
+    }
+}
+
+impl From<BoxF> for Rect {
+    fn from(b: BoxF) -> Self {
+        b.to_rect()
+    }
+}
+
+/// 旋转矩形框，角度为弧度，图像坐标系（y 轴向下）下顺时针为正
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RotatedBox {
+    pub cx: f32,
+    pub cy: f32,
+    pub width: f32,
+    pub height: f32,
+    pub angle: f32,
+}
+
+impl RotatedBox {
+    pub fn new(cx: f32, cy: f32, width: f32, height: f32, angle: f32) -> Self {
+        Self {
+            cx,
+            cy,
+            width,
+            height,
+            angle,
+        }
+    }
+
+    pub fn area(&self) -> f32 {
+        self.width.max(0.0) * self.height.max(0.0)
+    }
+
+    /// 四个顶点
+    pub fn corners(&self) -> [(f32, f32); 4] {
+        let (sin, cos) = self.angle.sin_cos();
+        let (hw, hh) = (self.width / 2.0, self.height / 2.0);
+        [(-hw, -hh), (hw, -hh), (hw, hh), (-hw, hh)]
+            .map(|(dx, dy)| (self.cx + dx * cos - dy * sin, self.cy + dx * sin + dy * cos))
+    }
+
+    /// 外接轴对齐框
+    pub fn bounding_box(&self) -> BoxF {
+        self.corners().iter().fold(
+            BoxF::new(f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+            |b, &(x, y)| BoxF::new(b.x1.min(x), b.y1.min(y), b.x2.max(x), b.y2.max(y)),
+        )
+    }
+
+    /// 基于多边形裁剪的旋转框交并比
+    pub fn iou(&self, other: &Self) -> f32 {
+        polygon_iou(&self.corners(), &other.corners())
+    }
+}
+
+/// 多边形面积（鞋带公式），顶点顺序任意
+pub fn polygon_area(points: &[(f32, f32)]) -> f32 {
+    if points.len() < 3 {
+        return 0.0;
+    }
+    let sum: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum();
+    (sum / 2.0).abs()
+}
+
+/// 点是否在多边形内（奇偶规则），顶点顺序任意
+pub fn point_in_polygon((x, y): (f32, f32), polygon: &[(f32, f32)]) -> bool {
+    let mut inside = false;
+    for (i, &(x1, y1)) in polygon.iter().enumerate() {
+        let (x2, y2) = polygon[(i + 1) % polygon.len()];
+        if (y1 > y) != (y2 > y) && x < x1 + (y - y1) / (y2 - y1) * (x2 - x1) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+/// Douglas–Peucker 折线简化，保留首尾点，epsilon 为允许的最大偏离距离
+pub fn simplify_polyline(points: &[(f32, f32)], epsilon: f32) -> Vec<(f32, f32)> {
+    simplify_polyline_indices(points, epsilon)
+        .into_iter()
+        .map(|i| points[i])
+        .collect()
+}
+
+/// Douglas–Peucker 折线简化，返回保留点的下标（升序）
+pub fn simplify_polyline_indices(points: &[(f32, f32)], epsilon: f32) -> Vec<usize> {
+    if points.len() < 3 {
+        return (0..points.len()).collect();
+    }
+    let mut keep = vec![false; points.len()];
+    keep[0] = true;
+    keep[points.len() - 1] = true;
+    let mut stack = vec![(0, points.len() - 1)];
+    while let Some((start, end)) = stack.pop() {
+        let (a, b) = (points[start], points[end]);
+        let (farthest, distance) = (start + 1..end)
+            .map(|i| (i, point_segment_distance(points[i], a, b)))
+            .fold((start, 0.0), |best, x| if x.1 > best.1 { x } else { best });
+        if distance > epsilon {
+            keep[farthest] = true;
+            stack.push((start, farthest));
+            stack.push((farthest, end));
+        }
+    }
+    (0..points.len()).filter(|&i| keep[i]).collect()
+}
+
+/// 点到线段的距离
+pub fn point_segment_distance(
+    (px, py): (f32, f32),
+    (ax, ay): (f32, f32),
+    (bx, by): (f32, f32),
+) -> f32 {
+    let (dx, dy) = (bx - ax, by - ay);
+    let len2 = dx * dx + dy * dy;
+    let t = if len2 > 0.0 {
+        (((px - ax) * dx + (py - ay) * dy) / len2).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let (cx, cy) = (ax + t * dx, ay + t * dy);
+    ((px - cx).powi(2) + (py - cy).powi(2)).sqrt()
+}
+
+/// 两个凸多边形的交集（Sutherland–Hodgman 裁剪）
+pub fn convex_polygon_intersection(subject: &[(f32, f32)], clip: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    if subject.len() < 3 || clip.len() < 3 {
+        return Vec::new();
+    }
+
+    let clip = counter_clockwise(clip);
+    let mut output = counter_clockwise(subject);
+    for (i, &a) in clip.iter().enumerate() {
+        let b = clip[(i + 1) % clip.len()];
+        let input = std::mem::take(&mut output);
+        if input.is_empty() {
+            break;
+        }
+        // 点在边 a->b 左侧（含边上）视为在内部
+        let inside = |p: (f32, f32)| (b.0 - a.0) * (p.1 - a.1) - (b.1 - a.1) * (p.0 - a.0) >= 0.0;
+        for (j, &p) in input.iter().enumerate() {
+            let q = input[(j + 1) % input.len()];
+            match (inside(p), inside(q)) {
+                (true, true) => output.push(q),
+                (true, false) => output.push(line_intersection(p, q, a, b)),
+                (false, true) => {
+                    output.push(line_intersection(p, q, a, b));
+                    output.push(q);
+                }
+                (false, false) => {}
+            }
+        }
+    }
+    output
+}
+
+/// 两个凸多边形的交并比
+pub fn polygon_iou(a: &[(f32, f32)], b: &[(f32, f32)]) -> f32 {
+    let inter = polygon_area(&convex_polygon_intersection(a, b));
+    let union = polygon_area(a) + polygon_area(b) - inter;
+    if union <= 0.0 {
+        return 0.0;
+    }
+    (inter / union).clamp(0.0, 1.0)
+}
+
+fn counter_clockwise(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let signed: f32 = points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.0 * b.1 - b.0 * a.1)
+        .sum();
+    let mut points = points.to_vec();
+    if signed < 0.0 {
+        points.reverse();
+    }
+    points
+}
+
+fn line_intersection(p: (f32, f32), q: (f32, f32), a: (f32, f32), b: (f32, f32)) -> (f32, f32) {
+    let (dx1, dy1) = (q.0 - p.0, q.1 - p.1);
+    let (dx2, dy2) = (b.0 - a.0, b.1 - a.1);
+    let denom = dx1 * dy2 - dy1 * dx2;
+    if denom.abs() < EPS {
+        return q;
+    }
+    let t = ((a.0 - p.0) * dy2 - (a.1 - p.1) * dx2) / denom;
+    (p.0 + t * dx1, p.1 + t * dy1)
+}
+
+/// 将多边形栅格化为 width x height 的二值掩码（255 为多边形内部）
+///
+/// 以像素中心判断是否在多边形内，使用奇偶规则，支持凹多边形。
+pub fn rasterize_polygons(polygons: &[Vec<(f32, f32)>], width: u32, height: u32) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let mut mask = vec![0u8; w * h];
+    let mut xs = Vec::new();
+    for polygon in polygons.iter().filter(|p| p.len() >= 3) {
+        for row in 0..h {
+            let cy = row as f32 + 0.5;
+            xs.clear();
+            for (i, &(x1, y1)) in polygon.iter().enumerate() {
+                let (x2, y2) = polygon[(i + 1) % polygon.len()];
+                if (y1 <= cy && y2 > cy) || (y2 <= cy && y1 > cy) {
+                    xs.push(x1 + (cy - y1) / (y2 - y1) * (x2 - x1));
+                }
+            }
+            xs.sort_by(|a, b| a.total_cmp(b));
+            for pair in xs.chunks_exact(2) {
+                // 像素中心 col + 0.5 落在 [pair[0], pair[1]) 内
+                let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((pair[1] - 0.5).ceil().max(0.0) as usize).min(w);
+                if start < end {
+                    mask[row * w + start..row * w + end].fill(255);
+                }
+            }
+        }
+    }
+    mask
+}
+
+/// 两个同尺寸二值掩码的交并比，非零像素视为前景；尺寸不同时返回 None
+pub fn mask_iou(a: &[u8], b: &[u8]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let (inter, union) = a.iter().zip(b).fold((0u64, 0u64), |(i, u), (&x, &y)| {
+        let (x, y) = (x != 0, y != 0);
+        (i + (x && y) as u64, u + (x || y) as u64)
+    });
+    if union == 0 {
+        return Some(0.0);
+    }
+    Some(inter as f32 / union as f32)
+}
+
+/// 关键点相似度 OKS（Object Keypoint Similarity）
+///
+/// `area` 为目标面积（COCO 中为分割面积，缺省时可用框面积），
+/// 只统计真值中可见的关键点；没有可见关键点时返回 0。
+pub fn oks(
+    pred: &[(f32, f32)],
+    gt: &[(f32, f32)],
+    visible: &[bool],
+    area: f32,
+    sigmas: &[f32],
+) -> f32 {
+    let mut sum = 0.0;
+    let mut count = 0;
+    for (((p, g), &v), &sigma) in pred.iter().zip(gt).zip(visible).zip(sigmas) {
+        if !v {
+            continue;
+        }
+        let d2 = (p.0 - g.0).powi(2) + (p.1 - g.1).powi(2);
+        let k2 = (2.0 * sigma).powi(2);
+        sum += (-d2 / (2.0 * (area + EPS) * k2)).exp();
+        count += 1;
+    }
+    if count == 0 {
+        return 0.0;
+    }
+    sum / count as f32
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Context, Error};
+use reqwest::Client;
+use serde::Deserialize;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// 请求鉴权方式，每次发送请求（含重试）前生成 `Authorization` 头
+#[derive(Clone)]
+pub enum Auth {
+    /// 固定 Bearer 令牌
+    Bearer(String),
+    /// 从环境变量读取 Bearer 令牌，每次请求时读取
+    BearerEnv(String),
+    /// 从文件读取 Bearer 令牌，每次请求时读取，便于令牌轮换
+    BearerFile(PathBuf),
+    /// OAuth2 client credentials 流程
+    OAuth2(ClientCredentials),
+    /// 自定义，返回完整的 `Authorization` 头的值
+    Custom(Arc<dyn Fn() -> Result<String, Error> + Send + Sync>),
+}
+
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 不输出令牌本身
+        match self {
+            Self::Bearer(_) => write!(f, "Bearer(***)"),
+            Self::BearerEnv(var) => write!(f, "BearerEnv({})", var),
+            Self::BearerFile(path) => write!(f, "BearerFile({})", path.display()),
+            Self::OAuth2(c) => write!(f, "OAuth2({:?})", c),
+            Self::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Auth {
+    /// 生成 `Authorization` 头的值，OAuth2 令牌需要时通过 `client` 获取
+    pub async fn header_value(&self, client: &Client) -> Result<String, Error> {
+        let token = match self {
+            Self::Bearer(token) => token.clone(),
+            Self::BearerEnv(var) => {
+                std::env::var(var).with_context(|| format!("Token env var {} not set", var))?
+            }
+            Self::BearerFile(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read token file {}", path.display()))?,
+            Self::OAuth2(credentials) => credentials.token(client).await?,
+            Self::Custom(f) => return f(),
+        };
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(anyhow!("Empty bearer token from {:?}", self));
+        }
+        Ok(format!("Bearer {}", token))
+    }
+}
+
+/// OAuth2 client credentials 配置，令牌缓存在各克隆间共享，过期前 `refresh_before` 刷新
+#[derive(Clone)]
+pub struct ClientCredentials {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    pub scope: Option<String>,
+    pub refresh_before: Duration,
+    cache: Arc<Mutex<Option<CachedToken>>>,
+}
+
+impl fmt::Debug for ClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientCredentials")
+            .field("token_url", &self.token_url)
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .field("refresh_before", &self.refresh_before)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug)]
+struct CachedToken {
+    access_token: String,
+    expires_at: Instant,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    /// 秒，缺省按 1 小时
+    expires_in: Option<u64>,
+}
+
+impl ClientCredentials {
+    pub fn new(token_url: &str, client_id: &str, client_secret: &str) -> Self {
+        Self {
+            token_url: token_url.to_string(),
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+            scope: None,
+            refresh_before: Duration::from_secs(60),
+            cache: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub fn with_scope(mut self, x: &str) -> Self {
+        self.scope = Some(x.to_string());
+        self
+    }
+
+    pub fn with_refresh_before(mut self, x: Duration) -> Self {
+        self.refresh_before = x;
+        self
+    }
+
+    /// 返回缓存的令牌，即将过期时重新获取；并发请求只会触发一次获取
+    pub async fn token(&self, client: &Client) -> Result<String, Error> {
+        let mut cache = self.cache.lock().await;
+        if let Some(cached) = cache.as_ref() {
+            if cached.expires_at > Instant::now() + self.refresh_before {
+                return Ok(cached.access_token.clone());
+            }
+        }
+
+        let mut form = vec![
+            ("grant_type", "client_credentials"),
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.as_str()),
+        ];
+        if let Some(scope) = &self.scope {
+            form.push(("scope", scope.as_str()));
+        }
+        let requested_at = Instant::now();
+        let response = client.post(&self.token_url).form(&form).send().await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await?;
+            return Err(anyhow!(
+                "OAuth2 token request failed with status code: {}, error: {}",
+                status,
+                error_text
+            ));
+        }
+        let token = response.json::<TokenResponse>().await?;
+        let expires_in = Duration::from_secs(token.expires_in.unwrap_or(3600));
+        *cache = Some(CachedToken {
+            access_token: token.access_token.clone(),
+            expires_at: requested_at + expires_in,
+        });
+        Ok(token.access_token)
+    }
+
+    /// 丢弃缓存的令牌，下次请求时重新获取
+    pub async fn invalidate(&self) {
+        *self.cache.lock().await = None;
+    }
+}
@@ -0,0 +1,53 @@
+use anyhow::{anyhow, Error};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::fmt;
+
+/// 默认表示成功的 `code`
+pub const DEFAULT_SUCCESS_CODES: [i64; 2] = [0, 200];
+
+/// 后端统一的响应包装 `{"code": 0, "msg": "success", "data": ...}`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ApiResponse {
+    pub code: i64,
+    #[serde(default, alias = "message")]
+    pub msg: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl ApiResponse {
+    /// `code` 在 `success_codes` 中时把 `data` 反序列化为 `T`，否则返回 [`ApiError`]
+    pub fn into_data<T: DeserializeOwned>(self, success_codes: &[i64]) -> Result<T, Error> {
+        if !success_codes.contains(&self.code) {
+            return Err(ApiError {
+                code: self.code,
+                msg: self.msg.unwrap_or_default(),
+            }
+            .into());
+        }
+        serde_json::from_value(self.data).map_err(|e| {
+            anyhow!(
+                "Failed to decode response data as {}: {}",
+                std::any::type_name::<T>(),
+                e
+            )
+        })
+    }
+}
+
+/// 响应包装中 `code` 不表示成功，可通过 `Error::downcast_ref::<ApiError>()` 取出
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub code: i64,
+    pub msg: String,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "API error code: {}, msg: {}", self.code, self.msg)
+    }
+}
+
+impl std::error::Error for ApiError {}
@@ -0,0 +1,439 @@
+mod auth;
+mod envelope;
+mod page;
+mod retry;
+
+pub use auth::{Auth, ClientCredentials};
+pub use envelope::{ApiError, ApiResponse, DEFAULT_SUCCESS_CODES};
+pub use page::{Page, Paginator};
+pub use retry::{RetryPolicy, IDEMPOTENCY_KEY};
+
+use anyhow::{anyhow, Error};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{multipart, Client, Method, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::Duration;
+
+/// [`HttpClient`] 构建器
+///
+/// ```ignore
+/// let client = HttpClient::builder()
+///     .with_base_url("http://127.0.0.1:8080/api")
+///     .with_auth(Auth::BearerEnv("API_TOKEN".to_string()))
+///     .build()?;
+/// let users = client.get("/users", None, None).await?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct HttpClientBuilder {
+    base_url: Option<String>,
+    headers: HashMap<String, String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    auth: Option<Auth>,
+    retry: RetryPolicy,
+    success_codes: Vec<i64>,
+}
+
+impl Default for HttpClientBuilder {
+    fn default() -> Self {
+        Self {
+            base_url: None,
+            headers: HashMap::new(),
+            timeout: Some(Duration::from_secs(10)),
+            connect_timeout: None,
+            user_agent: None,
+            auth: None,
+            retry: RetryPolicy::default(),
+            success_codes: DEFAULT_SUCCESS_CODES.to_vec(),
+        }
+    }
+}
+
+impl HttpClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 相对路径的请求以此为前缀，完整 URL 不受影响
+    pub fn with_base_url(mut self, x: &str) -> Self {
+        self.base_url = Some(x.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// 每个请求都带的请求头，单次请求传入的同名请求头优先
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn with_headers(mut self, x: HashMap<String, String>) -> Self {
+        self.headers.extend(x);
+        self
+    }
+
+    /// 单个请求从连接到读完响应的总超时，None 表示不限制
+    pub fn with_timeout(mut self, x: Option<Duration>) -> Self {
+        self.timeout = x;
+        self
+    }
+
+    pub fn with_connect_timeout(mut self, x: Duration) -> Self {
+        self.connect_timeout = Some(x);
+        self
+    }
+
+    pub fn with_user_agent(mut self, x: &str) -> Self {
+        self.user_agent = Some(x.to_string());
+        self
+    }
+
+    pub fn with_auth(mut self, x: Auth) -> Self {
+        self.auth = Some(x);
+        self
+    }
+
+    pub fn with_retry(mut self, x: RetryPolicy) -> Self {
+        self.retry = x;
+        self
+    }
+
+    /// 响应包装中表示成功的 `code`，见 [`ApiResponse`]
+    pub fn with_success_codes(mut self, x: &[i64]) -> Self {
+        self.success_codes = x.to_vec();
+        self
+    }
+
+    pub fn build(self) -> Result<HttpClient, Error> {
+        let mut headers = HeaderMap::new();
+        for (key, value) in &self.headers {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| anyhow!("Invalid header name {:?}: {}", key, e))?;
+            let value = HeaderValue::from_str(value)
+                .map_err(|e| anyhow!("Invalid value for header {}: {}", key, e))?;
+            headers.insert(name, value);
+        }
+
+        let mut builder = Client::builder().default_headers(headers);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(user_agent) = &self.user_agent {
+            builder = builder.user_agent(user_agent);
+        }
+
+        Ok(HttpClient {
+            client: builder.build()?,
+            base_url: self.base_url,
+            auth: self.auth,
+            retry: self.retry,
+            success_codes: self.success_codes,
+        })
+    }
+}
+
+pub struct HttpClient {
+    client: Client,
+    base_url: Option<String>,
+    auth: Option<Auth>,
+    retry: RetryPolicy,
+    success_codes: Vec<i64>,
+}
+
+impl HttpClient {
+    /// 默认配置：10 秒超时、默认重试策略、无鉴权
+    pub fn new() -> Result<Self, Error> {
+        HttpClientBuilder::new().build()
+    }
+
+    pub fn builder() -> HttpClientBuilder {
+        HttpClientBuilder::new()
+    }
+
+    pub fn with_retry(mut self, policy: RetryPolicy) -> Self {
+        self.retry = policy;
+        self
+    }
+
+    pub fn retry(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    /// 相对路径拼接到 base URL 上，完整 URL 原样返回
+    pub fn url(&self, path: &str) -> String {
+        match &self.base_url {
+            Some(base) if !path.starts_with("http://") && !path.starts_with("https://") => {
+                format!("{}/{}", base, path.trim_start_matches('/'))
+            }
+            _ => path.to_string(),
+        }
+    }
+
+    /// GET 请求方法，包含 URL、请求头、请求参数，并返回 JSON 数据
+    pub async fn get(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        query_params: Option<HashMap<&str, &str>>,
+    ) -> Result<Value, Error> {
+        self.get_as(url, headers, query_params).await
+    }
+
+    /// GET 请求，响应反序列化为 `T`
+    pub async fn get_as<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        query_params: Option<HashMap<&str, &str>>,
+    ) -> Result<T, Error> {
+        self.execute(Method::GET, headers, || {
+            let req = self.client.get(self.url(url));
+            // 添加查询参数
+            match &query_params {
+                Some(params) => req.query(params),
+                None => req,
+            }
+        })
+        .await
+    }
+
+    /// POST 请求方法，包含 URL、请求头、请求 BODY，并返回 JSON 数据
+    ///
+    /// 只有 `headers` 中带有 [`IDEMPOTENCY_KEY`] 时失败才会重试。
+    pub async fn post_json<T: Serialize>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        body: &T,
+    ) -> Result<Value, Error> {
+        self.post_as(url, headers, body).await
+    }
+
+    /// POST JSON 请求，响应反序列化为 `T`，重试规则同 [`HttpClient::post_json`]
+    pub async fn post_as<T: DeserializeOwned, B: Serialize>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        body: &B,
+    ) -> Result<T, Error> {
+        self.execute(Method::POST, headers, || {
+            self.client.post(self.url(url)).json(body)
+        })
+        .await
+    }
+
+    /// GET 请求后拆开 `code` / `msg` / `data` 包装，`code` 不表示成功时返回 [`ApiError`]
+    pub async fn get_data<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        query_params: Option<HashMap<&str, &str>>,
+    ) -> Result<T, Error> {
+        self.get_as::<ApiResponse>(url, headers, query_params)
+            .await?
+            .into_data(&self.success_codes)
+    }
+
+    /// POST JSON 请求后拆开 `code` / `msg` / `data` 包装，见 [`HttpClient::get_data`]
+    pub async fn post_data<T: DeserializeOwned, B: Serialize>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        body: &B,
+    ) -> Result<T, Error> {
+        self.post_as::<ApiResponse, _>(url, headers, body)
+            .await?
+            .into_data(&self.success_codes)
+    }
+
+    /// 按 `pageNo` / `pageSize` 逐页请求分页接口，`params` 为其余查询条件（JSON 对象）
+    ///
+    /// 默认以 POST JSON 发送，见 [`Paginator`]。
+    pub fn paginate<T: DeserializeOwned, P: Serialize>(
+        &self,
+        url: &str,
+        params: &P,
+    ) -> Result<Paginator<'_, T>, Error> {
+        Paginator::new(self, url, params)
+    }
+
+    /// POST form-data 请求方法
+    pub async fn post_form(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        form_data: &HashMap<&str, &str>,
+    ) -> Result<Value, Error> {
+        self.execute(Method::POST, headers, || {
+            self.client.post(self.url(url)).form(form_data)
+        })
+        .await
+    }
+
+    /// POST form-data 请求方法（支持文件上传）
+    pub async fn post_form_with_file(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        fields: HashMap<&str, &str>,
+        file_field_name: &str,
+        file_path: &Path,
+    ) -> Result<Value, Error> {
+        let file_bytes = std::fs::read(file_path)?;
+        let file_name = file_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        // multipart 表单无法复用，每次发送时重新构建
+        self.execute(Method::POST, headers, || {
+            // 添加普通字段到 form-data
+            let mut form = multipart::Form::new();
+            for (key, value) in &fields {
+                form = form.text(key.to_string(), value.to_string());
+            }
+
+            // 添加文件字段到 form-data
+            let file_part = multipart::Part::bytes(file_bytes.clone()).file_name(file_name.clone());
+            form = form.part(file_field_name.to_string(), file_part);
+
+            self.client.post(self.url(url)).multipart(form)
+        })
+        .await
+    }
+
+    /// PUT 请求方法
+    pub async fn put_json<T: serde::Serialize>(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+        body: &T,
+    ) -> Result<Value, Error> {
+        self.execute(Method::PUT, headers, || {
+            self.client.put(self.url(url)).json(body)
+        })
+        .await
+    }
+
+    /// DELETE 请求方法
+    pub async fn delete(
+        &self,
+        url: &str,
+        headers: Option<HashMap<String, String>>,
+    ) -> Result<Value, Error> {
+        self.execute(Method::DELETE, headers, || {
+            self.client.delete(self.url(url))
+        })
+        .await
+    }
+
+    /// 按重试策略发送请求，`build` 每次尝试都重新构建请求
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        headers: Option<HashMap<String, String>>,
+        build: impl Fn() -> RequestBuilder,
+    ) -> Result<T, Error> {
+        let retryable = RetryPolicy::is_retryable(&method, headers.as_ref());
+        // 单次请求自带 Authorization 时不再使用客户端的鉴权
+        let auth = self.auth.as_ref().filter(|_| {
+            !headers.as_ref().is_some_and(|h| {
+                h.keys()
+                    .any(|k| k.eq_ignore_ascii_case(AUTHORIZATION.as_str()))
+            })
+        });
+        let mut attempt = 1;
+        let mut reauthorized = false;
+        loop {
+            let mut req = build();
+            if let Some(auth) = auth {
+                req = req.header(AUTHORIZATION, auth.header_value(&self.client).await?);
+            }
+            // 添加 headers
+            if let Some(headers_map) = &headers {
+                for (key, value) in headers_map {
+                    req = req.header(key, value);
+                }
+            }
+
+            let result = req.send().await;
+
+            // OAuth2 令牌被服务端提前吊销时，重新获取一次令牌后再发送，不计入重试次数
+            if let (Some(Auth::OAuth2(credentials)), Ok(response)) = (auth, &result) {
+                if response.status() == StatusCode::UNAUTHORIZED && !reauthorized {
+                    tracing::warn!("{} request unauthorized, refreshing OAuth2 token", method);
+                    credentials.invalidate().await;
+                    reauthorized = true;
+                    continue;
+                }
+            }
+
+            let can_retry = retryable && attempt < self.retry.max_attempts;
+            let delay = match &result {
+                Ok(response) if can_retry && self.retry.retries_status(response.status()) => {
+                    tracing::warn!(
+                        "{} request failed with status {}, retry {}/{}",
+                        method,
+                        response.status(),
+                        attempt,
+                        self.retry.max_attempts - 1
+                    );
+                    Some(
+                        self.retry
+                            .retry_after(response)
+                            .unwrap_or_else(|| self.retry.delay(attempt)),
+                    )
+                }
+                Err(e) if can_retry && self.retry.retries_error(e) => {
+                    tracing::warn!(
+                        "{} request failed: {}, retry {}/{}",
+                        method,
+                        e,
+                        attempt,
+                        self.retry.max_attempts - 1
+                    );
+                    Some(self.retry.delay(attempt))
+                }
+                _ => None,
+            };
+
+            match delay {
+                Some(delay) => {
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return self.handle_response(result?).await,
+            }
+        }
+    }
+
+    /// 处理响应并将其反序列化为 `T`
+    async fn handle_response<T: DeserializeOwned>(&self, response: Response) -> Result<T, Error> {
+        if response.status().is_success() {
+            let text = response.text().await?;
+            serde_json::from_str(&text).map_err(|e| {
+                anyhow!(
+                    "Failed to decode response as {}: {}, body: {}",
+                    std::any::type_name::<T>(),
+                    e,
+                    text.chars().take(200).collect::<String>()
+                )
+            })
+        } else {
+            // 可以根据响应状态码进行自定义错误处理
+            let status = response.status();
+            let error_text = response.text().await?;
+            Err(Error::msg(format!(
+                "HTTP request failed with status code: {}, error: {}",
+                status, error_text
+            )))
+        }
+    }
+}
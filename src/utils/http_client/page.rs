@@ -0,0 +1,220 @@
+use anyhow::{anyhow, Error};
+use futures::stream::{self, Stream};
+use reqwest::Method;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{ApiResponse, HttpClient, IDEMPOTENCY_KEY};
+
+/// 分页接口 `data` 部分，兼容 `records` / `list` / `rows` 三种记录字段名
+#[derive(Debug, Clone, Deserialize)]
+pub struct Page<T> {
+    #[serde(default = "Vec::new", alias = "list", alias = "rows")]
+    pub records: Vec<T>,
+    /// 记录总数
+    #[serde(default)]
+    pub total: Option<u64>,
+    /// 总页数
+    #[serde(default)]
+    pub pages: Option<u64>,
+}
+
+/// 逐页请求 `pageNo` / `pageSize` 分页接口
+///
+/// 每页响应按 [`ApiResponse`] 拆包后解析为 [`Page`]。本页记录为空时停止；
+/// 否则优先按服务端返回的 `total`（已取满）或 `pages`（已到末页）判断，
+/// 两者都没有时才以本页不足 `pageSize` 作为结束（服务端限制了 pageSize 时会提前停止）。
+///
+/// 默认以 POST 发送，每页请求带上各不相同的 [`IDEMPOTENCY_KEY`]，失败时与 GET 一样按重试策略重试。
+///
+/// ```ignore
+/// let plans = client.paginate::<AlarmPlan, _>("/alarm/plan/page", &filter)?;
+/// let mut plans = std::pin::pin!(plans.into_stream());
+/// while let Some(plan) = plans.next().await {
+///     println!("{:?}", plan?);
+/// }
+/// ```
+pub struct Paginator<'a, T> {
+    client: &'a HttpClient,
+    url: String,
+    method: Method,
+    params: Map<String, Value>,
+    headers: Option<HashMap<String, String>>,
+    /// 本次翻页的标识，与页码拼成每页的幂等键
+    session: String,
+    page_no: u64,
+    page_size: u64,
+    fetched: u64,
+    done: bool,
+    buffer: VecDeque<T>,
+}
+
+impl<'a, T: DeserializeOwned> Paginator<'a, T> {
+    pub(super) fn new<P: Serialize>(
+        client: &'a HttpClient,
+        url: &str,
+        params: &P,
+    ) -> Result<Self, Error> {
+        let params = match serde_json::to_value(params)? {
+            Value::Object(map) => map,
+            Value::Null => Map::new(),
+            x => return Err(anyhow!("Page params must be a JSON object, got {}", x)),
+        };
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            method: Method::POST,
+            params,
+            headers: None,
+            session: session_id(),
+            page_no: 1,
+            page_size: 10,
+            fetched: 0,
+            done: false,
+            buffer: VecDeque::new(),
+        })
+    }
+
+    /// 只支持 GET（参数放在查询串）和 POST（参数放在 JSON 请求体）
+    pub fn with_method(mut self, x: Method) -> Self {
+        self.method = x;
+        self
+    }
+
+    pub fn with_headers(mut self, x: HashMap<String, String>) -> Self {
+        self.headers = Some(x);
+        self
+    }
+
+    pub fn with_page_size(mut self, x: u64) -> Self {
+        self.page_size = x.max(1);
+        self
+    }
+
+    /// 起始页码，默认从 1 开始
+    pub fn with_start_page(mut self, x: u64) -> Self {
+        self.page_no = x;
+        self
+    }
+
+    /// 请求下一页，没有更多数据时返回 None；出错后不再继续请求
+    pub async fn next_page(&mut self) -> Option<Result<Page<T>, Error>> {
+        if self.done {
+            return None;
+        }
+        let page = match self.fetch().await {
+            Ok(page) => page,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+
+        let n = page.records.len() as u64;
+        self.fetched += n;
+        self.done = n == 0
+            || match (page.total, page.pages) {
+                (Some(total), _) => self.fetched >= total,
+                (None, Some(pages)) => self.page_no >= pages,
+                (None, None) => n < self.page_size,
+            };
+        self.page_no += 1;
+        Some(Ok(page))
+    }
+
+    /// 逐条返回记录，需要时请求下一页
+    pub async fn next_record(&mut self) -> Option<Result<T, Error>> {
+        loop {
+            if let Some(record) = self.buffer.pop_front() {
+                return Some(Ok(record));
+            }
+            match self.next_page().await? {
+                Ok(page) => self.buffer.extend(page.records),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// 转为逐条记录的异步流，出错后流结束
+    pub fn into_stream(self) -> impl Stream<Item = Result<T, Error>> + 'a
+    where
+        T: 'a,
+    {
+        stream::try_unfold(self, |mut pages| async move {
+            match pages.next_record().await {
+                Some(Ok(record)) => Ok(Some((record, pages))),
+                Some(Err(e)) => Err(e),
+                None => Ok(None),
+            }
+        })
+    }
+
+    /// 取出余下全部记录
+    pub async fn collect_all(mut self) -> Result<Vec<T>, Error> {
+        let mut records: Vec<T> = self.buffer.drain(..).collect();
+        while let Some(page) = self.next_page().await {
+            records.extend(page?.records);
+        }
+        Ok(records)
+    }
+
+    async fn fetch(&self) -> Result<Page<T>, Error> {
+        let mut params = self.params.clone();
+        params.insert("pageNo".to_string(), self.page_no.into());
+        params.insert("pageSize".to_string(), self.page_size.into());
+
+        let client = self.client;
+        let url = client.url(&self.url);
+        let response: ApiResponse = match self.method {
+            Method::GET => {
+                let query: Vec<(String, String)> = params
+                    .into_iter()
+                    .map(|(k, v)| match v {
+                        Value::String(s) => (k, s),
+                        v => (k, v.to_string()),
+                    })
+                    .collect();
+                client
+                    .execute(Method::GET, self.headers.clone(), || {
+                        client.client.get(&url).query(&query)
+                    })
+                    .await?
+            }
+            Method::POST => {
+                // 调用方给出的幂等键对每页都相同，会让服务端把后续页当成重复请求
+                let mut headers = self.headers.clone().unwrap_or_default();
+                headers.retain(|k, _| !k.eq_ignore_ascii_case(IDEMPOTENCY_KEY));
+                headers.insert(
+                    IDEMPOTENCY_KEY.to_string(),
+                    format!("{}-{}", self.session, self.page_no),
+                );
+                client
+                    .execute(Method::POST, Some(headers), || {
+                        client.client.post(&url).json(&params)
+                    })
+                    .await?
+            }
+            ref x => return Err(anyhow!("Unsupported page request method: {}", x)),
+        };
+        response.into_data(&client.success_codes)
+    }
+}
+
+/// 进程内唯一的翻页标识
+fn session_id() -> String {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!(
+        "page-{:x}-{}-{}",
+        nanos,
+        std::process::id(),
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    )
+}
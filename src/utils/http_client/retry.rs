@@ -0,0 +1,116 @@
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, Response, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// 幂等键请求头，POST 请求带上该头时才会重试
+pub const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// 请求重试策略
+///
+/// 第 n 次重试前等待 `base_delay × 2^(n-1)`，不超过 `max_delay`，再按 `jitter` 随机缩短；
+/// 响应带 `Retry-After` 秒数时以其为准（同样不超过 `max_delay`）。
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// 最多发送次数（含首次），1 表示不重试
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// 0 ~ 1，等待时间在 `[delay × (1 - jitter), delay]` 内均匀随机
+    pub jitter: f64,
+    /// 需要重试的响应状态码
+    pub retry_statuses: Vec<u16>,
+    /// 连接失败、连接中断和超时时重试
+    pub retry_connection_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+            jitter: 0.5,
+            retry_statuses: vec![408, 429, 500, 502, 503, 504],
+            retry_connection_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 不重试
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, x: u32) -> Self {
+        self.max_attempts = x.max(1);
+        self
+    }
+
+    pub fn with_delays(mut self, base: Duration, max: Duration) -> Self {
+        self.base_delay = base;
+        self.max_delay = max;
+        self
+    }
+
+    pub fn with_jitter(mut self, x: f64) -> Self {
+        self.jitter = x.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_retry_statuses(mut self, x: &[u16]) -> Self {
+        self.retry_statuses = x.to_vec();
+        self
+    }
+
+    pub fn with_retry_connection_errors(mut self, x: bool) -> Self {
+        self.retry_connection_errors = x;
+        self
+    }
+
+    /// 第 `retry` 次重试（从 1 开始）前的等待时间，不含抖动
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.base_delay.saturating_mul(factor).min(self.max_delay)
+    }
+
+    /// 第 `retry` 次重试前的等待时间，含抖动
+    pub fn delay(&self, retry: u32) -> Duration {
+        // 不引入随机数依赖，RandomState 每次构造的种子都不同
+        let r = RandomState::new().hash_one(retry) as f64 / u64::MAX as f64;
+        self.backoff(retry).mul_f64(1.0 - self.jitter * r)
+    }
+
+    /// GET / PUT / DELETE 等幂等方法总是可以重试，POST / PATCH 需要带上 [`IDEMPOTENCY_KEY`]
+    pub fn is_retryable(method: &Method, headers: Option<&HashMap<String, String>>) -> bool {
+        method.is_idempotent()
+            || headers.is_some_and(|h| h.keys().any(|k| k.eq_ignore_ascii_case(IDEMPOTENCY_KEY)))
+    }
+
+    pub(super) fn retries_status(&self, status: StatusCode) -> bool {
+        self.retry_statuses.contains(&status.as_u16())
+    }
+
+    pub(super) fn retries_error(&self, e: &reqwest::Error) -> bool {
+        self.retry_connection_errors && (e.is_connect() || e.is_timeout() || e.is_request())
+    }
+
+    /// `Retry-After` 只支持秒数形式
+    pub(super) fn retry_after(&self, response: &Response) -> Option<Duration> {
+        let secs = response
+            .headers()
+            .get(RETRY_AFTER)?
+            .to_str()
+            .ok()?
+            .trim()
+            .parse::<u64>()
+            .ok()?;
+        Some(Duration::from_secs(secs).min(self.max_delay))
+    }
+}
@@ -1,5 +1,7 @@
 use opencv::core::Rect;
 
+use super::geometry::BoxF;
+
 /// 计算两个矩形的交并比
 pub fn calculate_iou(a: &Rect, b: &Rect) -> f32 {
     // 计算两个矩形的交集
@@ -31,3 +33,72 @@ pub fn calculate_iou(a: &Rect, b: &Rect) -> f32 {
     // 计算 IoU
     intersection_area as f32 / union_area as f32
 }
+
+/// 计算两个 xyxy 格式浮点框的交并比
+pub fn calculate_iou_xyxy(a: &[f32; 4], b: &[f32; 4]) -> f32 {
+    BoxF::from_xyxy(*a).iou(&BoxF::from_xyxy(*b))
+}
+
+/// 计算预测框与真值框两两之间的 IoU 矩阵，形状为 preds.len() x gts.len()
+pub fn iou_matrix(preds: &[[f32; 4]], gts: &[[f32; 4]]) -> Vec<Vec<f32>> {
+    preds
+        .iter()
+        .map(|p| gts.iter().map(|g| calculate_iou_xyxy(p, g)).collect())
+        .collect()
+}
+
+/// 按 COCO 规则贪心匹配预测框与真值框
+///
+/// `preds` 需已按置信度降序排列；每个预测框依次匹配 IoU 最大且尚未被占用、
+/// 并且 IoU 不低于 `iou_threshold` 的真值框。返回每个预测框匹配到的真值索引。
+pub fn greedy_match(ious: &[Vec<f32>], num_gts: usize, iou_threshold: f32) -> Vec<Option<usize>> {
+    let mut taken = vec![false; num_gts];
+    ious.iter()
+        .map(|row| {
+            let mut best: Option<(usize, f32)> = None;
+            for (j, &iou) in row.iter().enumerate() {
+                if taken[j] || iou < iou_threshold {
+                    continue;
+                }
+                if best.is_none_or(|(_, b)| iou > b) {
+                    best = Some((j, iou));
+                }
+            }
+            best.map(|(j, _)| {
+                taken[j] = true;
+                j
+            })
+        })
+        .collect()
+}
+
+/// 不区分顺序的一对一匹配：所有 IoU 不低于阈值的候选对按 IoU 降序贪心配对
+///
+/// 用于混淆矩阵统计，返回 (预测索引, 真值索引) 列表。
+pub fn match_pairs_by_iou(ious: &[Vec<f32>], iou_threshold: f32) -> Vec<(usize, usize)> {
+    let mut candidates: Vec<(usize, usize, f32)> = ious
+        .iter()
+        .enumerate()
+        .flat_map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .filter(|(_, &iou)| iou >= iou_threshold)
+                .map(move |(j, &iou)| (i, j, iou))
+        })
+        .collect();
+    candidates.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+
+    let num_gts = ious.first().map_or(0, |row| row.len());
+    let mut pred_taken = vec![false; ious.len()];
+    let mut gt_taken = vec![false; num_gts];
+    let mut pairs = Vec::new();
+    for (i, j, _) in candidates {
+        if pred_taken[i] || gt_taken[j] {
+            continue;
+        }
+        pred_taken[i] = true;
+        gt_taken[j] = true;
+        pairs.push((i, j));
+    }
+    pairs
+}
@@ -1,2 +1,6 @@
+pub mod color;
+pub mod crop;
+pub mod cv;
+pub mod geometry;
 pub mod http_client;
 pub mod math;
@@ -0,0 +1,273 @@
+use image::{DynamicImage, GrayImage, Luma, RgbImage};
+use usls::{Bbox, Keypoint, Mask, Y};
+use yolo_vision::annotation::{
+    AnnotationStyle, Annotator, DisplayNames, FontChain, LabelFields, LabelTemplate,
+    DEFAULT_FALLBACK_FONTS, PALETTE,
+};
+
+fn blank(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::new(width, height))
+}
+
+fn person() -> Bbox {
+    Bbox::default()
+        .with_xyxy(20.0, 30.0, 60.0, 90.0)
+        .with_confidence(0.876)
+        .with_id(0)
+        .with_name("person")
+}
+
+#[test]
+fn label_template_rendering() {
+    let t = LabelTemplate::parse("{name} {conf:.2} #{track_id}").unwrap();
+    let mut fields = LabelFields {
+        name: Some("person"),
+        class_id: 0,
+        confidence: 0.876,
+        track_id: Some(12),
+    };
+    assert_eq!(t.render(&fields), "person 0.88 #12");
+
+    // 没有取值的占位符所在的词被省略
+    fields.track_id = None;
+    assert_eq!(t.render(&fields), "person 0.88");
+    fields.name = None;
+    assert_eq!(t.render(&fields), "0.88");
+
+    let t = LabelTemplate::parse("cls={id} {conf:.1}%").unwrap();
+    assert_eq!(t.render(&fields), "cls=0 0.9%");
+    assert_eq!(
+        LabelTemplate::parse("{conf}").unwrap().render(&fields),
+        "0.88"
+    );
+
+    assert!(LabelTemplate::parse("{name").is_err());
+    assert!(LabelTemplate::parse("{score}").is_err());
+    assert!(LabelTemplate::parse("{conf:2}").is_err());
+}
+
+#[test]
+fn style_from_json() {
+    let style: AnnotationStyle = serde_json::from_str(
+        r#"{
+            "font_size": 16,
+            "label_template": "{name} #{track_id}",
+            "class_colors": {"person": [1, 2, 3], "2": [4, 5, 6]},
+            "skeleton": [[0, 1], [1, 2]]
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(style.font, "fonts/Arial.ttf");
+    assert_eq!(style.bbox_thickness, 3);
+    assert_eq!(style.color(0, Some("person")), [1, 2, 3]);
+    assert_eq!(style.color(2, Some("car")), [4, 5, 6]);
+    assert_eq!(style.color(3, None), PALETTE[3]);
+    assert_eq!(style.skeleton(), &[[0, 1], [1, 2]]);
+    assert_eq!(AnnotationStyle::default().skeleton().len(), 16);
+}
+
+#[test]
+fn bbox_colour_and_thickness() {
+    let mut style = AnnotationStyle {
+        labels: false,
+        bbox_thickness: 2,
+        ..Default::default()
+    };
+    style.class_colors.insert("person".to_string(), [255, 0, 0]);
+    let annotator = Annotator::new(style).unwrap();
+
+    let y = Y::default().with_bboxes(&[person()]);
+    let out = annotator.plot(&[blank(100, 120)], &[y]).unwrap()[0].to_rgb8();
+    assert_eq!(out.get_pixel(20, 50).0, [255, 0, 0]);
+    assert_eq!(out.get_pixel(21, 50).0, [255, 0, 0]);
+    assert_eq!(out.get_pixel(22, 50).0, [0, 0, 0]);
+    assert_eq!(out.get_pixel(40, 60).0, [0, 0, 0]);
+}
+
+#[test]
+fn labels_drawn_above_box_with_track_id() {
+    let annotator = Annotator::new(AnnotationStyle {
+        label_template: "{name} #{track_id}".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(annotator.label(&person(), Some(7)), "person #7");
+    assert_eq!(annotator.label(&person(), None), "person");
+
+    let y = Y::default().with_bboxes(&[person()]);
+    let out = annotator
+        .plot_with_tracks(
+            &[blank(100, 120)],
+            std::slice::from_ref(&y),
+            &[vec![Some(7)]],
+        )
+        .unwrap()[0]
+        .to_rgb8();
+    // 标签背景使用类别颜色
+    assert_eq!(out.get_pixel(21, 9).0, PALETTE[0]);
+
+    // 原地绘制与拷贝绘制结果一致
+    let annotated = annotator.annotate(vec![blank(100, 120)], &[y], &[vec![Some(7)]]);
+    assert_eq!(annotated[0].to_rgb8(), out);
+}
+
+#[test]
+fn mask_alpha_and_contours() {
+    let mut mask = GrayImage::new(50, 50);
+    for y in 10..30 {
+        for x in 10..30 {
+            mask.put_pixel(x, y, Luma([255]));
+        }
+    }
+    let y = Y::default().with_masks(&[Mask::default().with_mask(mask).with_id(1)]);
+    let style = AnnotationStyle {
+        masks: true,
+        mask_alpha: 0.5,
+        ..Default::default()
+    };
+
+    let out = Annotator::new(style.clone())
+        .unwrap()
+        .plot(&[blank(50, 50)], std::slice::from_ref(&y))
+        .unwrap()[0]
+        .to_rgb8();
+    let c = PALETTE[1];
+    assert_eq!(out.get_pixel(10, 20).0, c);
+    let half = c.map(|v| (v as f32 * 0.5).round() as u8);
+    assert_eq!(out.get_pixel(20, 20).0, half);
+    assert_eq!(out.get_pixel(5, 5).0, [0, 0, 0]);
+
+    let out = Annotator::new(AnnotationStyle {
+        contours: false,
+        masks: false,
+        ..style
+    })
+    .unwrap()
+    .plot(&[blank(50, 50)], &[y])
+    .unwrap()[0]
+        .to_rgb8();
+    assert!(out.pixels().all(|p| p.0 == [0, 0, 0]));
+}
+
+#[test]
+fn custom_skeleton_and_toggles() {
+    let kpts = vec![
+        Keypoint::default().with_xy(10.0, 10.0).with_confidence(0.9),
+        Keypoint::default().with_xy(40.0, 10.0).with_confidence(0.9),
+        Keypoint::default().with_xy(40.0, 40.0).with_confidence(0.1),
+    ];
+    let y = Y::default().with_keypoints(&[kpts]);
+    let style = AnnotationStyle {
+        keypoints: false,
+        skeleton: Some(vec![[0, 1], [1, 2]]),
+        ..Default::default()
+    };
+    let out = Annotator::new(style.clone())
+        .unwrap()
+        .plot(&[blank(50, 50)], std::slice::from_ref(&y))
+        .unwrap()[0]
+        .to_rgb8();
+    // 0-1 连线被绘制，1-2 因关键点 2 置信度低被跳过
+    assert_eq!(out.get_pixel(25, 10).0, PALETTE[0]);
+    assert_eq!(out.get_pixel(40, 25).0, [0, 0, 0]);
+
+    let out = Annotator::new(AnnotationStyle {
+        skeletons: false,
+        keypoints: true,
+        ..style
+    })
+    .unwrap()
+    .plot(&[blank(50, 50)], &[y])
+    .unwrap()[0]
+        .to_rgb8();
+    assert_eq!(out.get_pixel(25, 10).0, [0, 0, 0]);
+    assert_eq!(out.get_pixel(10, 10).0, PALETTE[0]);
+    assert_eq!(out.get_pixel(40, 10).0, PALETTE[1]);
+}
+
+#[test]
+fn skeleton_thickness_from_style() {
+    let kpts = vec![
+        Keypoint::default().with_xy(10.0, 20.0).with_confidence(0.9),
+        Keypoint::default().with_xy(40.0, 20.0).with_confidence(0.9),
+    ];
+    let y = Y::default().with_keypoints(&[kpts]);
+    let style = AnnotationStyle {
+        keypoints: false,
+        skeleton: Some(vec![[0, 1]]),
+        skeleton_thickness: 4,
+        ..Default::default()
+    };
+    let out = Annotator::new(style)
+        .unwrap()
+        .plot(&[blank(50, 50)], &[y])
+        .unwrap()[0]
+        .to_rgb8();
+    let drawn: Vec<u32> = (0..50)
+        .filter(|&y| out.get_pixel(25, y).0 == PALETTE[0])
+        .collect();
+    assert_eq!(drawn, vec![19, 20, 21, 22]);
+}
+
+#[test]
+fn display_names_from_translation_file() {
+    let path = "tests/fixtures/annotation/names.json";
+    let names = DisplayNames::from_file(path, "zh-CN").unwrap();
+    assert_eq!(names.get(0), Some("行人"));
+    assert_eq!(names.get(2), Some("汽车"));
+    assert_eq!(names.get(1), None);
+    assert!(DisplayNames::from_file(path, "fr").is_err());
+    assert!(DisplayNames::from_json(r#"{"en": {"person": "x"}}"#, "en").is_err());
+
+    // 未翻译的类别回退到模型给出的类别名
+    let annotator = Annotator::new(AnnotationStyle {
+        translations: Some(path.to_string()),
+        locale: "en".to_string(),
+        label_template: "{name}".to_string(),
+        ..Default::default()
+    })
+    .unwrap();
+    assert_eq!(annotator.label(&person(), None), "pedestrian");
+    let car = Bbox::default().with_id(2).with_name("car");
+    assert_eq!(annotator.label(&car, None), "car");
+}
+
+#[test]
+fn font_chain_reports_missing_glyphs() {
+    let chain = FontChain::load("fonts/Arial.ttf", &["fonts/missing.ttf".to_string()]).unwrap();
+    assert_eq!(chain.len(), 1);
+    assert_eq!(chain.font_for('A'), Some(0));
+    assert_eq!(chain.missing("行人 person 行"), vec!['行', '人']);
+    assert!(FontChain::load("fonts/missing.ttf", &[]).is_err());
+
+    let scale = ab_glyph::PxScale::from(20.0);
+    let (w, h) = chain.text_size(scale, "person");
+    assert!(w > 0 && h >= 20);
+    let mut img = RgbImage::new(w + 4, h + 4);
+    chain.draw_text(&mut img, image::Rgb([255, 255, 255]), 2, 2, scale, "person");
+    assert!(img.pixels().any(|p| p.0 != [0, 0, 0]));
+}
+
+#[test]
+#[ignore = "needs the OFL font fonts/NotoSansSC-Regular.ttf to be checked in"]
+fn cjk_fallback_renders_bundled_font() {
+    assert_eq!(DEFAULT_FALLBACK_FONTS[0], "fonts/NotoSansSC-Regular.ttf");
+    let chain =
+        FontChain::load("fonts/Arial.ttf", &[DEFAULT_FALLBACK_FONTS[0].to_string()]).unwrap();
+    assert_eq!(chain.len(), 2, "bundled CJK font not loaded");
+    assert_eq!(chain.font_for('人'), Some(1));
+    assert_eq!(chain.font_for('p'), Some(0));
+    assert!(chain.missing("行人 person 0.88").is_empty());
+
+    let scale = ab_glyph::PxScale::from(20.0);
+    let (latin, _) = chain.text_size(scale, "person");
+    let (mixed, _) = chain.text_size(scale, "person 行人");
+    assert!(mixed > latin);
+
+    // 中文字形实际绘制出像素
+    let (w, h) = chain.text_size(scale, "行人");
+    let mut img = RgbImage::new(w + 4, h + 4);
+    chain.draw_text(&mut img, image::Rgb([255, 255, 255]), 2, 2, scale, "行人");
+    let lit = img.pixels().filter(|p| p.0 != [0, 0, 0]).count();
+    assert!(lit > 20, "{} pixels drawn", lit);
+}
@@ -0,0 +1,227 @@
+use image::{DynamicImage, RgbImage};
+use opencv::prelude::*;
+use yolo_vision::misc::av_convert::{
+    alloc_frame, avframe_rgb24_as_image, avframe_rgb24_as_mat, avframe_to_image, avframe_to_mat,
+    frame_format, image_to_avframe, mat_to_avframe, FrameConverter, PixelFormat, RgbConverter,
+};
+use yolo_vision::misc::avio::{decode_frames, FrameReader};
+use yolo_vision::utils::cv::mat_to_rgb_image;
+
+const VIDEO: &str = "assets/test.mp4";
+
+fn max_diff(a: &RgbImage, b: &RgbImage) -> u8 {
+    assert_eq!(a.dimensions(), b.dimensions());
+    a.as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| x.abs_diff(y))
+        .max()
+        .unwrap_or(0)
+}
+
+fn mean_diff(a: &RgbImage, b: &RgbImage) -> f64 {
+    let sum: u64 = a
+        .as_raw()
+        .iter()
+        .zip(b.as_raw())
+        .map(|(&x, &y)| x.abs_diff(y) as u64)
+        .sum();
+    sum as f64 / a.as_raw().len() as f64
+}
+
+/// 平面每行末尾是否有对齐填充
+fn is_padded(frame: &rsmpeg::avutil::AVFrame, plane: usize, row_bytes: i32) -> bool {
+    frame.linesize[plane] > row_bytes
+}
+
+#[test]
+fn decoded_frames_convert_to_mat_and_image() {
+    let frames = decode_frames(VIDEO, 5).unwrap();
+    assert!(!frames.is_empty());
+    for frame in &frames {
+        assert_eq!(frame_format(frame).unwrap(), PixelFormat::Yuv420p);
+        let mat = avframe_to_mat(frame).unwrap();
+        assert_eq!((mat.cols(), mat.rows()), (frame.width, frame.height));
+        assert_eq!(mat.channels(), 3);
+
+        let image = avframe_to_image(frame).unwrap().to_rgb8();
+        assert_eq!(image, mat_to_rgb_image(&mat).unwrap());
+    }
+}
+
+#[test]
+fn yuv420p_round_trip_through_image() {
+    for frame in decode_frames(VIDEO, 3).unwrap() {
+        let image = avframe_to_image(&frame).unwrap();
+        let back = image_to_avframe(&image, PixelFormat::Yuv420p).unwrap();
+        assert_eq!((back.width, back.height), (frame.width, frame.height));
+
+        // YUV -> RGB -> YUV 只有舍入误差
+        let again = avframe_to_image(&back).unwrap().to_rgb8();
+        let image = image.to_rgb8();
+        assert!(
+            max_diff(&image, &again) <= 8,
+            "{}",
+            max_diff(&image, &again)
+        );
+        assert!(mean_diff(&image, &again) < 1.5);
+    }
+}
+
+#[test]
+fn nv12_matches_yuv420p() {
+    for frame in decode_frames(VIDEO, 3).unwrap() {
+        let rgb = avframe_to_mat(&frame).unwrap();
+        let nv12 = mat_to_avframe(&rgb, PixelFormat::Nv12).unwrap();
+        let i420 = mat_to_avframe(&rgb, PixelFormat::Yuv420p).unwrap();
+        assert_eq!(frame_format(&nv12).unwrap(), PixelFormat::Nv12);
+
+        // 两种布局的色度数据相同，转回 RGB 应完全一致
+        let a = avframe_to_image(&nv12).unwrap().to_rgb8();
+        let b = avframe_to_image(&i420).unwrap().to_rgb8();
+        assert_eq!(a, b);
+        assert!(mean_diff(&a, &mat_to_rgb_image(&rgb).unwrap()) < 1.5);
+    }
+}
+
+#[test]
+fn rgb24_round_trip_is_lossless() {
+    let frame = decode_frames(VIDEO, 1).unwrap().remove(0);
+    let image = avframe_to_image(&frame).unwrap();
+    let rgb = image_to_avframe(&image, PixelFormat::Rgb24).unwrap();
+
+    assert_eq!(avframe_to_image(&rgb).unwrap(), image);
+    let mat = avframe_rgb24_as_mat(&rgb).unwrap();
+    assert_eq!(mat_to_rgb_image(&mat).unwrap(), image.to_rgb8());
+    assert_eq!(
+        mat_to_rgb_image(&avframe_to_mat(&rgb).unwrap()).unwrap(),
+        image.to_rgb8()
+    );
+}
+
+#[test]
+fn padded_linesize_is_respected() {
+    // 宽 330 时 FFmpeg 按对齐要求填充每行，行跨度大于有效字节数
+    let image = DynamicImage::ImageRgb8(RgbImage::from_fn(330, 186, |x, y| {
+        image::Rgb([(x % 256) as u8, (y % 256) as u8, ((x * 3 + y) % 256) as u8])
+    }));
+
+    let rgb = image_to_avframe(&image, PixelFormat::Rgb24).unwrap();
+    assert!(is_padded(&rgb, 0, 330 * 3));
+    // 行跨度有填充时无法零拷贝借用为图像，但 Mat 视图可以带跨度
+    assert!(avframe_rgb24_as_image(&rgb).is_none());
+    let view = avframe_rgb24_as_mat(&rgb).unwrap();
+    assert_eq!(view.step1_def().unwrap(), rgb.linesize[0] as usize);
+    assert_eq!(avframe_to_image(&rgb).unwrap(), image);
+
+    let yuv = image_to_avframe(&image, PixelFormat::Yuv420p).unwrap();
+    assert!(is_padded(&yuv, 0, 330));
+    let back = avframe_to_image(&yuv).unwrap().to_rgb8();
+    assert!(mean_diff(&back, &image.to_rgb8()) < 2.0);
+}
+
+#[test]
+fn tight_rgb24_frame_is_borrowed() {
+    // 宽 64 时每行 192 字节，无需填充
+    let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(64, 32, image::Rgb([10, 20, 30])));
+    let frame = image_to_avframe(&image, PixelFormat::Rgb24).unwrap();
+    let view = avframe_rgb24_as_image(&frame).unwrap();
+    assert_eq!(view.as_raw().as_ptr(), frame.data[0] as *const u8);
+    assert_eq!(view.get_pixel(5, 5).0, [10, 20, 30]);
+}
+
+#[test]
+fn odd_sizes_are_rejected_for_yuv() {
+    assert!(alloc_frame(31, 20, PixelFormat::Yuv420p).is_err());
+    assert!(alloc_frame(31, 20, PixelFormat::Rgb24).is_ok());
+}
+
+#[test]
+fn frame_converter_scales_and_reuses_frame() {
+    let frame = decode_frames(VIDEO, 1).unwrap().remove(0);
+    let image = avframe_to_image(&frame).unwrap().to_rgb8();
+    let (w, h) = (
+        image.width() as i32 / 2 & !1,
+        image.height() as i32 / 2 & !1,
+    );
+
+    let mut converter = FrameConverter::new(w, h, PixelFormat::Yuv420p);
+    let first = converter.convert(&image).unwrap();
+    assert_eq!((first.width, first.height), (w, h));
+    assert_eq!(frame_format(first).unwrap(), PixelFormat::Yuv420p);
+    let ptr = first.data[0];
+
+    let out = converter.convert(&image).unwrap();
+    assert_eq!(out.data[0], ptr);
+
+    // 与先缩放再用 OpenCV 转换的结果接近
+    let expected = image::imageops::resize(
+        &image,
+        w as u32,
+        h as u32,
+        image::imageops::FilterType::Triangle,
+    );
+    let actual = avframe_to_image(out).unwrap().to_rgb8();
+    assert!(mean_diff(&actual, &expected) < 6.0);
+}
+
+#[test]
+fn frame_converter_converts_native_frames() {
+    // 解码器原生帧直接缩放转换，与先转 RGB 再转换的结果接近
+    let frame = decode_frames(VIDEO, 1).unwrap().remove(0);
+    let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+    let direct = avframe_to_image(converter.convert_frame(&frame).unwrap())
+        .unwrap()
+        .to_rgb8();
+    let rgb = RgbConverter::default().convert(&frame).unwrap();
+    assert_eq!(
+        (rgb.width(), rgb.height()),
+        (frame.width as u32, frame.height as u32)
+    );
+    let via_rgb = avframe_to_image(converter.convert(&rgb.to_rgb8()).unwrap())
+        .unwrap()
+        .to_rgb8();
+    assert_eq!(direct.dimensions(), (1280, 720));
+    assert!(mean_diff(&direct, &via_rgb) < 3.0);
+}
+
+#[test]
+fn frame_reader_yields_batches_of_native_frames() {
+    let expected = decode_frames(VIDEO, usize::MAX).unwrap();
+    let batches: Vec<_> = FrameReader::new(VIDEO).unwrap().with_batch(4).collect();
+    assert!(batches[..batches.len() - 1].iter().all(|b| b.len() == 4));
+    assert_eq!(
+        batches.iter().map(|b| b.len()).sum::<usize>(),
+        expected.len()
+    );
+    let first = &batches[0][0];
+    assert_eq!(first.format, expected[0].format);
+    assert_eq!(
+        (first.width, first.height),
+        (expected[0].width, expected[0].height)
+    );
+}
+
+#[test]
+fn encoder_accepts_converted_yuv420p_720p() {
+    // 与 main.rs 的编码路径一致：FrameConverter 输出 1280x720 YUV420P，直接交给 encode_raw
+    let path = std::env::temp_dir().join(format!("encode-yuv420p-{}.mp4", std::process::id()));
+    let mut encoder = rsmedia::EncoderBuilder::new(&path, 1280, 720)
+        .with_codec_name("libx264".to_string())
+        .build()
+        .unwrap();
+    let mut converter = FrameConverter::new(1280, 720, PixelFormat::Yuv420p);
+    let frames = decode_frames(VIDEO, 10).unwrap();
+    for frame in &frames {
+        let rgb = avframe_to_image(frame).unwrap().to_rgb8();
+        let yuv = converter.convert(&rgb).unwrap();
+        assert_eq!(frame_format(yuv).unwrap(), PixelFormat::Yuv420p);
+        encoder.encode_raw(yuv).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let encoded = decode_frames(path.to_str().unwrap(), frames.len()).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(encoded.len(), frames.len());
+    assert!(encoded.iter().all(|f| (f.width, f.height) == (1280, 720)));
+}
@@ -0,0 +1,270 @@
+use yolo_vision::backend::{decode, nms_raw, HeadLayout, LayoutHint, MaskProtos, OutputLayout};
+use yolo_vision::postprocess::NmsConfig;
+use yolo_vision::utils::geometry::BoxF;
+
+const TOL: f32 = 1e-4;
+
+/// 按布局把每个候选框的通道拼成输出张量
+fn tensor(rows: &[Vec<f32>], transposed: bool) -> Vec<f32> {
+    if transposed {
+        (0..rows[0].len())
+            .flat_map(|c| rows.iter().map(move |r| r[c]))
+            .collect()
+    } else {
+        rows.iter().flatten().copied().collect()
+    }
+}
+
+#[test]
+fn decode_v5_uses_objectness() {
+    let layout = HeadLayout::new(OutputLayout::V5, 2);
+    let data = tensor(
+        &[
+            vec![100.0, 100.0, 20.0, 40.0, 0.9, 0.1, 0.8],
+            // 类别分数高但 objectness 低
+            vec![200.0, 200.0, 10.0, 10.0, 0.2, 0.9, 0.0],
+        ],
+        false,
+    );
+    let dets = decode(&layout, &data, 0.25).unwrap();
+    assert_eq!(dets.len(), 1);
+    let d = &dets[0].detection;
+    assert_eq!(d.class_id, 1);
+    assert!((d.score - 0.72).abs() < TOL);
+    assert_eq!(d.bbox, BoxF::new(90.0, 80.0, 110.0, 120.0));
+    assert!(dets[0].mask_coefs.is_empty() && dets[0].keypoints.is_empty());
+}
+
+#[test]
+fn decode_v8_in_both_orientations() {
+    let rows = [
+        vec![50.0, 60.0, 10.0, 20.0, 0.3, 0.6],
+        vec![10.0, 10.0, 4.0, 4.0, 0.1, 0.05],
+        vec![300.0, 200.0, 100.0, 50.0, 0.95, 0.2],
+    ];
+    let layout = HeadLayout::new(OutputLayout::V8, 2);
+    let dets = decode(&layout, &tensor(&rows, true), 0.25).unwrap();
+    assert_eq!(dets.len(), 2);
+    assert_eq!(dets[0].detection.class_id, 1);
+    assert_eq!(dets[0].detection.bbox, BoxF::new(45.0, 50.0, 55.0, 70.0));
+    assert_eq!(dets[1].detection.class_id, 0);
+    assert!((dets[1].detection.score - 0.95).abs() < TOL);
+
+    let untransposed = layout.with_transposed(false);
+    assert_eq!(
+        decode(&untransposed, &tensor(&rows, false), 0.25).unwrap(),
+        dets
+    );
+
+    assert!(decode(&layout, &[0.0; 10], 0.25).is_err());
+    assert!(decode(&layout, &[], 0.25).is_err());
+}
+
+#[test]
+fn decode_mask_coefficients_and_keypoints() {
+    // v8 姿态 + 分割：框 4、类别 1、掩码系数 2、关键点 2 × 3
+    let layout = HeadLayout::new(OutputLayout::V8, 1)
+        .with_masks(2)
+        .with_keypoints(2);
+    assert_eq!(layout.channels(), 13);
+    let rows = [
+        vec![
+            4.0, 4.0, 8.0, 8.0, 0.9, 1.0, 0.0, 1.0, 2.0, 0.8, 3.0, 4.0, 0.1,
+        ],
+        vec![
+            4.0, 4.0, 8.0, 8.0, 0.1, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+        ],
+    ];
+    for transposed in [true, false] {
+        let dets = decode(
+            &layout.with_transposed(transposed),
+            &tensor(&rows, transposed),
+            0.25,
+        )
+        .unwrap();
+        assert_eq!(dets.len(), 1);
+        assert_eq!(dets[0].mask_coefs, vec![1.0, 0.0]);
+        assert_eq!(dets[0].keypoints, vec![[1.0, 2.0, 0.8], [3.0, 4.0, 0.1]]);
+    }
+}
+
+#[test]
+fn mask_from_prototypes() {
+    // 原型 0 左半为正，原型 1 上半为正
+    let (w, h) = (4, 4);
+    let mut data = vec![0.0; 2 * w * h];
+    for y in 0..h {
+        for x in 0..w {
+            data[y * w + x] = if x < 2 { 10.0 } else { -10.0 };
+            data[w * h + y * w + x] = if y < 2 { 10.0 } else { -10.0 };
+        }
+    }
+    let protos = MaskProtos::new(&data, [2, h, w]).unwrap();
+    assert_eq!(protos.num_masks(), 2);
+    assert!(MaskProtos::new(&data, [3, h, w]).is_err());
+
+    let full = BoxF::new(0.0, 0.0, 8.0, 8.0);
+    let left = protos.mask(&[1.0, 0.0], &full, (8, 8));
+    assert_eq!(left.dimensions(), (8, 8));
+    for (x, _, p) in left.enumerate_pixels() {
+        assert_eq!(p[0], if x < 4 { 255 } else { 0 });
+    }
+
+    // 框外置零
+    let cropped = protos.mask(&[0.0, 1.0], &BoxF::new(0.0, 0.0, 2.0, 8.0), (8, 8));
+    for (x, y, p) in cropped.enumerate_pixels() {
+        assert_eq!(p[0], if x < 2 && y < 4 { 255 } else { 0 });
+    }
+}
+
+#[test]
+fn nms_keeps_extras_aligned() {
+    let layout = HeadLayout::new(OutputLayout::V8, 1).with_keypoints(1);
+    let rows = [
+        vec![10.0, 10.0, 10.0, 10.0, 0.6, 1.0, 1.0, 0.5],
+        vec![10.5, 10.0, 10.0, 10.0, 0.9, 2.0, 2.0, 0.5],
+        vec![50.0, 50.0, 10.0, 10.0, 0.7, 3.0, 3.0, 0.5],
+    ];
+    let raws = decode(&layout, &tensor(&rows, true), 0.25).unwrap();
+    let kept = nms_raw(&raws, &NmsConfig::default());
+    assert_eq!(kept.len(), 2);
+    assert!((kept[0].detection.score - 0.9).abs() < TOL);
+    assert_eq!(kept[0].keypoints, vec![[2.0, 2.0, 0.5]]);
+    assert_eq!(kept[1].keypoints, vec![[3.0, 3.0, 0.5]]);
+    assert!(kept.iter().all(|r| r.detection.source == 0));
+}
+
+#[test]
+fn detect_layout_from_shapes() {
+    let detect = |output: &[usize], protos: Option<&[usize]>, hint: LayoutHint| {
+        HeadLayout::detect(output, protos, hint)
+    };
+    let none = LayoutHint::default();
+    // 普通检测、分割模型：没有关键点
+    let det = LayoutHint {
+        num_keypoints: Some(0),
+        ..none
+    };
+
+    assert_eq!(
+        detect(&[1, 84, 8400], None, det).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 80)
+    );
+    assert_eq!(
+        detect(&[1, 25200, 85], None, det).unwrap(),
+        HeadLayout::new(OutputLayout::V5, 80)
+    );
+
+    // 非常规导出：v8 输出转置为 [候选数, 通道]，需要类别数区分
+    let nc80 = LayoutHint {
+        num_classes: Some(80),
+        ..none
+    };
+    assert_eq!(
+        detect(&[1, 84, 8400], None, nc80).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 80)
+    );
+    assert_eq!(
+        detect(&[1, 8400, 84], None, nc80).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 80).with_transposed(false)
+    );
+    assert_eq!(
+        detect(&[1, 8400, 85], None, nc80).unwrap(),
+        HeadLayout::new(OutputLayout::V5, 80)
+    );
+
+    // 分割模型
+    assert_eq!(
+        detect(&[1, 116, 8400], Some(&[1, 32, 160, 160]), det).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 80).with_masks(32)
+    );
+    assert_eq!(
+        detect(&[1, 25200, 117], Some(&[1, 32, 160, 160]), nc80).unwrap(),
+        HeadLayout::new(OutputLayout::V5, 80).with_masks(32)
+    );
+
+    // 姿态模型：已知关键点数或类别数均可推算另一个
+    let pose = LayoutHint {
+        num_keypoints: Some(17),
+        ..none
+    };
+    assert_eq!(
+        detect(&[1, 56, 8400], None, pose).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 1).with_keypoints(17)
+    );
+    let nc1 = LayoutHint {
+        num_classes: Some(1),
+        ..none
+    };
+    assert_eq!(
+        detect(&[1, 56, 8400], None, nc1).unwrap(),
+        HeadLayout::new(OutputLayout::V8, 1).with_keypoints(17)
+    );
+
+    let v5 = LayoutHint {
+        family: Some(OutputLayout::V5),
+        ..det
+    };
+    assert_eq!(
+        detect(&[84, 8400], None, v5).unwrap(),
+        HeadLayout::new(OutputLayout::V5, 79).with_transposed(true)
+    );
+
+    // 类别数和关键点数都未知时，[1, 56, 8400] 既可能是 52 类检测也可能是单类姿态
+    assert!(detect(&[1, 56, 8400], None, none).is_err());
+    assert!(detect(&[1, 84, 8400], None, none).is_err());
+
+    assert!(detect(&[1, 4, 4], None, det).is_err());
+    assert!(detect(&[1, 100, 100], None, det).is_err());
+    assert!(detect(
+        &[1, 84, 8400],
+        None,
+        LayoutHint {
+            num_classes: Some(3),
+            ..none
+        }
+    )
+    .is_err());
+    assert!(detect(&[1, 116, 8400], Some(&[32, 160]), det).is_err());
+
+    assert_eq!(OutputLayout::from_version(5.0), OutputLayout::V5);
+    assert_eq!(OutputLayout::from_version(11.0), OutputLayout::V8);
+}
+
+#[test]
+fn decode_v8_pose_tensor() {
+    // 合成 v8-pose 输出 [1, 56, 8400]：框 4、类别 1、关键点 17 × 3，只有候选 100 置信度高
+    let (channels, anchors) = (56, 8400);
+    let mut data = vec![0.0; channels * anchors];
+    let mut set = |c: usize, v: f32| data[c * anchors + 100] = v;
+    for (c, v) in [320.0, 240.0, 40.0, 120.0, 0.9].into_iter().enumerate() {
+        set(c, v);
+    }
+    for k in 0..17 {
+        set(5 + 3 * k, 300.0 + k as f32);
+        set(5 + 3 * k + 1, 200.0 + 2.0 * k as f32);
+        set(5 + 3 * k + 2, 0.5);
+    }
+
+    let layout = HeadLayout::detect(
+        &[1, channels, anchors],
+        None,
+        LayoutHint {
+            num_classes: Some(1),
+            ..LayoutHint::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(layout.num_keypoints, 17);
+
+    let dets = decode(&layout, &data, 0.25).unwrap();
+    assert_eq!(dets.len(), 1);
+    assert_eq!(dets[0].detection.class_id, 0);
+    assert!((dets[0].detection.score - 0.9).abs() < TOL);
+    assert_eq!(
+        dets[0].detection.bbox,
+        BoxF::new(300.0, 180.0, 340.0, 300.0)
+    );
+    assert_eq!(dets[0].keypoints.len(), 17);
+    assert_eq!(dets[0].keypoints[16], [316.0, 232.0, 0.5]);
+}
@@ -0,0 +1,240 @@
+use image::RgbImage;
+use opencv::core::{Mat, Scalar, CV_8UC1};
+use opencv::{imgproc, prelude::*};
+use yolo_vision::utils::color::{Backend, ColorSpace, YuvConverter};
+use yolo_vision::utils::cv::{mat_to_rgb_image, rgb_image_to_mat};
+
+const SPACES: [ColorSpace; 4] = [
+    ColorSpace::BT601_LIMITED,
+    ColorSpace::BT601_FULL,
+    ColorSpace::BT709_LIMITED,
+    ColorSpace::BT709_FULL,
+];
+
+/// 伪随机像素，宽度不是 16 的倍数以覆盖向量化内核的尾部
+fn noise(width: u32, height: u32) -> RgbImage {
+    let mut state = 7u32;
+    RgbImage::from_fn(width, height, |_, _| {
+        let mut next = || {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        };
+        image::Rgb([next(), next(), next()])
+    })
+}
+
+/// 每个 2x2 块颜色相同的图像，色度下采样不丢信息
+fn blocks(width: u32, height: u32) -> RgbImage {
+    let src = noise(width / 2, height / 2);
+    RgbImage::from_fn(width, height, |x, y| *src.get_pixel(x / 2, y / 2))
+}
+
+fn max_diff(a: &[u8], b: &[u8]) -> u8 {
+    assert_eq!(a.len(), b.len());
+    a.iter().zip(b).map(|(x, y)| x.abs_diff(*y)).max().unwrap()
+}
+
+#[test]
+fn scalar_and_simd_are_identical() {
+    let (w, h) = (70, 24);
+    let rgb = noise(w, h).into_raw();
+    let (w, h) = (w as usize, h as usize);
+    for space in SPACES {
+        let scalar = YuvConverter::new(space).with_backend(Backend::Scalar);
+        let simd = YuvConverter::new(space).with_backend(Backend::Simd);
+
+        let i420 = scalar.rgb_to_i420(&rgb, w, h).unwrap();
+        assert_eq!(i420, simd.rgb_to_i420(&rgb, w, h).unwrap());
+        let nv12 = scalar.rgb_to_nv12(&rgb, w, h).unwrap();
+        assert_eq!(nv12, simd.rgb_to_nv12(&rgb, w, h).unwrap());
+        let yuyv = scalar.rgb_to_yuyv(&rgb, w, h).unwrap();
+        assert_eq!(yuyv, simd.rgb_to_yuyv(&rgb, w, h).unwrap());
+
+        assert_eq!(
+            scalar.i420_to_rgb(&i420, w, h).unwrap(),
+            simd.i420_to_rgb(&i420, w, h).unwrap()
+        );
+        assert_eq!(
+            scalar.nv12_to_rgb(&nv12, w, h).unwrap(),
+            simd.nv12_to_rgb(&nv12, w, h).unwrap()
+        );
+        assert_eq!(
+            scalar.yuyv_to_rgb(&yuyv, w, h).unwrap(),
+            simd.yuyv_to_rgb(&yuyv, w, h).unwrap()
+        );
+    }
+}
+
+#[test]
+fn simd_saturates_like_scalar() {
+    // 随机字节作为 YUV 输入，包含大量超出 RGB 范围的组合
+    let (w, h) = (70usize, 24usize);
+    let yuv = noise(w as u32, h as u32).into_raw();
+    for space in SPACES {
+        let scalar = YuvConverter::new(space).with_backend(Backend::Scalar);
+        let simd = YuvConverter::new(space).with_backend(Backend::Simd);
+        let i420 = &yuv[..w * h * 3 / 2];
+        assert_eq!(
+            scalar.i420_to_rgb(i420, w, h).unwrap(),
+            simd.i420_to_rgb(i420, w, h).unwrap()
+        );
+        assert_eq!(
+            scalar.nv12_to_rgb(i420, w, h).unwrap(),
+            simd.nv12_to_rgb(i420, w, h).unwrap()
+        );
+        let yuyv = &yuv[..w * h * 2];
+        assert_eq!(
+            scalar.yuyv_to_rgb(yuyv, w, h).unwrap(),
+            simd.yuyv_to_rgb(yuyv, w, h).unwrap()
+        );
+    }
+}
+
+#[test]
+fn round_trip_within_tolerance() {
+    let (w, h) = (64, 32);
+    let rgb = blocks(w, h).into_raw();
+    let (w, h) = (w as usize, h as usize);
+    for space in SPACES {
+        let c = YuvConverter::new(space);
+        // 有限范围量化更粗，并且饱和色可能超出 YUV 可表示的范围
+        let i420 = c.i420_to_rgb(&c.rgb_to_i420(&rgb, w, h).unwrap(), w, h);
+        assert!(max_diff(&rgb, &i420.unwrap()) <= 4, "{:?}", space);
+        let nv12 = c.nv12_to_rgb(&c.rgb_to_nv12(&rgb, w, h).unwrap(), w, h);
+        assert!(max_diff(&rgb, &nv12.unwrap()) <= 4, "{:?}", space);
+        let yuyv = c.yuyv_to_rgb(&c.rgb_to_yuyv(&rgb, w, h).unwrap(), w, h);
+        assert!(max_diff(&rgb, &yuyv.unwrap()) <= 4, "{:?}", space);
+    }
+}
+
+#[test]
+fn reference_values() {
+    let pixel = |space: ColorSpace, rgb: [u8; 3]| {
+        let rgb: Vec<u8> = rgb.repeat(4);
+        let yuv = YuvConverter::new(space).rgb_to_i420(&rgb, 2, 2).unwrap();
+        (yuv[0], yuv[4], yuv[5])
+    };
+    assert_eq!(
+        pixel(ColorSpace::BT601_LIMITED, [255, 255, 255]),
+        (235, 128, 128)
+    );
+    assert_eq!(pixel(ColorSpace::BT601_LIMITED, [0, 0, 0]), (16, 128, 128));
+    assert_eq!(
+        pixel(ColorSpace::BT601_FULL, [255, 255, 255]),
+        (255, 128, 128)
+    );
+    assert_eq!(pixel(ColorSpace::BT601_FULL, [0, 0, 0]), (0, 128, 128));
+    // BT.601 与 BT.709 的红色差异明显
+    assert_eq!(pixel(ColorSpace::BT601_LIMITED, [255, 0, 0]), (81, 90, 240));
+    assert_eq!(
+        pixel(ColorSpace::BT709_LIMITED, [255, 0, 0]),
+        (63, 102, 240)
+    );
+    assert_eq!(pixel(ColorSpace::BT709_FULL, [255, 0, 0]), (54, 99, 255));
+}
+
+#[test]
+fn layouts_agree() {
+    let (w, h) = (34, 18);
+    let rgb = noise(w, h).into_raw();
+    let (w, h) = (w as usize, h as usize);
+    let c = YuvConverter::new(ColorSpace::BT709_LIMITED);
+    let i420 = c.rgb_to_i420(&rgb, w, h).unwrap();
+    let nv12 = c.rgb_to_nv12(&rgb, w, h).unwrap();
+    assert_eq!(i420[..w * h], nv12[..w * h]);
+    assert_eq!(
+        c.i420_to_rgb(&i420, w, h).unwrap(),
+        c.nv12_to_rgb(&nv12, w, h).unwrap()
+    );
+}
+
+#[test]
+fn invalid_sizes_are_rejected() {
+    let c = YuvConverter::new(ColorSpace::BT601_LIMITED);
+    assert!(c.rgb_to_i420(&[0; 3 * 3 * 2], 3, 2).is_err());
+    assert!(c.rgb_to_i420(&[0; 3 * 4 * 3], 4, 3).is_err());
+    assert!(c.rgb_to_yuyv(&[0; 3 * 4 * 3], 4, 3).is_ok());
+    assert!(c.i420_to_rgb(&[0; 10], 4, 4).is_err());
+}
+
+// ---- 与 OpenCV cvt_color（BT.601 有限范围）对比 ----
+
+fn gray_mat(data: &[u8], rows: i32, cols: i32) -> Mat {
+    let mut mat = Mat::new_rows_cols_with_default(rows, cols, CV_8UC1, Scalar::all(0.0)).unwrap();
+    mat.data_bytes_mut().unwrap().copy_from_slice(data);
+    mat
+}
+
+fn asset() -> RgbImage {
+    image::open("assets/bus.jpg").unwrap().to_rgb8()
+}
+
+fn opencv_to_rgb(yuv: &Mat, code: i32) -> Vec<u8> {
+    let mut rgb = Mat::default();
+    imgproc::cvt_color_def(yuv, &mut rgb, code).unwrap();
+    mat_to_rgb_image(&rgb).unwrap().into_raw()
+}
+
+#[test]
+fn decoding_matches_opencv() {
+    let img = asset();
+    let (w, h) = (img.width() as usize & !1, img.height() as usize & !1);
+    let img = image::imageops::crop_imm(&img, 0, 0, w as u32, h as u32).to_image();
+    let c = YuvConverter::new(ColorSpace::BT601_LIMITED);
+    let rows = (h * 3 / 2) as i32;
+
+    let i420 = c.rgb_to_i420(img.as_raw(), w, h).unwrap();
+    let expected = opencv_to_rgb(
+        &gray_mat(&i420, rows, w as i32),
+        imgproc::COLOR_YUV2RGB_I420,
+    );
+    assert!(max_diff(&c.i420_to_rgb(&i420, w, h).unwrap(), &expected) <= 3);
+
+    let nv12 = c.rgb_to_nv12(img.as_raw(), w, h).unwrap();
+    let expected = opencv_to_rgb(
+        &gray_mat(&nv12, rows, w as i32),
+        imgproc::COLOR_YUV2RGB_NV12,
+    );
+    assert!(max_diff(&c.nv12_to_rgb(&nv12, w, h).unwrap(), &expected) <= 3);
+
+    let yuyv = c.rgb_to_yuyv(img.as_raw(), w, h).unwrap();
+    let mut packed = Mat::new_rows_cols_with_default(
+        h as i32,
+        w as i32,
+        opencv::core::CV_8UC2,
+        Scalar::all(0.0),
+    )
+    .unwrap();
+    packed.data_bytes_mut().unwrap().copy_from_slice(&yuyv);
+    let expected = opencv_to_rgb(&packed, imgproc::COLOR_YUV2RGB_YUYV);
+    assert!(max_diff(&c.yuyv_to_rgb(&yuyv, w, h).unwrap(), &expected) <= 3);
+}
+
+#[test]
+fn encoding_matches_opencv() {
+    let img = asset();
+    let (w, h) = (img.width() as usize & !1, img.height() as usize & !1);
+    let img = image::imageops::crop_imm(&img, 0, 0, w as u32, h as u32).to_image();
+    let c = YuvConverter::new(ColorSpace::BT601_LIMITED);
+
+    let mut expected = Mat::default();
+    imgproc::cvt_color_def(
+        &rgb_image_to_mat(&img).unwrap(),
+        &mut expected,
+        imgproc::COLOR_RGB2YUV_I420,
+    )
+    .unwrap();
+    let expected = expected.data_bytes().unwrap();
+    let actual = c.rgb_to_i420(img.as_raw(), w, h).unwrap();
+
+    // 亮度逐像素接近；色度的下采样方式可能不同，只比较平均误差
+    assert!(max_diff(&actual[..w * h], &expected[..w * h]) <= 2);
+    let chroma = actual.len() - w * h;
+    let mean = actual[w * h..]
+        .iter()
+        .zip(&expected[w * h..])
+        .map(|(a, b)| a.abs_diff(*b) as u64)
+        .sum::<u64>() as f64
+        / chroma as f64;
+    assert!(mean < 2.0, "{}", mean);
+}
@@ -0,0 +1,150 @@
+use image::{DynamicImage, ImageFormat, RgbImage};
+use opencv::core::Rect;
+use std::io::Cursor;
+use yolo_vision::utils::crop::{border_mask, crop_black_borders, largest_valid_rect, valid_rect};
+
+fn mask_from(rows: &[&str]) -> (Vec<u8>, usize, usize) {
+    let w = rows[0].len();
+    let data = rows
+        .iter()
+        .flat_map(|r| r.bytes().map(|b| if b == b'#' { 255 } else { 0 }))
+        .collect();
+    (data, w, rows.len())
+}
+
+#[test]
+fn full_mask_is_whole_image() {
+    let (mask, w, h) = mask_from(&["####", "####", "####"]);
+    assert_eq!(largest_valid_rect(&mask, w, h), Some(Rect::new(0, 0, 4, 3)));
+}
+
+#[test]
+fn empty_mask_has_no_rect() {
+    let (mask, w, h) = mask_from(&["....", "...."]);
+    assert_eq!(largest_valid_rect(&mask, w, h), None);
+}
+
+#[test]
+fn panorama_like_mask_crops_black_borders() {
+    // 拼接结果常见的弯曲黑边
+    let (mask, w, h) = mask_from(&[
+        "..######..",
+        ".########.",
+        "##########",
+        "##########",
+        ".########.",
+        "...####...",
+    ]);
+    assert_eq!(largest_valid_rect(&mask, w, h), Some(Rect::new(1, 1, 8, 4)));
+}
+
+#[test]
+fn picks_largest_of_disjoint_regions() {
+    let (mask, w, h) = mask_from(&["##.###", "##.###", "...###"]);
+    assert_eq!(largest_valid_rect(&mask, w, h), Some(Rect::new(3, 0, 3, 3)));
+}
+
+/// 模拟拼接后的全景：原图按行错位贴到黑色画布上，四角留下三角形黑边，再经 JPEG 压缩引入噪声
+fn warped_panorama(path: &str) -> (DynamicImage, Rect) {
+    let src = image::open(path).unwrap().to_rgb8();
+    let (w, h) = src.dimensions();
+    let shift = h / 8;
+    let mut canvas = RgbImage::new(w + shift, h + 2 * shift);
+    for y in 0..h {
+        // 上下各 shift 行的梯形区域，中间部分完整
+        let offset = shift * y / h;
+        for x in 0..w {
+            canvas.put_pixel(x + offset, y + shift, *src.get_pixel(x, y));
+        }
+    }
+    let mut bytes = Vec::new();
+    DynamicImage::ImageRgb8(canvas)
+        .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg)
+        .unwrap();
+    let pano = image::load_from_memory(&bytes).unwrap();
+    (
+        pano,
+        Rect::new(shift as i32, shift as i32, (w - shift) as i32, h as i32),
+    )
+}
+
+fn dark_rows_and_cols(img: &RgbImage, tolerance: u8) -> usize {
+    let dark = |p: &image::Rgb<u8>| p.0.iter().all(|&v| v <= tolerance);
+    let (w, h) = img.dimensions();
+    let edges = [
+        (0..w).map(|x| img.get_pixel(x, 0)).all(dark),
+        (0..w).map(|x| img.get_pixel(x, h - 1)).all(dark),
+        (0..h).map(|y| img.get_pixel(0, y)).all(dark),
+        (0..h).map(|y| img.get_pixel(w - 1, y)).all(dark),
+    ];
+    edges.iter().filter(|&&e| e).count()
+}
+
+#[test]
+fn crops_warped_asset_panoramas() {
+    for i in 1..=3 {
+        let path = format!("assets/image-{}.jpg", i);
+        let (pano, expected) = warped_panorama(&path);
+        let rect = valid_rect(&pano.to_rgb8(), 24).unwrap();
+
+        // 裁剪结果落在有效区域内，且不比期望的有效区域小太多
+        let inner = |r: Rect| {
+            r.x >= expected.x - 2
+                && r.y >= expected.y - 2
+                && r.x + r.width <= expected.x + expected.width + 2
+                && r.y + r.height <= expected.y + expected.height + 2
+        };
+        assert!(inner(rect), "{}: {:?} outside {:?}", path, rect, expected);
+        assert!(
+            rect.area() as f32 >= expected.area() as f32 * 0.95,
+            "{}: {:?} much smaller than {:?}",
+            path,
+            rect,
+            expected
+        );
+
+        let cropped = crop_black_borders(&pano, 24).to_rgb8();
+        assert_eq!(
+            cropped.dimensions(),
+            (rect.width as u32, rect.height as u32)
+        );
+        assert_eq!(dark_rows_and_cols(&cropped, 24), 0, "{}", path);
+    }
+}
+
+#[test]
+fn tolerance_absorbs_compression_noise() {
+    // 黑边中混入低亮度噪声，容差为 0 时裁不干净
+    let mut img = RgbImage::from_pixel(40, 30, image::Rgb([200, 120, 60]));
+    for y in 0..30 {
+        for x in 0..40 {
+            if !(5..35).contains(&x) || !(4..26).contains(&y) {
+                let v = ((x * 7 + y * 3) % 9) as u8;
+                img.put_pixel(x, y, image::Rgb([v, v / 2, v]));
+            }
+        }
+    }
+    assert_eq!(valid_rect(&img, 10), Some(Rect::new(5, 4, 30, 22)));
+    assert_ne!(valid_rect(&img, 0), Some(Rect::new(5, 4, 30, 22)));
+}
+
+#[test]
+fn dark_content_inside_is_kept() {
+    // 画面内部的黑色物体不与边缘连通，不算黑边
+    let mut img = RgbImage::from_pixel(30, 20, image::Rgb([90, 90, 90]));
+    for y in 8..12 {
+        for x in 10..20 {
+            img.put_pixel(x, y, image::Rgb([0, 0, 0]));
+        }
+    }
+    assert_eq!(valid_rect(&img, 10), Some(Rect::new(0, 0, 30, 20)));
+    let mask = border_mask(img.as_raw(), 30, 20, 3, 10);
+    assert!(mask.iter().all(|&v| v == 255));
+}
+
+#[test]
+fn all_black_image_is_returned_unchanged() {
+    let img = DynamicImage::ImageRgb8(RgbImage::new(16, 9));
+    assert_eq!(valid_rect(&img.to_rgb8(), 10), None);
+    assert_eq!(crop_black_borders(&img, 10).width(), 16);
+}
@@ -0,0 +1,208 @@
+use yolo_vision::eval::dataset::parse_yolo_labels;
+use yolo_vision::eval::metrics::average_precision;
+use yolo_vision::eval::{Dataset, Detection, Evaluator, GroundTruth};
+
+fn gt(class_id: usize, xyxy: [f32; 4]) -> GroundTruth {
+    GroundTruth {
+        class_id,
+        xyxy,
+        iscrowd: false,
+    }
+}
+
+fn crowd(class_id: usize, xyxy: [f32; 4]) -> GroundTruth {
+    GroundTruth {
+        iscrowd: true,
+        ..gt(class_id, xyxy)
+    }
+}
+
+fn det(class_id: usize, score: f32, xyxy: [f32; 4]) -> Detection {
+    Detection {
+        class_id,
+        score,
+        xyxy,
+    }
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+}
+
+#[test]
+fn perfect_predictions() {
+    let mut evaluator = Evaluator::new(2);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(1, 0.8, [20., 20., 40., 40.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.]), gt(1, [20., 20., 40., 40.])],
+    );
+    let report = evaluator.compute();
+    assert_close(report.map50, 1.0);
+    assert_close(report.map50_95, 1.0);
+    assert_eq!(report.confusion.get(0, 0), 1);
+    assert_eq!(report.confusion.get(1, 1), 1);
+}
+
+#[test]
+fn interleaved_false_positive() {
+    // TP(0.9), FP(0.8), TP(0.7) over 2 gts:
+    // recall = [0.5, 0.5, 1.0], precision envelope = [1, 2/3, 2/3]
+    // AP = (51 * 1 + 50 * 2/3) / 101
+    let mut evaluator = Evaluator::new(1);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(0, 0.8, [50., 50., 60., 60.]),
+            det(0, 0.7, [20., 20., 30., 30.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.]), gt(0, [20., 20., 30., 30.])],
+    );
+    let report = evaluator.compute();
+    assert_close(report.map50, (51.0 + 50.0 * 2.0 / 3.0) / 101.0);
+
+    let m = report.class(0).unwrap();
+    assert_eq!(m.num_gts, 2);
+    assert_eq!(m.num_preds, 3);
+    assert_eq!(m.pr_curve.recall, vec![0.5, 0.5, 1.0]);
+    assert_close(m.recall, 1.0);
+    assert_close(m.precision, 2.0 / 3.0);
+}
+
+#[test]
+fn iou_thresholds() {
+    // IoU = 60 / 100 = 0.6，仅 0.50 / 0.55 / 0.60 三个阈值下为 TP
+    let mut evaluator = Evaluator::new(1);
+    evaluator.update(
+        &[det(0, 0.9, [0., 0., 10., 6.])],
+        &[gt(0, [0., 0., 10., 10.])],
+    );
+    let report = evaluator.compute();
+    assert_close(report.map50, 1.0);
+    assert_close(report.map75, 0.0);
+    assert_close(report.map50_95, 0.3);
+}
+
+#[test]
+fn duplicates_and_missed_images() {
+    // 重复框算作 FP，另一张图片的真值未被检出
+    let mut evaluator = Evaluator::new(1);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(0, 0.8, [0., 0., 10., 10.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.])],
+    );
+    evaluator.update(&[], &[gt(0, [0., 0., 10., 10.])]);
+    let report = evaluator.compute();
+    // recall = [0.5, 0.5]，recall > 0.5 的采样点精确率为 0
+    assert_close(report.map50, 51.0 / 101.0);
+    assert_eq!(report.confusion.get(0, 0), 1);
+    assert_eq!(report.confusion.get(0, 1), 1);
+    assert_eq!(report.confusion.get(1, 0), 1);
+}
+
+#[test]
+fn classes_without_ground_truth_are_excluded() {
+    let mut evaluator = Evaluator::new(3);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(2, 0.9, [30., 30., 40., 40.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.])],
+    );
+    let report = evaluator.compute();
+    assert_eq!(report.per_class.len(), 1);
+    assert_close(report.map50, 1.0);
+    assert_eq!(report.confusion.get(2, 3), 1);
+}
+
+#[test]
+fn confusion_matrix_misclassification() {
+    let mut evaluator = Evaluator::new(2);
+    evaluator.update(
+        &[det(1, 0.9, [0., 0., 10., 10.])],
+        &[gt(0, [0., 0., 10., 10.])],
+    );
+    let report = evaluator.compute();
+    assert_eq!(report.confusion.get(1, 0), 1);
+    assert_eq!(report.confusion.get(0, 0), 0);
+    assert_close(report.map50, 0.0);
+}
+
+#[test]
+fn average_precision_empty() {
+    assert_eq!(average_precision(&[], &[]), 0.0);
+}
+
+#[test]
+fn yolo_labels() {
+    let gts = parse_yolo_labels("0 0.5 0.5 0.2 0.4\n\n3 0.1 0.1 0.2 0.2\n", 100.0, 50.0).unwrap();
+    assert_eq!(gts.len(), 2);
+    assert_eq!(gts[0].class_id, 0);
+    assert_eq!(gts[1].class_id, 3);
+    for (a, b) in gts[0].xyxy.iter().zip([40.0, 15.0, 60.0, 35.0]) {
+        assert_close(*a, b);
+    }
+    for (a, b) in gts[1].xyxy.iter().zip([0.0, 0.0, 20.0, 10.0]) {
+        assert_close(*a, b);
+    }
+
+    assert!(parse_yolo_labels("0 0.5 0.5", 100.0, 100.0).is_err());
+}
+
+#[test]
+fn coco_annotations() {
+    let dataset = Dataset::from_coco("tests/fixtures/eval/coco.json", "images").unwrap();
+    assert_eq!(dataset.class_names, vec!["person", "car"]);
+    assert_eq!(dataset.samples.len(), 2);
+
+    let a = &dataset.samples[0];
+    assert!(a.image.ends_with("a.jpg"));
+    assert_eq!(a.ground_truths.len(), 2);
+    assert!(a.ground_truths.contains(&gt(1, [10., 20., 40., 60.])));
+    assert!(a.ground_truths.contains(&gt(0, [0., 0., 50., 50.])));
+
+    // crowd 标注保留为忽略区域
+    assert_eq!(
+        dataset.samples[1].ground_truths,
+        vec![crowd(0, [5., 5., 15., 15.])]
+    );
+}
+
+#[test]
+fn crowd_regions_are_ignored() {
+    let mut evaluator = Evaluator::new(1);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            // 落在 crowd 区域内，不算 FP
+            det(0, 0.8, [100., 100., 110., 110.]),
+            det(0, 0.7, [120., 120., 130., 130.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.]), crowd(0, [90., 90., 200., 200.])],
+    );
+    let report = evaluator.compute();
+    let m = report.class(0).unwrap();
+    assert_eq!(m.num_gts, 1);
+    assert_eq!(m.num_preds, 1);
+    assert_close(m.ap50_95, 1.0);
+    assert_eq!(report.confusion.get(0, report.confusion.background()), 0);
+
+    // 没有 crowd 区域时两个预测都是 FP
+    let mut evaluator = Evaluator::new(1);
+    evaluator.update(
+        &[
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(0, 0.8, [100., 100., 110., 110.]),
+        ],
+        &[gt(0, [0., 0., 10., 10.])],
+    );
+    let report = evaluator.compute();
+    assert_eq!(report.class(0).unwrap().num_preds, 2);
+    assert_eq!(report.confusion.get(0, report.confusion.background()), 1);
+}
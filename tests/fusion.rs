@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use yolo_vision::postprocess::fusion::{weighted_boxes_fusion, ConfType};
+use yolo_vision::postprocess::{
+    fuse, fuse_groups, Detection, FusionConfig, FusionMethod, LabelSpace,
+};
+use yolo_vision::utils::geometry::BoxF;
+
+fn det(class_id: usize, score: f32, xyxy: [f32; 4]) -> Detection {
+    Detection::new(BoxF::from_xyxy(xyxy), score, class_id)
+}
+
+fn assert_close(a: f32, b: f32) {
+    assert!((a - b).abs() < 1e-4, "{} != {}", a, b);
+}
+
+#[test]
+fn wbf_averages_overlapping_boxes() {
+    let sources = vec![
+        vec![det(0, 0.9, [0., 0., 10., 10.])],
+        vec![det(0, 0.6, [2., 0., 12., 10.])],
+    ];
+    let fused = weighted_boxes_fusion(&sources, &[1.0, 1.0], &FusionConfig::default());
+    assert_eq!(fused.len(), 1);
+    // 坐标按置信度加权：x1 = (0 * 0.9 + 2 * 0.6) / 1.5
+    let [x1, y1, x2, y2] = fused[0].bbox.xyxy();
+    assert_close(x1, 0.8);
+    assert_close(y1, 0.0);
+    assert_close(x2, 10.8);
+    assert_close(y2, 10.0);
+    assert_close(fused[0].score, 0.75);
+}
+
+#[test]
+fn wbf_penalizes_single_source_boxes() {
+    let sources = vec![
+        vec![
+            det(0, 0.9, [0., 0., 10., 10.]),
+            det(1, 0.8, [50., 50., 60., 60.]),
+        ],
+        vec![det(0, 0.7, [0., 0., 10., 10.])],
+    ];
+    let fused = weighted_boxes_fusion(&sources, &[1.0, 1.0], &FusionConfig::default());
+    assert_eq!(fused.len(), 2);
+    // 只有一个模型检出的框，置信度按 1 / 2 折算
+    let lone = fused.iter().find(|d| d.class_id == 1).unwrap();
+    assert_close(lone.score, 0.4);
+
+    let max = FusionConfig {
+        conf_type: ConfType::Max,
+        ..Default::default()
+    };
+    let fused = weighted_boxes_fusion(&sources, &[1.0, 1.0], &max);
+    assert_close(fused.iter().find(|d| d.class_id == 0).unwrap().score, 0.9);
+}
+
+#[test]
+fn wbf_weights_and_classes() {
+    let sources = vec![
+        vec![det(0, 0.5, [0., 0., 10., 10.])],
+        vec![
+            det(0, 0.5, [4., 0., 14., 10.]),
+            det(1, 0.5, [0., 0., 10., 10.]),
+        ],
+    ];
+    let config = FusionConfig {
+        iou_threshold: 0.4,
+        ..Default::default()
+    };
+    let fused = weighted_boxes_fusion(&sources, &[1.0, 3.0], &config);
+    // 不同类别不融合
+    assert_eq!(fused.len(), 2);
+    // 权重 3 的来源主导坐标：x1 = (0 * 0.5 + 4 * 1.5) / 2.0
+    let f = fused.iter().find(|d| d.class_id == 0).unwrap();
+    assert_close(f.bbox.x1, 3.0);
+}
+
+#[test]
+fn union_nms() {
+    let sources = vec![
+        vec![det(0, 0.9, [0., 0., 10., 10.])],
+        vec![
+            det(0, 0.6, [1., 0., 11., 10.]),
+            det(0, 0.5, [40., 40., 50., 50.]),
+        ],
+    ];
+    let config = FusionConfig {
+        method: FusionMethod::Nms,
+        ..Default::default()
+    };
+    let fused = fuse(&sources, &[1.0, 1.0], &config);
+    assert_eq!(fused.len(), 2);
+    assert_eq!((fused[0].source, fused[0].score), (0, 0.9));
+    assert_eq!((fused[1].source, fused[1].score), (1, 0.5));
+}
+
+#[test]
+fn fused_groups_report_members() {
+    let sources = vec![
+        vec![
+            det(1, 0.8, [50., 50., 60., 60.]),
+            det(0, 0.6, [0., 0., 10., 10.]),
+        ],
+        vec![det(0, 0.9, [1., 0., 11., 10.])],
+    ];
+    // 成员为 (来源编号, 来源内下标)，簇内按分数降序
+    let groups = fuse_groups(&sources, &[1.0, 1.0], &FusionConfig::default());
+    assert_eq!(groups.len(), 2);
+    let car = groups.iter().find(|(d, _)| d.class_id == 0).unwrap();
+    assert_eq!(car.1, vec![(1, 0), (0, 1)]);
+    let other = groups.iter().find(|(d, _)| d.class_id == 1).unwrap();
+    assert_eq!(other.1, vec![(0, 0)]);
+
+    let config = FusionConfig {
+        method: FusionMethod::Nms,
+        ..Default::default()
+    };
+    let groups = fuse_groups(&sources, &[1.0, 1.0], &config);
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].1, vec![(1, 0)]);
+    assert_eq!(groups[0].0.source, 1);
+    assert_eq!(groups[1].1, vec![(0, 0)]);
+}
+
+#[test]
+fn label_space_remap() {
+    let mut labels = LabelSpace::new(&["person".to_string()]);
+    let map: HashMap<String, String> = [("pedestrian", "person"), ("truck", "vehicle")]
+        .iter()
+        .map(|(a, b)| (a.to_string(), b.to_string()))
+        .collect();
+
+    assert_eq!(labels.remap("pedestrian", &map, true), Some(0));
+    assert_eq!(labels.remap("truck", &map, true), Some(1));
+    assert_eq!(labels.remap("dog", &map, true), None);
+    assert_eq!(labels.remap("dog", &map, false), Some(2));
+    assert_eq!(labels.remap("person", &HashMap::new(), false), Some(0));
+    assert_eq!(labels.names(), ["person", "vehicle", "dog"]);
+}
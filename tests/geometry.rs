@@ -0,0 +1,215 @@
+use opencv::core::Rect;
+use proptest::prelude::*;
+use yolo_vision::utils::geometry::{
+    mask_iou, oks, point_in_polygon, polygon_iou, rasterize_polygons, simplify_polyline, Anchor,
+    BoxF, RotatedBox,
+};
+use yolo_vision::utils::math::calculate_iou;
+
+const TOL: f32 = 1e-4;
+
+fn boxf() -> impl Strategy<Value = BoxF> {
+    (0f32..500.0, 0f32..500.0, 0.5f32..300.0, 0.5f32..300.0)
+        .prop_map(|(x, y, w, h)| BoxF::from_xywh([x, y, w, h]))
+}
+
+fn rotated() -> impl Strategy<Value = RotatedBox> {
+    (
+        0f32..200.0,
+        0f32..200.0,
+        1f32..100.0,
+        1f32..100.0,
+        -3.2f32..3.2,
+    )
+        .prop_map(|(cx, cy, w, h, a)| RotatedBox::new(cx, cy, w, h, a))
+}
+
+proptest! {
+    #[test]
+    fn iou_variants_symmetric(a in boxf(), b in boxf()) {
+        prop_assert!((a.iou(&b) - b.iou(&a)).abs() < TOL);
+        prop_assert!((a.giou(&b) - b.giou(&a)).abs() < TOL);
+        prop_assert!((a.diou(&b) - b.diou(&a)).abs() < TOL);
+        prop_assert!((a.ciou(&b) - b.ciou(&a)).abs() < TOL);
+    }
+
+    #[test]
+    fn iou_variants_bounded(a in boxf(), b in boxf()) {
+        let (iou, giou, diou, ciou) = (a.iou(&b), a.giou(&b), a.diou(&b), a.ciou(&b));
+        prop_assert!((0.0..=1.0 + TOL).contains(&iou));
+        prop_assert!((-1.0 - TOL..=1.0 + TOL).contains(&giou));
+        prop_assert!((-1.0 - TOL..=1.0 + TOL).contains(&diou));
+        prop_assert!(giou <= iou + TOL);
+        prop_assert!(diou <= iou + TOL);
+        prop_assert!(ciou <= diou + TOL);
+        prop_assert!(ciou >= -2.0);
+    }
+
+    #[test]
+    fn identical_boxes(a in boxf()) {
+        prop_assert!((a.iou(&a) - 1.0).abs() < TOL);
+        prop_assert!((a.giou(&a) - 1.0).abs() < TOL);
+        prop_assert!((a.ciou(&a) - 1.0).abs() < TOL);
+    }
+
+    #[test]
+    fn format_round_trip(a in boxf()) {
+        for b in [
+            BoxF::from_xywh(a.xywh()),
+            BoxF::from_cxcywh(a.cxcywh()),
+            BoxF::from_xyxy(a.xyxy()),
+        ] {
+            for (x, y) in a.xyxy().iter().zip(b.xyxy()) {
+                prop_assert!((x - y).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn matches_integer_rect_iou(x in 0i32..100, y in 0i32..100, w in 1i32..100, h in 1i32..100,
+                                x2 in 0i32..100, y2 in 0i32..100, w2 in 1i32..100, h2 in 1i32..100) {
+        let (a, b) = (Rect::new(x, y, w, h), Rect::new(x2, y2, w2, h2));
+        prop_assert!((calculate_iou(&a, &b) - BoxF::from(a).iou(&BoxF::from(b))).abs() < TOL);
+        prop_assert_eq!(BoxF::from(a).to_rect(), a);
+    }
+
+    #[test]
+    fn rotated_iou_symmetric_and_bounded(a in rotated(), b in rotated()) {
+        let (ab, ba) = (a.iou(&b), b.iou(&a));
+        prop_assert!((ab - ba).abs() < 1e-3);
+        prop_assert!((0.0..=1.0).contains(&ab));
+        prop_assert!((a.iou(&a) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rotated_iou_matches_axis_aligned(a in boxf(), b in boxf()) {
+        let to_rotated = |r: BoxF| {
+            let [cx, cy, w, h] = r.cxcywh();
+            RotatedBox::new(cx, cy, w, h, 0.0)
+        };
+        prop_assert!((to_rotated(a).iou(&to_rotated(b)) - a.iou(&b)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn mask_iou_symmetric_and_bounded(a in prop::collection::vec(0u8..2, 64), b in prop::collection::vec(0u8..2, 64)) {
+        let (ab, ba) = (mask_iou(&a, &b).unwrap(), mask_iou(&b, &a).unwrap());
+        prop_assert_eq!(ab, ba);
+        prop_assert!((0.0..=1.0).contains(&ab));
+    }
+}
+
+#[test]
+fn known_values() {
+    let a = BoxF::new(0.0, 0.0, 10.0, 10.0);
+    let b = BoxF::new(5.0, 0.0, 15.0, 10.0);
+    assert!((a.iou(&b) - 50.0 / 150.0).abs() < TOL);
+    // 外接框面积与并集相同，GIoU = IoU
+    assert!((a.giou(&b) - a.iou(&b)).abs() < TOL);
+    // 中心距离平方 25，外接框对角线平方 15^2 + 10^2 = 325
+    assert!((a.diou(&b) - (50.0 / 150.0 - 25.0 / 325.0)).abs() < TOL);
+    // 宽高比相同，CIoU = DIoU
+    assert!((a.ciou(&b) - a.diou(&b)).abs() < TOL);
+
+    // 不相交时 GIoU 为负
+    let c = BoxF::new(20.0, 0.0, 30.0, 10.0);
+    assert!((a.giou(&c) - (0.0 - 100.0 / 300.0)).abs() < TOL);
+}
+
+#[test]
+fn rotated_known_values() {
+    // 正方形旋转 45° 与自身原状的交集为正八边形
+    let a = RotatedBox::new(0.0, 0.0, 2.0, 2.0, 0.0);
+    let b = RotatedBox::new(0.0, 0.0, 2.0, 2.0, std::f32::consts::FRAC_PI_4);
+    let octagon = 8.0 * (std::f32::consts::SQRT_2 - 1.0);
+    assert!((a.iou(&b) - octagon / (8.0 - octagon)).abs() < 1e-3);
+
+    let far = RotatedBox::new(100.0, 100.0, 2.0, 2.0, 0.3);
+    assert_eq!(a.iou(&far), 0.0);
+
+    let square = [(0.0, 0.0), (2.0, 0.0), (2.0, 2.0), (0.0, 2.0)];
+    let shifted = [(1.0, 0.0), (3.0, 0.0), (3.0, 2.0), (1.0, 2.0)];
+    assert!((polygon_iou(&square, &shifted) - 2.0 / 6.0).abs() < TOL);
+}
+
+#[test]
+fn mask_and_keypoint_similarity() {
+    assert_eq!(mask_iou(&[1, 1, 0, 0], &[0, 1, 1, 0]), Some(1.0 / 3.0));
+    assert_eq!(mask_iou(&[0, 0], &[0, 0]), Some(0.0));
+    assert_eq!(mask_iou(&[1, 1, 0, 0], &[1, 1, 0]), None);
+
+    let gt = [(10.0, 10.0), (20.0, 20.0)];
+    assert!((oks(&gt, &gt, &[true, true], 100.0, &[0.05, 0.05]) - 1.0).abs() < TOL);
+    // 不可见关键点不计入
+    let pred = [(10.0, 10.0), (90.0, 90.0)];
+    assert!((oks(&pred, &gt, &[true, false], 100.0, &[0.05, 0.05]) - 1.0).abs() < TOL);
+    assert!(oks(&pred, &gt, &[true, true], 100.0, &[0.05, 0.05]) < 0.6);
+    assert_eq!(oks(&pred, &gt, &[false, false], 100.0, &[0.05, 0.05]), 0.0);
+}
+
+#[test]
+fn rasterize_polygons_by_pixel_centre() {
+    // 覆盖像素 [2, 6) x [1, 3) 的矩形
+    let rect = vec![(2.0, 1.0), (6.0, 1.0), (6.0, 3.0), (2.0, 3.0)];
+    let mask = rasterize_polygons(&[rect], 8, 4);
+    let filled: Vec<(usize, usize)> = (0..32)
+        .filter(|i| mask[*i] == 255)
+        .map(|i| (i % 8, i / 8))
+        .collect();
+    let expected: Vec<(usize, usize)> = (1..3).flat_map(|y| (2..6).map(move |x| (x, y))).collect();
+    assert_eq!(filled, expected);
+
+    // 三角形面积近似，越界部分被裁剪
+    let triangle = vec![(0.0, 0.0), (100.0, 0.0), (0.0, 100.0)];
+    let mask = rasterize_polygons(&[triangle], 100, 100);
+    let area = mask.iter().filter(|&&m| m == 255).count() as f32;
+    assert!((area - 5000.0).abs() < 100.0);
+
+    let outside = vec![
+        (-10.0, -10.0),
+        (200.0, -10.0),
+        (200.0, 200.0),
+        (-10.0, 200.0),
+    ];
+    assert!(rasterize_polygons(&[outside], 10, 10)
+        .iter()
+        .all(|&m| m == 255));
+    assert!(rasterize_polygons(&[vec![(0.0, 0.0), (5.0, 5.0)]], 10, 10)
+        .iter()
+        .all(|&m| m == 0));
+}
+
+#[test]
+fn anchors_and_point_in_polygon() {
+    let b = BoxF::new(10.0, 20.0, 30.0, 60.0);
+    assert_eq!(b.anchor(Anchor::Center), (20.0, 40.0));
+    assert_eq!(b.anchor(Anchor::BottomCenter), (20.0, 60.0));
+    assert_eq!(b.anchor(Anchor::TopCenter), (20.0, 20.0));
+
+    // 凹多边形（L 形）
+    let l = [
+        (0.0, 0.0),
+        (10.0, 0.0),
+        (10.0, 4.0),
+        (4.0, 4.0),
+        (4.0, 10.0),
+        (0.0, 10.0),
+    ];
+    assert!(point_in_polygon((2.0, 8.0), &l));
+    assert!(point_in_polygon((8.0, 2.0), &l));
+    assert!(!point_in_polygon((8.0, 8.0), &l));
+    assert!(!point_in_polygon((-1.0, 2.0), &l));
+}
+
+#[test]
+fn douglas_peucker() {
+    let line: Vec<(f32, f32)> = (0..10).map(|i| (i as f32, 0.0)).collect();
+    assert_eq!(simplify_polyline(&line, 0.1), vec![(0.0, 0.0), (9.0, 0.0)]);
+
+    let zigzag = [(0.0, 0.0), (1.0, 0.05), (2.0, 0.0), (3.0, 2.0), (4.0, 0.0)];
+    assert_eq!(
+        simplify_polyline(&zigzag, 0.1),
+        vec![(0.0, 0.0), (2.0, 0.0), (3.0, 2.0), (4.0, 0.0)]
+    );
+    assert_eq!(simplify_polyline(&zigzag, 0.01), zigzag.to_vec());
+    assert_eq!(simplify_polyline(&zigzag[..2], 10.0), zigzag[..2].to_vec());
+}
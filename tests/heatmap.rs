@@ -0,0 +1,202 @@
+use image::{DynamicImage, GrayImage, Luma, RgbImage};
+use std::time::{Duration, Instant};
+use usls::{Bbox, Mask, Y};
+use yolo_vision::heatmap::{colormap, Heatmap, HeatmapConfig, HeatmapDecay, HeatmapSource};
+use yolo_vision::utils::geometry::Anchor;
+
+const TOL: f32 = 1e-4;
+
+fn person(x1: f32, y1: f32, x2: f32, y2: f32) -> Bbox {
+    Bbox::default()
+        .with_xyxy(x1, y1, x2, y2)
+        .with_confidence(0.9)
+        .with_id(0)
+        .with_name("person")
+}
+
+fn config(source: HeatmapSource) -> HeatmapConfig {
+    HeatmapConfig {
+        grid: [10, 10],
+        source,
+        radius: 0.0,
+        decay: HeatmapDecay::None,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn anchor_points_in_grid_coordinates() {
+    let mut cfg = config(HeatmapSource::Anchor);
+    cfg.anchor = Anchor::BottomCenter;
+    let mut heatmap = Heatmap::new(cfg, "s").unwrap();
+    // 1000x500 的画面，底边中点 (250, 275) 落在网格 (2, 5)
+    let y = Y::default().with_bboxes(&[person(200.0, 100.0, 300.0, 275.0)]);
+    heatmap.update(&[y.clone(), y], &[(1000, 500), (1000, 500)]);
+    assert_eq!(heatmap.grid()[5 * 10 + 2], 2.0);
+    assert_eq!(heatmap.grid().iter().sum::<f32>(), 2.0);
+
+    // 高斯核：中心权重最大，总和大于 1
+    let mut cfg = config(HeatmapSource::Anchor);
+    cfg.radius = 2.0;
+    let mut heatmap = Heatmap::new(cfg, "s").unwrap();
+    heatmap.add(
+        &Y::default().with_bboxes(&[person(40.0, 40.0, 60.0, 55.0)]),
+        100,
+        100,
+    );
+    let g = heatmap.grid();
+    let max = g.iter().cloned().fold(0.0, f32::max);
+    assert_eq!(max, g[5 * 10 + 5]);
+    assert!(g.iter().sum::<f32>() > 1.0);
+}
+
+#[test]
+fn box_and_mask_coverage() {
+    let mut heatmap = Heatmap::new(config(HeatmapSource::Box), "s").unwrap();
+    // 网格坐标下为 (1, 1)-(3.5, 2)，覆盖面积 2.5
+    heatmap.add(
+        &Y::default().with_bboxes(&[person(10.0, 10.0, 35.0, 20.0)]),
+        100,
+        100,
+    );
+    assert!((heatmap.grid().iter().sum::<f32>() - 2.5).abs() < TOL);
+    assert!((heatmap.grid()[10 + 3] - 0.5).abs() < TOL);
+
+    let mut mask = GrayImage::new(100, 100);
+    for y in 0..20 {
+        for x in 0..15 {
+            mask.put_pixel(x, y, Luma([255]));
+        }
+    }
+    let mut heatmap = Heatmap::new(config(HeatmapSource::Mask), "s").unwrap();
+    heatmap.add(
+        &Y::default().with_masks(&[Mask::default().with_mask(mask).with_id(0)]),
+        100,
+        100,
+    );
+    let g = heatmap.grid();
+    assert!((g[0] - 1.0).abs() < TOL);
+    assert!((g[1] - 0.5).abs() < TOL);
+    assert!((g.iter().sum::<f32>() - 3.0).abs() < TOL);
+}
+
+#[test]
+fn class_filter() {
+    let mut cfg = config(HeatmapSource::Box);
+    cfg.classes = vec!["car".to_string()];
+    let mut heatmap = Heatmap::new(cfg, "s").unwrap();
+    heatmap.add(
+        &Y::default().with_bboxes(&[person(0.0, 0.0, 50.0, 50.0)]),
+        100,
+        100,
+    );
+    assert!(heatmap.grid().iter().all(|&v| v == 0.0));
+}
+
+#[test]
+fn exponential_decay_and_windows() {
+    let y = Y::default().with_bboxes(&[person(0.0, 0.0, 10.0, 10.0)]);
+    let t0 = Instant::now();
+
+    let mut cfg = config(HeatmapSource::Box);
+    cfg.decay = HeatmapDecay::Exponential {
+        half_life_secs: 10.0,
+    };
+    let mut heatmap = Heatmap::new(cfg, "s").unwrap();
+    heatmap.update_at(std::slice::from_ref(&y), &[(100, 100)], t0);
+    heatmap.update_at(&[], &[], t0 + Duration::from_secs(10));
+    assert!((heatmap.grid()[0] - 0.5).abs() < TOL);
+    heatmap.update_at(&[], &[], t0 + Duration::from_secs(30));
+    assert!((heatmap.grid()[0] - 0.125).abs() < TOL);
+
+    let mut cfg = config(HeatmapSource::Box);
+    cfg.decay = HeatmapDecay::Window { secs: 60.0 };
+    let mut heatmap = Heatmap::new(cfg, "s").unwrap();
+    heatmap.update_at(std::slice::from_ref(&y), &[(100, 100)], t0);
+    heatmap.update_at(
+        std::slice::from_ref(&y),
+        &[(100, 100)],
+        t0 + Duration::from_secs(59),
+    );
+    assert!((heatmap.grid()[0] - 2.0).abs() < TOL);
+    heatmap.update_at(
+        std::slice::from_ref(&y),
+        &[(100, 100)],
+        t0 + Duration::from_secs(61),
+    );
+    assert!((heatmap.grid()[0] - 1.0).abs() < TOL);
+}
+
+#[test]
+fn invalid_decay_is_rejected() {
+    for decay in [
+        HeatmapDecay::Window { secs: -1.0 },
+        HeatmapDecay::Window { secs: 0.0 },
+        HeatmapDecay::Window { secs: f32::NAN },
+        HeatmapDecay::Window { secs: f32::MAX },
+        HeatmapDecay::Exponential {
+            half_life_secs: f32::INFINITY,
+        },
+    ] {
+        let mut cfg = config(HeatmapSource::Box);
+        cfg.decay = decay;
+        assert!(Heatmap::new(cfg, "s").is_err(), "{:?}", decay);
+    }
+}
+
+#[test]
+fn overlay_only_touches_hot_regions() {
+    let mut heatmap = Heatmap::new(config(HeatmapSource::Box), "s").unwrap();
+    heatmap.add(
+        &Y::default().with_bboxes(&[person(0.0, 0.0, 50.0, 50.0)]),
+        100,
+        100,
+    );
+    let x = DynamicImage::ImageRgb8(RgbImage::from_pixel(200, 200, image::Rgb([10, 20, 30])));
+    let out = heatmap.apply(vec![x])[0].to_rgb8();
+    assert_ne!(out.get_pixel(40, 40).0, [10, 20, 30]);
+    assert_eq!(out.get_pixel(180, 180).0, [10, 20, 30]);
+
+    assert_eq!(colormap(0), [0, 0, 127]);
+    assert_eq!(colormap(255), [127, 0, 0]);
+}
+
+#[test]
+fn snapshot_and_raw_export() {
+    let dir = std::env::temp_dir().join(format!("heatmap-test-{}", std::process::id()));
+    let mut cfg = config(HeatmapSource::Box);
+    cfg.snapshot_interval_secs = Some(60);
+    cfg.output_dir = dir.to_string_lossy().to_string();
+    let mut heatmap = Heatmap::new(cfg, "gate-1").unwrap();
+    heatmap.add(
+        &Y::default().with_bboxes(&[person(0.0, 0.0, 20.0, 10.0)]),
+        100,
+        100,
+    );
+
+    let background = DynamicImage::ImageRgb8(RgbImage::new(64, 48));
+    let t0 = Instant::now();
+    assert!(heatmap
+        .maybe_snapshot_at(&background, t0)
+        .unwrap()
+        .is_none());
+    let png = heatmap
+        .maybe_snapshot_at(&background, t0 + Duration::from_secs(60))
+        .unwrap()
+        .unwrap();
+    assert_eq!(image::open(&png).unwrap().to_rgb8().dimensions(), (64, 48));
+
+    let npy = std::fs::read(png.with_extension("npy")).unwrap();
+    assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+    assert_eq!((10 + header_len) % 64, 0);
+    let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+    assert!(header.contains("'shape': (10, 10)"));
+    let values: Vec<f32> = npy[10 + header_len..]
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect();
+    assert_eq!(values, heatmap.grid());
+
+    std::fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,650 @@
+use futures::TryStreamExt;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use yolo_vision::utils::http_client::{
+    ApiError, Auth, ClientCredentials, HttpClient, RetryPolicy, IDEMPOTENCY_KEY,
+};
+
+/// 本地 HTTP 服务，按第几次请求和请求内容决定响应；返回 None 时直接断开连接
+struct FlakyServer {
+    url: String,
+    hits: Arc<AtomicUsize>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl FlakyServer {
+    async fn start<F>(respond: F) -> Self
+    where
+        F: Fn(usize, &str) -> Option<(u16, String)> + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond = Arc::new(respond);
+
+        let (h, r) = (hits.clone(), requests.clone());
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    break;
+                };
+                let (hits, requests, respond) = (h.clone(), r.clone(), respond.clone());
+                tokio::spawn(async move {
+                    let request = read_request(&mut stream).await;
+                    let n = hits.fetch_add(1, Ordering::SeqCst);
+                    let answer = respond(n, &request);
+                    requests.lock().unwrap().push(request);
+                    let Some((status, body)) = answer else {
+                        return;
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status,
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        Self {
+            url,
+            hits,
+            requests,
+        }
+    }
+
+    fn hits(&self) -> usize {
+        self.hits.load(Ordering::SeqCst)
+    }
+
+    /// 请求头名称转为小写，便于断言
+    fn requests(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|r| match r.split_once("\r\n\r\n") {
+                Some((head, body)) => format!("{}\r\n\r\n{}", head.to_lowercase(), body),
+                None => r.to_lowercase(),
+            })
+            .collect()
+    }
+}
+
+/// 读取请求头和 Content-Length 指定长度的请求体
+async fn read_request(stream: &mut tokio::net::TcpStream) -> String {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let n = stream.read(&mut chunk).await.unwrap_or(0);
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        let text = String::from_utf8_lossy(&buf);
+        if let Some(end) = text.find("\r\n\r\n") {
+            let length = text[..end]
+                .lines()
+                .find_map(|l| {
+                    let (k, v) = l.split_once(':')?;
+                    k.eq_ignore_ascii_case("content-length")
+                        .then(|| v.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or(0);
+            if buf.len() >= end + 4 + length {
+                break;
+            }
+        }
+    }
+    String::from_utf8_lossy(&buf).into_owned()
+}
+
+fn fast_retry() -> RetryPolicy {
+    RetryPolicy::default()
+        .with_max_attempts(4)
+        .with_delays(Duration::from_millis(5), Duration::from_millis(20))
+}
+
+fn client() -> HttpClient {
+    HttpClient::builder()
+        .with_retry(fast_retry())
+        .build()
+        .unwrap()
+}
+
+fn reply(status: u16, body: &str) -> Option<(u16, String)> {
+    Some((status, body.to_string()))
+}
+
+#[test]
+fn backoff_is_exponential_capped_and_jittered() {
+    let policy = RetryPolicy::default()
+        .with_delays(Duration::from_millis(100), Duration::from_millis(1000))
+        .with_jitter(0.0);
+    let delays: Vec<u128> = (1..=6).map(|n| policy.delay(n).as_millis()).collect();
+    assert_eq!(delays, vec![100, 200, 400, 800, 1000, 1000]);
+    assert_eq!(policy.backoff(40), Duration::from_millis(1000));
+
+    let policy = policy.with_jitter(0.5);
+    for n in 1..=6 {
+        for _ in 0..50 {
+            let d = policy.delay(n);
+            assert!(d <= policy.backoff(n) && d >= policy.backoff(n) / 2);
+        }
+    }
+    assert_eq!(RetryPolicy::none().max_attempts, 1);
+}
+
+#[test]
+fn only_idempotent_requests_are_retryable() {
+    use reqwest::Method;
+    for m in [Method::GET, Method::PUT, Method::DELETE, Method::HEAD] {
+        assert!(RetryPolicy::is_retryable(&m, None));
+    }
+    assert!(!RetryPolicy::is_retryable(&Method::POST, None));
+    let headers = HashMap::from([("idempotency-key".to_string(), "abc".to_string())]);
+    assert!(RetryPolicy::is_retryable(&Method::POST, Some(&headers)));
+}
+
+#[tokio::test]
+async fn get_retries_server_errors() {
+    let server = FlakyServer::start(|n, _| match n {
+        0 => reply(503, "{}"),
+        1 => reply(502, "{}"),
+        _ => reply(200, r#"{"ok":true}"#),
+    })
+    .await;
+    let value = client().get(&server.url, None, None).await.unwrap();
+    assert_eq!(value["ok"], true);
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn get_retries_dropped_connections() {
+    let server = FlakyServer::start(|n, _| {
+        if n >= 2 {
+            reply(200, r#"{"ok":true}"#)
+        } else {
+            None
+        }
+    })
+    .await;
+    let value = client().get(&server.url, None, None).await.unwrap();
+    assert_eq!(value["ok"], true);
+    assert_eq!(server.hits(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_max_attempts() {
+    let server = FlakyServer::start(|_, _| reply(503, r#"{"msg":"restarting"}"#)).await;
+    let err = client().get(&server.url, None, None).await.unwrap_err();
+    assert!(err.to_string().contains("503"), "{}", err);
+    assert_eq!(server.hits(), 4);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = FlakyServer::start(|_, _| reply(400, "{}")).await;
+    assert!(client().get(&server.url, None, None).await.is_err());
+    assert_eq!(server.hits(), 1);
+
+    let server = FlakyServer::start(|_, _| reply(503, "{}")).await;
+    let no_retry = HttpClient::new().unwrap().with_retry(RetryPolicy::none());
+    assert!(no_retry.get(&server.url, None, None).await.is_err());
+    assert_eq!(server.hits(), 1);
+}
+
+#[tokio::test]
+async fn post_without_idempotency_key_is_not_retried() {
+    let server = FlakyServer::start(|n, _| match n {
+        0 => reply(503, "{}"),
+        _ => reply(200, "{}"),
+    })
+    .await;
+    let body = HashMap::from([("alarm", "intrusion")]);
+    assert!(client().post_json(&server.url, None, &body).await.is_err());
+    assert_eq!(server.hits(), 1);
+}
+
+#[tokio::test]
+async fn post_with_idempotency_key_is_retried() {
+    let server = FlakyServer::start(|n, _| match n {
+        0 => None,
+        1 => reply(503, "{}"),
+        _ => reply(200, r#"{"id":7}"#),
+    })
+    .await;
+    let body = HashMap::from([("alarm", "intrusion")]);
+    let headers = HashMap::from([(IDEMPOTENCY_KEY.to_string(), "alarm-42".to_string())]);
+    let value = client()
+        .post_json(&server.url, Some(headers), &body)
+        .await
+        .unwrap();
+    assert_eq!(value["id"], 7);
+    assert_eq!(server.hits(), 3);
+
+    // 每次重试都带相同的幂等键和请求体
+    for request in server.requests() {
+        assert!(request.contains("idempotency-key: alarm-42"));
+        assert!(request.ends_with(r#"{"alarm":"intrusion"}"#));
+    }
+}
+
+#[tokio::test]
+async fn base_url_default_headers_and_user_agent() {
+    let server = FlakyServer::start(|_, _| reply(200, "{}")).await;
+    let client = HttpClient::builder()
+        .with_base_url(&format!("{}/api/", server.url))
+        .with_header("X-Tenant", "t1")
+        .with_user_agent("yolo-vision-test")
+        .build()
+        .unwrap();
+    assert_eq!(client.url("/users"), format!("{}/api/users", server.url));
+    assert_eq!(client.url("https://example.com/x"), "https://example.com/x");
+
+    client.get("/users", None, None).await.unwrap();
+    let headers = HashMap::from([("X-Tenant".to_string(), "t2".to_string())]);
+    client.delete("users/1", Some(headers)).await.unwrap();
+
+    let requests = server.requests();
+    assert!(requests[0].starts_with("get /api/users http/1.1"));
+    assert!(requests[0].contains("x-tenant: t1"));
+    assert!(requests[0].contains("user-agent: yolo-vision-test"));
+    // 单次请求的同名请求头覆盖默认值
+    assert!(requests[1].starts_with("delete /api/users/1 http/1.1"));
+    assert!(requests[1].contains("x-tenant: t2") && !requests[1].contains("x-tenant: t1"));
+
+    let invalid = HttpClient::builder().with_header("X Tenant", "t1").build();
+    assert!(invalid.is_err());
+}
+
+#[tokio::test]
+async fn bearer_tokens_from_env_and_file() {
+    let server = FlakyServer::start(|_, _| reply(200, "{}")).await;
+    let var = "YOLO_VISION_TEST_HTTP_TOKEN";
+    std::env::set_var(var, "env-token");
+    let path = std::env::temp_dir().join(format!("yolo-vision-token-{}", std::process::id()));
+    std::fs::write(&path, "  file-token\n").unwrap();
+
+    for (auth, expected) in [
+        (Auth::Bearer("static-token".to_string()), "static-token"),
+        (Auth::BearerEnv(var.to_string()), "env-token"),
+        (Auth::BearerFile(path.clone()), "file-token"),
+    ] {
+        let client = HttpClient::builder().with_auth(auth).build().unwrap();
+        client.get(&server.url, None, None).await.unwrap();
+        let last = server.requests().pop().unwrap();
+        assert!(
+            last.contains(&format!("authorization: bearer {}", expected)),
+            "{}",
+            last
+        );
+    }
+
+    // 令牌读取失败时不发送请求
+    let hits = server.hits();
+    let missing = HttpClient::builder()
+        .with_auth(Auth::BearerEnv(
+            "YOLO_VISION_TEST_MISSING_TOKEN".to_string(),
+        ))
+        .build()
+        .unwrap();
+    assert!(missing.get(&server.url, None, None).await.is_err());
+    assert_eq!(server.hits(), hits);
+    std::fs::remove_file(path).unwrap();
+}
+
+/// `/token` 返回 `tok-<序号>`，其余路径前 `unauthorized` 次返回 401
+async fn oauth_server(expires_in: u64, unauthorized: usize) -> FlakyServer {
+    let tokens = AtomicUsize::new(0);
+    let api = AtomicUsize::new(0);
+    FlakyServer::start(move |_, request| {
+        if request.starts_with("POST /token") {
+            assert!(request.contains("grant_type=client_credentials"));
+            assert!(request.contains("client_id=camera") && request.contains("scope=alarm"));
+            let n = tokens.fetch_add(1, Ordering::SeqCst);
+            let body = format!(
+                r#"{{"access_token":"tok-{}","token_type":"Bearer","expires_in":{}}}"#,
+                n, expires_in
+            );
+            Some((200, body))
+        } else if api.fetch_add(1, Ordering::SeqCst) < unauthorized {
+            reply(401, r#"{"msg":"token revoked"}"#)
+        } else {
+            reply(200, r#"{"ok":true}"#)
+        }
+    })
+    .await
+}
+
+fn oauth_client(server: &FlakyServer) -> HttpClient {
+    let token_url = format!("{}/token", server.url);
+    let credentials = ClientCredentials::new(&token_url, "camera", "secret").with_scope("alarm");
+    HttpClient::builder()
+        .with_base_url(&server.url)
+        .with_auth(Auth::OAuth2(credentials))
+        .with_retry(fast_retry())
+        .build()
+        .unwrap()
+}
+
+fn authorizations(server: &FlakyServer) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .filter(|r| !r.starts_with("post /token"))
+        .map(|r| {
+            r.lines()
+                .find_map(|l| l.strip_prefix("authorization: "))
+                .unwrap_or_default()
+                .to_string()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn oauth2_token_is_cached_until_near_expiry() {
+    let server = oauth_server(3600, 0).await;
+    let client = oauth_client(&server);
+    client.get("/a", None, None).await.unwrap();
+    client.get("/b", None, None).await.unwrap();
+    assert_eq!(server.hits(), 3);
+    assert_eq!(authorizations(&server), vec!["bearer tok-0"; 2]);
+
+    // 有效期短于提前刷新时间，每次请求都重新获取
+    let server = oauth_server(30, 0).await;
+    let client = oauth_client(&server);
+    client.get("/a", None, None).await.unwrap();
+    client.get("/b", None, None).await.unwrap();
+    assert_eq!(server.hits(), 4);
+    assert_eq!(
+        authorizations(&server),
+        vec!["bearer tok-0", "bearer tok-1"]
+    );
+}
+
+#[tokio::test]
+async fn oauth2_refreshes_once_on_unauthorized() {
+    let server = oauth_server(3600, 1).await;
+    let client = oauth_client(&server);
+    let value = client.get("/a", None, None).await.unwrap();
+    assert_eq!(value["ok"], true);
+    assert_eq!(
+        authorizations(&server),
+        vec!["bearer tok-0", "bearer tok-1"]
+    );
+
+    // 刷新后仍然 401 时返回错误
+    let server = oauth_server(3600, 2).await;
+    let err = oauth_client(&server)
+        .get("/a", None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("401"), "{}", err);
+    assert_eq!(server.hits(), 4);
+}
+
+#[tokio::test]
+async fn request_timeout_is_applied() {
+    // 接受连接但从不响应
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let mut held = Vec::new();
+        while let Ok((stream, _)) = listener.accept().await {
+            held.push(stream);
+        }
+    });
+
+    let client = HttpClient::builder()
+        .with_timeout(Some(Duration::from_millis(100)))
+        .with_retry(RetryPolicy::none())
+        .build()
+        .unwrap();
+    let start = std::time::Instant::now();
+    assert!(client.get(&url, None, None).await.is_err());
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Plan {
+    id: u64,
+    plan_name: String,
+}
+
+#[tokio::test]
+async fn typed_responses() {
+    let server = FlakyServer::start(|n, _| match n {
+        0 => reply(200, r#"{"id":1,"planName":"night","extra":true}"#),
+        1 => reply(200, r#"[{"id":2,"planName":"day"}]"#),
+        _ => reply(200, r#"{"id":"x"}"#),
+    })
+    .await;
+    let client = client();
+    let plan: Plan = client.get_as(&server.url, None, None).await.unwrap();
+    assert_eq!(
+        plan,
+        Plan {
+            id: 1,
+            plan_name: "night".to_string()
+        }
+    );
+    let plans: Vec<Plan> = client
+        .post_as(&server.url, None, &json!({"enabled": true}))
+        .await
+        .unwrap();
+    assert_eq!(plans[0].id, 2);
+
+    let err = client
+        .get_as::<Plan>(&server.url, None, None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("Plan"), "{}", err);
+}
+
+#[tokio::test]
+async fn envelope_codes_become_typed_errors() {
+    let server = FlakyServer::start(|n, _| match n {
+        0 => reply(
+            200,
+            r#"{"code":0,"msg":"success","data":{"id":3,"planName":"a"}}"#,
+        ),
+        1 => reply(200, r#"{"code":200,"data":null}"#),
+        _ => reply(200, r#"{"code":40101,"msg":"token expired","data":null}"#),
+    })
+    .await;
+    let client = client();
+    let plan: Plan = client.get_data(&server.url, None, None).await.unwrap();
+    assert_eq!(plan.id, 3);
+    let empty: Option<Plan> = client
+        .post_data(&server.url, None, &json!({}))
+        .await
+        .unwrap();
+    assert!(empty.is_none());
+
+    let err = client
+        .get_data::<Plan>(&server.url, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ApiError>(),
+        Some(&ApiError {
+            code: 40101,
+            msg: "token expired".to_string()
+        })
+    );
+
+    // 自定义成功码
+    let strict = HttpClient::builder()
+        .with_success_codes(&[1])
+        .build()
+        .unwrap();
+    let err = strict
+        .get_data::<Value>(&server.url, None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err.downcast_ref::<ApiError>().unwrap().code, 40101);
+}
+
+/// 共 `total` 条记录的分页接口，记录为序号；`report_total` 为 false 时不返回 total，
+/// 每页最多返回 `max_size` 条
+async fn page_server(total: u64, report_total: bool, max_size: u64) -> FlakyServer {
+    FlakyServer::start(move |_, request| page_reply(request, total, report_total, max_size)).await
+}
+
+/// 按请求中的 pageNo / pageSize 返回 0..total 中对应的一页
+fn page_reply(
+    request: &str,
+    total: u64,
+    report_total: bool,
+    max_size: u64,
+) -> Option<(u16, String)> {
+    let (head, body) = request.split_once("\r\n\r\n").unwrap();
+    let params: Value = if head.starts_with("GET") {
+        let query = head.split_whitespace().nth(1).unwrap();
+        let query = query.split_once('?').map(|(_, q)| q).unwrap_or_default();
+        query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .map(|(k, v)| (k.to_string(), json!(v.parse::<u64>().ok())))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    } else {
+        serde_json::from_str(body).unwrap()
+    };
+    let (no, size) = (
+        params["pageNo"].as_u64().unwrap(),
+        params["pageSize"].as_u64().unwrap().min(max_size),
+    );
+    let records: Vec<u64> = ((no - 1) * size..(no * size).min(total)).collect();
+    let data = if report_total {
+        json!({"records": records, "total": total})
+    } else {
+        json!({"list": records})
+    };
+    Some((200, json!({"code": 0, "data": data}).to_string()))
+}
+
+#[tokio::test]
+async fn paginator_walks_all_pages() {
+    let server = page_server(5, true, 100).await;
+    let client = client();
+    let filter = json!({"status": "enabled"});
+    let mut pages = client
+        .paginate::<u64, _>(&server.url, &filter)
+        .unwrap()
+        .with_page_size(2);
+    let mut records = Vec::new();
+    while let Some(record) = pages.next_record().await {
+        records.push(record.unwrap());
+    }
+    assert_eq!(records, vec![0, 1, 2, 3, 4]);
+    assert_eq!(server.hits(), 3);
+    for (i, request) in server.requests().iter().enumerate() {
+        let body: Value = serde_json::from_str(request.split("\r\n\r\n").nth(1).unwrap()).unwrap();
+        assert_eq!(body["pageNo"], i as u64 + 1);
+        assert_eq!(body["pageSize"], 2);
+        assert_eq!(body["status"], "enabled");
+    }
+
+    // total 恰好整除时不会多请求一页空页
+    let server = page_server(4, true, 100).await;
+    let all = client
+        .paginate::<u64, _>(&server.url, &filter)
+        .unwrap()
+        .with_page_size(2)
+        .collect_all()
+        .await
+        .unwrap();
+    assert_eq!(all, vec![0, 1, 2, 3]);
+    assert_eq!(server.hits(), 2);
+}
+
+#[tokio::test]
+async fn paginator_retries_failed_pages() {
+    // 第二页的第一次请求断开连接，POST 分页请求带幂等键，应重试而不是结束
+    let server = FlakyServer::start(|n, request| match n {
+        1 => None,
+        _ => page_reply(request, 5, true, 100),
+    })
+    .await;
+    let client = client();
+    let headers = HashMap::from([(IDEMPOTENCY_KEY.to_string(), "fixed".to_string())]);
+    let all = client
+        .paginate::<u64, _>(&server.url, &())
+        .unwrap()
+        .with_headers(headers)
+        .with_page_size(2)
+        .collect_all()
+        .await
+        .unwrap();
+    assert_eq!(all, vec![0, 1, 2, 3, 4]);
+    assert_eq!(server.hits(), 4);
+
+    // 每页的幂等键不同，重试时不变；调用方给出的固定键被替换
+    let keys: Vec<String> = server
+        .requests()
+        .iter()
+        .map(|r| {
+            r.lines()
+                .find_map(|l| l.strip_prefix("idempotency-key: "))
+                .unwrap()
+                .to_string()
+        })
+        .collect();
+    assert_eq!(keys[1], keys[2]);
+    assert!(keys[0] != keys[1] && keys[2] != keys[3] && keys[0] != keys[3]);
+    assert!(keys.iter().all(|k| k != "fixed"));
+}
+
+#[tokio::test]
+async fn paginator_stops_on_short_page_and_supports_get() {
+    let server = page_server(5, false, 100).await;
+    let client = client();
+    let mut pages = client
+        .paginate::<u64, _>(&server.url, &())
+        .unwrap()
+        .with_method(reqwest::Method::GET)
+        .with_page_size(3);
+    assert_eq!(
+        pages.next_page().await.unwrap().unwrap().records,
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        pages.next_page().await.unwrap().unwrap().records,
+        vec![3, 4]
+    );
+    assert!(pages.next_page().await.is_none());
+    assert_eq!(server.hits(), 2);
+    assert!(server.requests()[1].starts_with("get /?pageno=2&pagesize=3"));
+
+    assert!(client.paginate::<u64, _>(&server.url, &[1, 2]).is_err());
+}
+
+#[tokio::test]
+async fn paginator_stream_follows_total_when_page_size_is_capped() {
+    // 服务端每页最多 2 条，请求 3 条时也不应在第一页提前结束
+    let server = page_server(5, true, 2).await;
+    let client = client();
+    let records: Vec<u64> = client
+        .paginate::<u64, _>(&server.url, &())
+        .unwrap()
+        .with_page_size(3)
+        .into_stream()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(records, vec![0, 1, 2, 3, 4]);
+    assert_eq!(server.hits(), 3);
+}
@@ -0,0 +1,207 @@
+use image::GrayImage;
+use usls::{Bbox, Keypoint, Mask, Y};
+use yolo_vision::postprocess::{
+    class_names_from_ys, concat_sources, detections_from_y, instances_from_y, nms,
+    y_from_instances, Detection, Instance, NmsConfig, NmsKind, SoftNmsMethod,
+};
+use yolo_vision::utils::geometry::BoxF;
+
+fn det(class_id: usize, score: f32, xyxy: [f32; 4]) -> Detection {
+    Detection::new(BoxF::from_xyxy(xyxy), score, class_id)
+}
+
+/// 两个高度重叠的框 + 一个独立的框，类别可配置
+fn cluster(classes: [usize; 3]) -> Vec<Detection> {
+    vec![
+        det(classes[0], 0.9, [0., 0., 10., 10.]),
+        det(classes[1], 0.8, [1., 0., 11., 10.]),
+        det(classes[2], 0.7, [50., 50., 60., 60.]),
+    ]
+}
+
+fn scores(dets: &[Detection]) -> Vec<f32> {
+    dets.iter().map(|d| d.score).collect()
+}
+
+#[test]
+fn hard_class_aware_and_agnostic() {
+    let dets = cluster([0, 0, 0]);
+    let kept = nms(&dets, &NmsConfig::default());
+    assert_eq!(scores(&kept), vec![0.9, 0.7]);
+
+    // 不同类别的重叠框在区分类别时都保留
+    let dets = cluster([0, 1, 0]);
+    assert_eq!(nms(&dets, &NmsConfig::default()).len(), 3);
+    let agnostic = NmsConfig::default().with_class_agnostic(true);
+    assert_eq!(scores(&nms(&dets, &agnostic)), vec![0.9, 0.7]);
+}
+
+#[test]
+fn top_k_and_score_threshold() {
+    let dets = cluster([0, 1, 2]);
+    let config = NmsConfig::default().with_top_k(Some(2));
+    assert_eq!(scores(&nms(&dets, &config)), vec![0.9, 0.8]);
+
+    let config = NmsConfig::default().with_score_threshold(0.75);
+    assert_eq!(scores(&nms(&dets, &config)), vec![0.9, 0.8]);
+
+    let config = NmsConfig::default()
+        .with_class_agnostic(true)
+        .with_pre_top_k(Some(1));
+    assert_eq!(scores(&nms(&dets, &config)), vec![0.9]);
+}
+
+#[test]
+fn soft_nms_decays_instead_of_removing() {
+    let dets = cluster([0, 0, 0]);
+    let iou = dets[0].bbox.iou(&dets[1].bbox);
+
+    let config = NmsConfig::default().with_kind(NmsKind::Soft(SoftNmsMethod::Linear));
+    let kept = nms(&dets, &config);
+    assert_eq!(kept.len(), 3);
+    assert!((kept[2].score - 0.8 * (1.0 - iou)).abs() < 1e-5);
+
+    let config = NmsConfig::default()
+        .with_kind(NmsKind::Soft(SoftNmsMethod::Gaussian { sigma: 0.5 }))
+        .with_score_threshold(0.1);
+    let kept = nms(&dets, &config);
+    let expected = 0.8 * (-(iou * iou) / 0.5).exp();
+    assert!(kept.iter().any(|d| (d.score - expected).abs() < 1e-5));
+    // 不重叠的框分数不变
+    assert!(kept.iter().any(|d| d.score == 0.7));
+}
+
+#[test]
+fn diou_nms_keeps_distant_centers() {
+    // 一个大框与其内部偏角落的小框，IoU 不低但中心点距离较大
+    let dets = vec![
+        det(0, 0.9, [0., 0., 20., 20.]),
+        det(0, 0.8, [0., 0., 12., 12.]),
+    ];
+    let iou = dets[0].bbox.iou(&dets[1].bbox);
+    let threshold = iou - 0.01;
+    let hard = NmsConfig::default().with_iou_threshold(threshold);
+    assert_eq!(nms(&dets, &hard).len(), 1);
+    let diou = hard.with_kind(NmsKind::DIoU);
+    assert_eq!(nms(&dets, &diou).len(), 2);
+}
+
+#[test]
+fn matrix_nms_decays_duplicates() {
+    let dets = cluster([0, 0, 0]);
+    let iou = dets[0].bbox.iou(&dets[1].bbox);
+    let config = NmsConfig::default().with_kind(NmsKind::Matrix {
+        gaussian: false,
+        sigma: 2.0,
+    });
+    let kept = nms(&dets, &config);
+    assert_eq!(kept[0].score, 0.9);
+    assert!(kept.iter().any(|d| d.score == 0.7));
+    assert!(kept
+        .iter()
+        .any(|d| (d.score - 0.8 * (1.0 - iou)).abs() < 1e-5));
+
+    let config = config.with_score_threshold(0.5);
+    assert_eq!(nms(&dets, &config).len(), 2);
+}
+
+/// 与 SOLOv2 论文公式 decay_j = min_i exp(-sigma × (IoU_ij² - comp_i²)) 对照
+#[test]
+fn matrix_nms_gaussian_matches_paper() {
+    let dets = vec![
+        det(0, 0.9, [0., 0., 10., 10.]),
+        det(0, 0.8, [1., 0., 11., 10.]),
+        det(0, 0.7, [2., 0., 12., 10.]),
+    ];
+    let iou = |i: usize, j: usize| dets[i].bbox.iou(&dets[j].bbox);
+    let sigma = 2.0f32;
+    let comp1 = iou(0, 1);
+    let decay1 = (-sigma * iou(0, 1).powi(2)).exp();
+    let decay2 = (-sigma * iou(0, 2).powi(2))
+        .exp()
+        .min((-sigma * (iou(1, 2).powi(2) - comp1.powi(2))).exp());
+
+    let config = NmsConfig::default()
+        .with_score_threshold(0.0)
+        .with_kind(NmsKind::Matrix {
+            gaussian: true,
+            sigma,
+        });
+    let kept = nms(&dets, &config);
+    let score = |x1: f32| kept.iter().find(|d| d.bbox.x1 == x1).unwrap().score;
+    assert_eq!(score(0.0), 0.9);
+    assert!((score(1.0) - 0.8 * decay1).abs() < 1e-5);
+    assert!((score(2.0) - 0.7 * decay2).abs() < 1e-5);
+    // IoU ≈ 0.82 时 sigma = 2 的衰减约为 0.26
+    assert!((decay1 - 0.262).abs() < 1e-3, "{}", decay1);
+}
+
+#[test]
+fn fuse_multiple_sources() {
+    let a = [det(0, 0.9, [0., 0., 10., 10.])];
+    let b = [
+        det(0, 0.95, [0.5, 0., 10.5, 10.]),
+        det(1, 0.6, [30., 30., 40., 40.]),
+    ];
+    let merged = concat_sources(&[&a, &b]);
+    assert_eq!(
+        merged.iter().map(|d| d.source).collect::<Vec<_>>(),
+        vec![0, 1, 1]
+    );
+
+    let kept = nms(&merged, &NmsConfig::default());
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].source, 1);
+    assert_eq!(kept[0].score, 0.95);
+}
+
+#[test]
+fn unknown_class_ids_are_dropped() {
+    let bbox = |id: isize| {
+        Bbox::default()
+            .with_xyxy(0., 0., 10., 10.)
+            .with_confidence(0.9)
+            .with_id(id)
+            .with_name("x")
+    };
+    let y = Y::default().with_bboxes(&[bbox(-1), bbox(2)]);
+    let dets = detections_from_y(&y);
+    assert_eq!(dets.len(), 1);
+    assert_eq!(dets[0].class_id, 2);
+    assert_eq!(
+        class_names_from_ys([&y]).into_keys().collect::<Vec<_>>(),
+        vec![2]
+    );
+}
+
+#[test]
+fn instances_keep_masks_and_keypoints_aligned() {
+    let bbox = |id: isize| {
+        Bbox::default()
+            .with_xyxy(0., 0., 10., 10.)
+            .with_confidence(0.9)
+            .with_id(id)
+    };
+    let mask = |v: u8| Mask::default().with_mask(GrayImage::from_pixel(4, 4, image::Luma([v])));
+    let kpts = |x: f32| vec![Keypoint::default().with_xy(x, 0.).with_confidence(1.)];
+    let y = Y::default()
+        .with_bboxes(&[bbox(-1), bbox(2)])
+        .with_masks(&[mask(1), mask(2)])
+        .with_keypoints(&[kpts(1.), kpts(2.)]);
+
+    let instances = instances_from_y(&y);
+    assert_eq!(instances.len(), 1);
+    assert_eq!(instances[0].detection.class_id, 2);
+    assert_eq!(instances[0].mask.as_ref().unwrap().get(0, 0), 2);
+    assert_eq!(instances[0].keypoints.as_ref().unwrap()[0].x(), 2.);
+
+    // 缺失的掩码以空掩码补齐
+    let plain = Instance::new(det(1, 0.5, [0., 0., 5., 5.]));
+    let names = [(2, "car".to_string())].into_iter().collect();
+    let y = y_from_instances(&[instances[0].clone(), plain], &names);
+    let masks = y.masks().unwrap();
+    assert_eq!(masks.len(), 2);
+    assert_eq!(masks[0].name().map(|n| n.as_str()), Some("car"));
+    assert_eq!(masks[1].mask().get_pixel(0, 0)[0], 0);
+    assert_eq!(y.keypoints().unwrap()[1].len(), 0);
+}
@@ -0,0 +1,125 @@
+use chrono::{Local, TimeZone};
+use image::RgbImage;
+use std::time::{Duration, Instant};
+use usls::{Bbox, Y};
+use yolo_vision::annotation::{
+    AnnotationStyle, FpsMeter, Overlay, OverlayConfig, OverlayPosition, OverlayStats, ZoneConfig,
+};
+use yolo_vision::utils::geometry::Anchor;
+
+fn bbox(x1: f32, y1: f32, x2: f32, y2: f32, id: isize, name: &str) -> Bbox {
+    Bbox::default()
+        .with_xyxy(x1, y1, x2, y2)
+        .with_confidence(0.9)
+        .with_id(id)
+        .with_name(name)
+}
+
+fn frame() -> Y {
+    Y::default().with_bboxes(&[
+        bbox(10.0, 10.0, 30.0, 60.0, 0, "person"),
+        bbox(60.0, 10.0, 80.0, 40.0, 0, "person"),
+        bbox(50.0, 50.0, 90.0, 90.0, 2, "car"),
+    ])
+}
+
+fn config() -> OverlayConfig {
+    OverlayConfig {
+        zones: vec![ZoneConfig {
+            name: "left".to_string(),
+            polygon: vec![[0.0, 0.0], [0.5, 0.0], [0.5, 1.0], [0.0, 1.0]],
+            anchor: Anchor::BottomCenter,
+        }],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn overlay_lines_and_toggles() {
+    let overlay = Overlay::new(config(), "gate-1", &AnnotationStyle::default()).unwrap();
+    let now = Local.with_ymd_and_hms(2024, 5, 6, 7, 8, 9).unwrap();
+    let stats = OverlayStats {
+        fps: 24.96,
+        latency: Duration::from_millis(85),
+        pts: Some(Duration::from_millis(3_723_004)),
+    };
+    assert_eq!(
+        overlay.lines(&frame(), 100, 100, &stats, now),
+        vec![
+            "2024-05-06 07:08:09.000  PTS 01:02:03.004",
+            "gate-1",
+            "FPS 25.0  Latency 85 ms",
+            "car: 1  person: 2",
+            "left: 1",
+        ]
+    );
+
+    let overlay = Overlay::new(
+        OverlayConfig {
+            timestamp: false,
+            stream_name: false,
+            latency: false,
+            class_counts: false,
+            ..config()
+        },
+        "gate-1",
+        &AnnotationStyle::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        overlay.lines(&frame(), 100, 100, &stats, now),
+        vec!["FPS 25.0", "left: 1"]
+    );
+}
+
+#[test]
+fn zone_counts_use_anchor() {
+    let mut cfg = config();
+    cfg.zones.push(ZoneConfig {
+        name: "top".to_string(),
+        polygon: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 0.5], [0.0, 0.5]],
+        anchor: Anchor::Center,
+    });
+    let overlay = Overlay::new(cfg, "s", &AnnotationStyle::default()).unwrap();
+    assert_eq!(
+        overlay.zone_counts(&frame(), 100, 100),
+        vec![("left".to_string(), 1), ("top".to_string(), 2)]
+    );
+    // 归一化坐标随分辨率缩放
+    assert_eq!(overlay.zone_counts(&frame(), 200, 200)[0].1, 3);
+}
+
+#[test]
+fn fps_meter_window() {
+    let mut meter = FpsMeter::new(Duration::from_secs(2));
+    assert_eq!(meter.fps(), 0.0);
+    let t0 = Instant::now();
+    for i in 0..=10 {
+        meter.record_at(t0 + Duration::from_millis(100 * i), 4);
+    }
+    assert!((meter.fps() - 40.0).abs() < 1e-3);
+
+    // 超出窗口的记录被丢弃
+    meter.record_at(t0 + Duration::from_secs(10), 4);
+    assert_eq!(meter.fps(), 0.0);
+}
+
+#[test]
+fn overlay_drawn_in_configured_corner() {
+    let overlay = Overlay::new(
+        OverlayConfig {
+            position: OverlayPosition::BottomRight,
+            background: [200, 100, 0],
+            opacity: 1.0,
+            ..config()
+        },
+        "gate-1",
+        &AnnotationStyle::default(),
+    )
+    .unwrap();
+    let mut img = RgbImage::new(400, 300);
+    overlay.draw(&mut img, &["gate-1".to_string()]);
+    assert_eq!(img.get_pixel(389, 289).0, [200, 100, 0]);
+    assert_eq!(img.get_pixel(10, 10).0, [0, 0, 0]);
+    assert_eq!(img.get_pixel(395, 295).0, [0, 0, 0]);
+}
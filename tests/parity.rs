@@ -0,0 +1,202 @@
+use usls::{models::YOLO, Options};
+use yolo_vision::backend::{DnnConfig, OpenCvDnn, OutputLayout};
+use yolo_vision::eval::parity::{
+    check_goldens, collect, compare, compare_backends, match_detections, UPDATE_GOLDENS_ENV,
+};
+use yolo_vision::eval::{Detection, Goldens, ParityTolerance};
+
+const ASSETS: [&str; 6] = [
+    "assets/bus.jpg",
+    "assets/cat.jpg",
+    "assets/dog.jpg",
+    "assets/fruits.jpg",
+    "assets/101.jpg",
+    "assets/starryberry.jpg",
+];
+
+fn det(class_id: usize, score: f32, xyxy: [f32; 4]) -> Detection {
+    Detection {
+        class_id,
+        score,
+        xyxy,
+    }
+}
+
+fn goldens(entries: &[(&str, Vec<Detection>)]) -> Goldens {
+    Goldens {
+        images: entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect(),
+    }
+}
+
+#[test]
+fn matches_within_tolerance() {
+    let tol = ParityTolerance::default();
+    let expected = [
+        det(0, 0.90, [10.0, 10.0, 110.0, 210.0]),
+        det(0, 0.80, [300.0, 10.0, 400.0, 210.0]),
+        det(5, 0.70, [0.0, 300.0, 500.0, 600.0]),
+    ];
+    // 顺序打乱、坐标和置信度轻微抖动
+    let actual = [
+        det(5, 0.72, [1.0, 301.0, 500.0, 600.0]),
+        det(0, 0.81, [301.0, 10.0, 401.0, 211.0]),
+        det(0, 0.88, [10.0, 11.0, 110.0, 210.0]),
+    ];
+    let diff = match_detections(&expected, &actual, &tol);
+    assert_eq!(diff.matched.len(), 3);
+    assert_eq!(diff.failures(&tol), 0);
+    assert!(!diff.is_identical());
+    assert!(match_detections(&expected, &expected, &tol).is_identical());
+}
+
+#[test]
+fn reports_drift_missing_and_extra() {
+    let tol = ParityTolerance::default();
+    let expected = [
+        det(0, 0.90, [0.0, 0.0, 100.0, 100.0]),
+        det(2, 0.60, [200.0, 200.0, 300.0, 300.0]),
+        // 阈值附近的框缺失不算失败
+        det(3, 0.26, [400.0, 0.0, 450.0, 50.0]),
+    ];
+    let actual = [
+        // IoU 0.8，置信度差 0.2
+        det(0, 0.70, [0.0, 0.0, 100.0, 80.0]),
+        // 位置相同但类别不同：缺失 + 多出
+        det(7, 0.60, [200.0, 200.0, 300.0, 300.0]),
+    ];
+    let diff = match_detections(&expected, &actual, &tol);
+    assert_eq!(diff.matched.len(), 1);
+    assert!((diff.matched[0].iou - 0.8).abs() < 1e-4);
+    assert_eq!(diff.drifted(&tol).count(), 1);
+    assert_eq!(diff.missing.len(), 2);
+    assert_eq!(diff.extra.len(), 1);
+    assert_eq!(diff.failures(&tol), 3);
+
+    let loose = ParityTolerance {
+        iou: 0.75,
+        score: 0.25,
+        ..tol
+    };
+    assert_eq!(diff.failures(&loose), 2);
+}
+
+#[test]
+fn report_lists_only_differences() {
+    let expected = goldens(&[
+        ("a.jpg", vec![det(0, 0.9, [0.0, 0.0, 10.0, 10.0])]),
+        ("b.jpg", vec![det(1, 0.5, [0.0, 0.0, 10.0, 10.0])]),
+        ("c.jpg", vec![]),
+    ]);
+    let actual = goldens(&[
+        ("a.jpg", vec![det(0, 0.9, [0.0, 0.0, 10.0, 10.0])]),
+        ("b.jpg", vec![det(1, 0.2, [50.0, 50.0, 60.0, 60.0])]),
+        ("d.jpg", vec![]),
+    ]);
+    let report = compare(&expected, &actual, ParityTolerance::default());
+    assert_eq!(report.missing_images, vec!["c.jpg"]);
+    assert_eq!(report.extra_images, vec!["d.jpg"]);
+    // b.jpg 缺失 1 个，多出的框低于 ignore_below；c.jpg、d.jpg 各 1
+    assert_eq!(report.failures(), 3);
+    assert!(!report.is_ok());
+
+    let text = report.to_string();
+    assert!(!text.contains("a.jpg"));
+    assert!(text.contains("b.jpg: 0 matched, 0 drifted, 1 missing, 1 extra"));
+    assert!(text.contains("  - class 1   score 0.500 [0.0, 0.0, 10.0, 10.0]\n"));
+    assert!(text.contains("  + class 1   score 0.200 [50.0, 50.0, 60.0, 60.0] (ignored)\n"));
+    assert!(text.contains("c.jpg: missing from actual outputs"));
+    assert!(text.ends_with("2 images, 3 failures (match iou 0.5, iou 0.9, score ±0.05)"));
+}
+
+#[test]
+fn goldens_round_trip() {
+    let path = std::env::temp_dir().join(format!("parity-{}.json", std::process::id()));
+    let g = goldens(&[
+        ("bus.jpg", vec![det(0, 0.875, [1.5, 2.0, 30.25, 40.0])]),
+        ("empty.jpg", vec![]),
+    ]);
+    g.save(&path).unwrap();
+    assert_eq!(Goldens::load(&path).unwrap(), g);
+
+    let report = check_goldens(&path, &g, ParityTolerance::default()).unwrap();
+    assert!(report.is_ok());
+    std::fs::remove_file(&path).unwrap();
+
+    if std::env::var_os(UPDATE_GOLDENS_ENV).is_none() {
+        let err = check_goldens(&path, &g, ParityTolerance::default()).unwrap_err();
+        assert!(err.to_string().contains(UPDATE_GOLDENS_ENV));
+    }
+}
+
+fn yolo(version: f32) -> anyhow::Result<YOLO> {
+    let options = Options::yolo()
+        .with_model_task("det".try_into()?)
+        .with_model_version(version.into())
+        .with_model_scale("n".try_into()?)
+        .with_model_device("cpu:0".try_into()?)
+        .with_class_confs(&[0.25])
+        .with_class_names(&usls::COCO_CLASS_NAMES_80)
+        .commit()?;
+    YOLO::try_from(options)
+}
+
+/// `UPDATE_GOLDENS=1 cargo test --test parity -- --ignored` 重新生成金标准
+#[test]
+#[ignore = "downloads YOLOv8n weights and runs CPU inference"]
+fn yolov8n_matches_goldens() {
+    let mut model = yolo(8.0).unwrap();
+    let outputs = collect(&mut model, &ASSETS).unwrap();
+    let report = check_goldens(
+        "tests/fixtures/parity/yolov8n-det.json",
+        &outputs,
+        ParityTolerance::default(),
+    )
+    .unwrap();
+    assert!(report.is_ok(), "\n{}", report);
+}
+
+/// 两个后端预处理和 NMS 实现不同，容差放宽
+#[test]
+#[ignore = "set PARITY_ONNX to the YOLOv8n ONNX file used by usls"]
+fn opencv_dnn_matches_usls() {
+    let onnx = std::env::var("PARITY_ONNX")
+        .expect("PARITY_ONNX must point to the YOLOv8n ONNX file used by usls");
+    let mut usls = yolo(8.0).unwrap();
+    let mut dnn = OpenCvDnn::new(DnnConfig {
+        model: onnx,
+        layout: Some(OutputLayout::V8),
+        num_classes: Some(80),
+        class_names: usls::COCO_CLASS_NAMES_80
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        ..Default::default()
+    })
+    .unwrap();
+    let tolerance = ParityTolerance {
+        iou: 0.85,
+        score: 0.1,
+        ..Default::default()
+    };
+    let report = compare_backends(&mut usls, &mut dnn, &ASSETS, tolerance).unwrap();
+    assert!(report.is_ok(), "\n{}", report);
+}
+
+/// 升级模型版本前的回归检查：两个版本在高置信度目标上应当一致
+#[test]
+#[ignore = "downloads YOLOv8n and YOLO11n weights and runs CPU inference"]
+fn yolo11n_agrees_with_yolov8n() {
+    let mut v8 = yolo(8.0).unwrap();
+    let mut v11 = yolo(11.0).unwrap();
+    let tolerance = ParityTolerance {
+        iou: 0.7,
+        score: 0.25,
+        ignore_below: 0.5,
+        ..Default::default()
+    };
+    let report = compare_backends(&mut v8, &mut v11, &ASSETS, tolerance).unwrap();
+    assert!(report.is_ok(), "\n{}", report);
+}
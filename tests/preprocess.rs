@@ -0,0 +1,257 @@
+use anyhow::Result;
+use image::{DynamicImage, GrayImage, Luma, Rgb, RgbImage};
+use proptest::prelude::*;
+use usls::{Bbox, Keypoint, Mask, Y};
+use yolo_vision::backend::{
+    ChannelOrder, InferenceBackend, PreprocessConfig, Preprocessed, Preprocessor, ResizeMode,
+    Transform,
+};
+use yolo_vision::utils::geometry::BoxF;
+
+const MODES: [ResizeMode; 3] = [
+    ResizeMode::Letterbox,
+    ResizeMode::Stretch,
+    ResizeMode::CenterCrop,
+];
+
+fn mode() -> impl Strategy<Value = ResizeMode> {
+    prop::sample::select(MODES.to_vec())
+}
+
+fn close(a: f32, b: f32, tol: f32) -> bool {
+    (a - b).abs() <= tol * a.abs().max(b.abs()).max(1.0)
+}
+
+proptest! {
+    #[test]
+    fn point_and_box_round_trip(
+        w in 16u32..2000, h in 16u32..2000, dw in 32u32..1280, dh in 32u32..1280,
+        mode in mode(), fx in 0f32..1.0, fy in 0f32..1.0, fw in 0f32..1.0, fh in 0f32..1.0,
+    ) {
+        let t = Transform::new(w, h, [dw, dh], mode);
+        let (x, y) = (fx * w as f32, fy * h as f32);
+        let (ix, iy) = t.forward_point(x, y);
+        let (bx, by) = t.inverse_point(ix, iy);
+        prop_assert!(close(bx, x, 1e-5) && close(by, y, 1e-5));
+
+        let b = BoxF::new(x, y, x + fw * (w as f32 - x), y + fh * (h as f32 - y));
+        let back = t.inverse_box(&t.forward_box(&b));
+        for (p, q) in back.xyxy().iter().zip(b.xyxy()) {
+            prop_assert!(close(*p, q, 1e-5));
+        }
+
+        let kpts = [[x, y, 0.7]];
+        let (kx, ky) = t.forward_point(x, y);
+        let back = t.inverse_keypoints(&[[kx, ky, 0.7]]);
+        prop_assert!(close(back[0][0], kpts[0][0], 1e-5) && close(back[0][1], kpts[0][1], 1e-5));
+        prop_assert_eq!(back[0][2], 0.7);
+    }
+
+    /// 原图四角精确落在缩放后图像的像素边界上
+    #[test]
+    fn content_region_is_pixel_exact(
+        w in 16u32..2000, h in 16u32..2000, dw in 32u32..1280, dh in 32u32..1280, mode in mode(),
+    ) {
+        let t = Transform::new(w, h, [dw, dh], mode);
+        let (rw, rh) = t.resized();
+        let full = t.forward_box(&BoxF::new(0.0, 0.0, w as f32, h as f32));
+        prop_assert!(close(full.x1, t.offset_x, 1e-5));
+        prop_assert!(close(full.y1, t.offset_y, 1e-5));
+        prop_assert!(close(full.x2, t.offset_x + rw as f32, 1e-5));
+        prop_assert!(close(full.y2, t.offset_y + rh as f32, 1e-5));
+        prop_assert_eq!((t.offset_x.fract(), t.offset_y.fract()), (0.0, 0.0));
+
+        match mode {
+            ResizeMode::Stretch => prop_assert_eq!((rw, rh), (dw, dh)),
+            // 填充模式下内容完整落在输入内，至少一边贴满
+            ResizeMode::Letterbox => {
+                prop_assert!(rw <= dw && rh <= dh && (rw == dw || rh == dh));
+                prop_assert!(t.offset_x >= 0.0 && t.offset_y >= 0.0);
+            }
+            // 裁剪模式下输入被内容完全覆盖
+            ResizeMode::CenterCrop => {
+                prop_assert!(rw >= dw && rh >= dh && (rw == dw || rh == dh));
+                prop_assert!(t.offset_x <= 0.0 && t.offset_y <= 0.0);
+            }
+        }
+    }
+
+    /// 原图掩码正向栅格化到输入后再逆变换，只有边界附近的像素可能不同
+    #[test]
+    fn mask_round_trip(
+        w in 16u32..300, h in 16u32..300, dw in 32u32..320, dh in 32u32..320, mode in mode(),
+        fx in 0f32..0.5, fy in 0f32..0.5, fw in 0.2f32..0.5, fh in 0.2f32..0.5,
+    ) {
+        let t = Transform::new(w, h, [dw, dh], mode);
+        let b = BoxF::new(fx * w as f32, fy * h as f32, (fx + fw) * w as f32, (fy + fh) * h as f32);
+        let inside = |x: f32, y: f32| x >= b.x1 && x < b.x2 && y >= b.y1 && y < b.y2;
+
+        let input = GrayImage::from_fn(dw, dh, |x, y| {
+            let (ox, oy) = t.inverse_point(x as f32 + 0.5, y as f32 + 0.5);
+            Luma([if inside(ox, oy) { 255 } else { 0 }])
+        });
+        let back = t.inverse_mask(&input);
+        prop_assert_eq!(back.dimensions(), (w, h));
+
+        // 允许误差：一个输入像素对应的原图宽度再加一个像素
+        let (mx, my) = (1.0 / t.scale_x + 1.0, 1.0 / t.scale_y + 1.0);
+        let visible = t.inverse_box(&BoxF::new(0.0, 0.0, dw as f32, dh as f32));
+        for (x, y, p) in back.enumerate_pixels() {
+            let (cx, cy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let near_edge = (cx - b.x1).abs() < mx || (cx - b.x2).abs() < mx
+                || (cy - b.y1).abs() < my || (cy - b.y2).abs() < my;
+            let cropped = cx < visible.x1 || cx >= visible.x2 || cy < visible.y1 || cy >= visible.y2;
+            if cropped {
+                prop_assert_eq!(p[0], 0);
+            } else if !near_edge {
+                prop_assert_eq!(p[0] == 255, inside(cx, cy));
+            }
+        }
+    }
+}
+
+/// 非正方形原图：旧实现按统一比例逆变换，底边会偏移若干像素
+#[test]
+fn letterbox_non_square_is_exact() {
+    let t = Transform::new(1000, 333, [640, 640], ResizeMode::Letterbox);
+    assert_eq!(t.resized(), (640, 213));
+    assert_eq!((t.offset_x, t.offset_y), (0.0, 213.0));
+    let full = t.inverse_box(&BoxF::new(0.0, 213.0, 640.0, 426.0));
+    assert!((full.y2 - 333.0).abs() < 1e-3);
+    assert!((full.x2 - 1000.0).abs() < 1e-3);
+}
+
+#[test]
+fn resize_modes_place_content() {
+    // 左半红、右半蓝
+    let image = DynamicImage::ImageRgb8(RgbImage::from_fn(400, 200, |x, _| {
+        if x < 200 {
+            Rgb([255, 0, 0])
+        } else {
+            Rgb([0, 0, 255])
+        }
+    }));
+    let config = PreprocessConfig::default()
+        .with_size(100, 100)
+        .with_pad_color([1, 2, 3]);
+
+    let (canvas, t) = Preprocessor::new(config).resize(&image);
+    assert_eq!(canvas.dimensions(), (100, 100));
+    assert_eq!((t.offset_x, t.offset_y), (0.0, 25.0));
+    assert_eq!(canvas.get_pixel(50, 10).0, [1, 2, 3]);
+    assert_eq!(canvas.get_pixel(10, 50).0, [255, 0, 0]);
+    assert_eq!(canvas.get_pixel(90, 50).0, [0, 0, 255]);
+    assert_eq!(canvas.get_pixel(50, 90).0, [1, 2, 3]);
+
+    let (canvas, t) = Preprocessor::new(config.with_mode(ResizeMode::Stretch)).resize(&image);
+    assert_eq!((t.scale_x, t.scale_y), (0.25, 0.5));
+    assert_eq!(canvas.get_pixel(10, 0).0, [255, 0, 0]);
+    assert_eq!(canvas.get_pixel(90, 99).0, [0, 0, 255]);
+
+    // 裁掉左右各 100 像素
+    let (canvas, t) = Preprocessor::new(config.with_mode(ResizeMode::CenterCrop)).resize(&image);
+    assert_eq!(t.resized(), (200, 100));
+    assert_eq!((t.offset_x, t.offset_y), (-50.0, 0.0));
+    assert_eq!(canvas.get_pixel(0, 0).0, [255, 0, 0]);
+    assert_eq!(canvas.get_pixel(99, 99).0, [0, 0, 255]);
+    assert_eq!(t.inverse_point(0.0, 0.0), (100.0, 0.0));
+
+    assert_eq!(
+        ResizeMode::try_from("Crop").unwrap(),
+        ResizeMode::CenterCrop
+    );
+    assert!(ResizeMode::try_from("fit").is_err());
+}
+
+#[test]
+fn blob_normalization_and_channel_order() {
+    let image = DynamicImage::ImageRgb8(RgbImage::from_pixel(2, 2, Rgb([255, 0, 51])));
+    let config = PreprocessConfig::default().with_size(2, 2);
+
+    let (data, transforms) = Preprocessor::new(config).blob(&[image.clone(), image.clone()]);
+    assert_eq!(data.len(), 2 * 3 * 4);
+    assert_eq!(transforms.len(), 2);
+    assert_eq!(
+        &data[..12],
+        &[1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.2, 0.2, 0.2, 0.2]
+    );
+    assert_eq!(&data[12..], &data[..12]);
+
+    let config = config
+        .with_channel_order(ChannelOrder::Bgr)
+        .with_normalization([0.5, 0.5, 0.0], [0.5, 0.25, 0.1]);
+    let (data, _) = Preprocessor::new(config).blob(&[image]);
+    let expected = [2.0, -2.0, 1.0];
+    for (c, e) in expected.iter().enumerate() {
+        for v in &data[c * 4..(c + 1) * 4] {
+            assert!((v - e).abs() < 1e-5, "channel {}: {} != {}", c, v, e);
+        }
+    }
+}
+
+/// 在输入坐标系中给出固定结果的后端
+struct FixedBackend;
+
+impl InferenceBackend for FixedBackend {
+    fn forward(&mut self, xs: &[DynamicImage]) -> Result<Vec<Y>> {
+        // 收到的帧已是输入尺寸
+        assert!(xs.iter().all(|x| (x.width(), x.height()) == (640, 640)));
+        let bbox = |x1, y1, x2, y2| {
+            Bbox::default()
+                .with_xyxy(x1, y1, x2, y2)
+                .with_confidence(0.9)
+                .with_id(0)
+        };
+        // 内容区域为 y ∈ [213, 426)
+        let mut mask = GrayImage::new(640, 640);
+        for y in 213..426 {
+            for x in 0..640 {
+                mask.put_pixel(x, y, Luma([255]));
+            }
+        }
+        let kpt = |x, y| vec![Keypoint::default().with_xy(x, y).with_confidence(1.0)];
+        Ok(xs
+            .iter()
+            .map(|_| {
+                Y::default()
+                    // 覆盖整个内容区域 / 完全落在填充区域
+                    .with_bboxes(&[bbox(0.0, 213.0, 640.0, 426.0), bbox(0.0, 0.0, 640.0, 100.0)])
+                    .with_keypoints(&[kpt(320.0, 319.5), kpt(0.0, 50.0)])
+                    .with_masks(&[
+                        Mask::default().with_mask(mask.clone()),
+                        Mask::default().with_mask(GrayImage::new(640, 640)),
+                    ])
+            })
+            .collect())
+    }
+
+    fn batch(&self) -> usize {
+        1
+    }
+}
+
+#[test]
+fn preprocessed_backend_maps_results_back() {
+    let mut backend = Preprocessed::new(Box::new(FixedBackend), PreprocessConfig::default());
+    let image = DynamicImage::ImageRgb8(RgbImage::new(1000, 333));
+    let ys = backend.forward(&[image]).unwrap();
+    assert_eq!(ys.len(), 1);
+
+    let bboxes = ys[0].bboxes().unwrap();
+    assert_eq!(bboxes.len(), 1);
+    let b = &bboxes[0];
+    for (a, e) in [b.xmin(), b.ymin(), b.xmax(), b.ymax()]
+        .into_iter()
+        .zip([0.0, 0.0, 1000.0, 333.0])
+    {
+        assert!((a - e).abs() < 1e-3, "{} != {}", a, e);
+    }
+
+    let kpts = ys[0].keypoints().unwrap();
+    assert!((kpts[0][0].x() - 500.0).abs() < 1e-3);
+    assert!((kpts[0][0].y() - 166.5).abs() < 1e-3);
+
+    let masks = ys[0].masks().unwrap();
+    assert_eq!(masks[0].mask().dimensions(), (1000, 333));
+    assert!(masks[0].mask().pixels().all(|p| p[0] == 255));
+}
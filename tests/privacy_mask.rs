@@ -0,0 +1,180 @@
+use image::{DynamicImage, RgbImage};
+use usls::{Bbox, Keypoint, Y};
+use yolo_vision::misc::av_convert::{avframe_to_image, FrameConverter, PixelFormat};
+use yolo_vision::misc::avio::decode_frames;
+use yolo_vision::privacy_mask::{PrivacyMask, PrivacyMaskConfig, PrivacyMaskMethod};
+use yolo_vision::utils::geometry::Anchor;
+
+fn config(method: PrivacyMaskMethod) -> PrivacyMaskConfig {
+    PrivacyMaskConfig {
+        polygons: vec![vec![[0.25, 0.25], [0.75, 0.25], [0.5, 0.75]]],
+        method,
+        anchor: Anchor::BottomCenter,
+    }
+}
+
+/// 带纹理的测试图像，`shift` 不同时纹理随之平移，模拟运动画面
+fn textured(width: u32, height: u32, shift: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        let x = x + shift;
+        image::Rgb([
+            (x * 37 % 256) as u8,
+            (y * 59 % 256) as u8,
+            ((x ^ y) * 13 % 256) as u8,
+        ])
+    }))
+}
+
+/// 模拟标注：在画面上画一条贯穿遮挡区域的横线
+fn annotate(x: DynamicImage) -> DynamicImage {
+    let mut rgb = x.to_rgb8();
+    let y = rgb.height() / 2;
+    for x in 0..rgb.width() {
+        rgb.put_pixel(x, y, image::Rgb([0, 255, 0]));
+    }
+    DynamicImage::ImageRgb8(rgb)
+}
+
+/// 掩码内部像素：周围 `margin` 像素内全部被遮挡，避开色度下采样造成的边缘混色
+fn interior(m: &[u8], w: u32, h: u32, margin: i64) -> Vec<(u32, u32)> {
+    let inside = |x: i64, y: i64| {
+        x >= 0 && y >= 0 && x < w as i64 && y < h as i64 && m[(y * w as i64 + x) as usize] > 0
+    };
+    (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .filter(|&(x, y)| {
+            (-margin..=margin)
+                .all(|dy| (-margin..=margin).all(|dx| inside(x as i64 + dx, y as i64 + dy)))
+        })
+        .collect()
+}
+
+#[test]
+fn masked_pixels_constant_in_encoded_output() {
+    let color = [12, 34, 56];
+    let mask = PrivacyMask::new(config(PrivacyMaskMethod::Fill { color }));
+    let (w, h) = (320, 180);
+
+    // 与 main.rs 的输出路径一致：推理前遮挡 -> 标注 -> 输出前再次遮挡 -> YUV420P -> rsmedia 编码
+    let path = std::env::temp_dir().join(format!("privacy-mask-{}.mp4", std::process::id()));
+    let mut encoder = rsmedia::EncoderBuilder::new(&path, w as usize, h as usize)
+        .with_codec_name("libx264".to_string())
+        .build()
+        .unwrap();
+    let mut converter = FrameConverter::new(w as i32, h as i32, PixelFormat::Yuv420p);
+    let n = 10;
+    for i in 0..n {
+        let xs = mask.apply(vec![textured(w, h, i * 7)]).unwrap();
+        let frames = mask.apply(xs.into_iter().map(annotate).collect()).unwrap();
+        let yuv = converter.convert(&frames[0].to_rgb8()).unwrap();
+        encoder.encode_raw(yuv).unwrap();
+    }
+    encoder.finish().unwrap();
+
+    let decoded = decode_frames(path.to_str().unwrap(), n as usize).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(decoded.len(), n as usize);
+
+    // 避开色度下采样和编码块边界造成的混色
+    let m = mask.mask(w, h);
+    let pixels = interior(&m, w, h, 8);
+    assert!(!pixels.is_empty());
+    for (i, frame) in decoded.iter().enumerate() {
+        let rgb = avframe_to_image(frame).unwrap().to_rgb8();
+        for &(x, y) in &pixels {
+            let p = rgb.get_pixel(x, y).0;
+            for (a, b) in p.iter().zip(color.iter()) {
+                assert!(
+                    (*a as i32 - *b as i32).abs() <= 6,
+                    "frame {} ({}, {}): {:?} != {:?}",
+                    i,
+                    x,
+                    y,
+                    p,
+                    color
+                );
+            }
+        }
+    }
+}
+
+fn bbox(xyxy: [f32; 4]) -> Bbox {
+    Bbox::default()
+        .with_xyxy(xyxy[0], xyxy[1], xyxy[2], xyxy[3])
+        .with_confidence(0.9)
+        .with_id(0)
+}
+
+#[test]
+fn filter_drops_detections_inside_mask() {
+    // 遮挡区域为 (25, 25), (75, 25), (50, 75) 构成的三角形
+    let mask = PrivacyMask::new(config(PrivacyMaskMethod::default()));
+    let kpts = |x: f32| vec![Keypoint::default().with_xy(x, 0.).with_confidence(1.)];
+    let y = Y::default()
+        .with_bboxes(&[
+            // 底边中点 (50, 40) 在区域内
+            bbox([40., 20., 60., 40.]),
+            // 完全在区域外
+            bbox([0., 0., 10., 10.]),
+            // 底边中点在区域外，框与区域部分相交
+            bbox([40., 60., 60., 90.]),
+        ])
+        .with_keypoints(&[kpts(1.), kpts(2.), kpts(3.)]);
+
+    let ys = mask.filter(&[y.clone(), y], &[(100, 100), (100, 100)]);
+    assert_eq!(ys.len(), 2);
+    for y in &ys {
+        let bboxes = y.bboxes().unwrap();
+        assert_eq!(bboxes.len(), 2);
+        assert_eq!(bboxes[0].xmin(), 0.);
+        assert_eq!(bboxes[1].xmin(), 40.);
+        let kpts = y.keypoints().unwrap();
+        assert_eq!((kpts[0][0].x(), kpts[1][0].x()), (2., 3.));
+    }
+
+    // 顶边中点在区域内、底边伸出区域的框只在锚点取顶边中点时被丢弃
+    let y = Y::default().with_bboxes(&[bbox([45., 30., 55., 90.]), bbox([0., 0., 10., 10.])]);
+    assert_eq!(
+        mask.filter(&[y.clone()], &[(100, 100)])[0]
+            .bboxes()
+            .unwrap()
+            .len(),
+        2
+    );
+    let mut cfg = config(PrivacyMaskMethod::default());
+    cfg.anchor = Anchor::TopCenter;
+    let mask = PrivacyMask::new(cfg);
+    let ys = mask.filter(&[y], &[(100, 100)]);
+    assert_eq!(ys[0].bboxes().unwrap().len(), 1);
+}
+
+#[test]
+fn blur_only_changes_masked_region() {
+    let mask = PrivacyMask::new(config(PrivacyMaskMethod::Blur { ksize: 15 }));
+    let x = textured(64, 48, 0);
+    let y = mask.apply_image(x.clone()).unwrap().to_rgb8();
+    let m = mask.mask(64, 48);
+
+    let changed = y
+        .pixels()
+        .zip(x.to_rgb8().pixels())
+        .zip(m.iter())
+        .filter(|((a, b), _)| a != b)
+        .map(|(_, &v)| v)
+        .collect::<Vec<_>>();
+    assert!(!changed.is_empty());
+    assert!(changed.iter().all(|&v| v > 0));
+}
+
+#[test]
+fn config_from_json() {
+    let cfg: PrivacyMaskConfig = serde_json::from_str(
+        r#"{"polygons": [[[0.1, 0.1], [0.4, 0.1], [0.4, 0.3]]], "method": {"type": "blur"}}"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.method, PrivacyMaskMethod::Blur { ksize: 51 });
+
+    let cfg: PrivacyMaskConfig =
+        serde_json::from_str(r#"{"polygons": [[[0.1, 0.1], [0.4, 0.1], [0.4, 0.3]]]}"#).unwrap();
+    assert_eq!(cfg.method, PrivacyMaskMethod::Fill { color: [0, 0, 0] });
+}
@@ -0,0 +1,137 @@
+use image::{DynamicImage, GrayImage, RgbImage};
+use usls::{Bbox, Keypoint, Mask, Y};
+use yolo_vision::redaction::{RedactMethod, RedactMode, RedactRegion, RedactionConfig, Redactor};
+
+fn config(method: RedactMethod) -> RedactionConfig {
+    RedactionConfig {
+        classes: vec!["person".to_string()],
+        class_ids: vec![],
+        method,
+        region: RedactRegion::Box,
+        mode: RedactMode::RedactThenAnnotate,
+        padding: 0.0,
+    }
+}
+
+/// 带纹理的测试图像，保证模糊/马赛克会改变像素
+fn textured(width: u32, height: u32) -> DynamicImage {
+    DynamicImage::ImageRgb8(RgbImage::from_fn(width, height, |x, y| {
+        image::Rgb([
+            (x * 37 % 256) as u8,
+            (y * 59 % 256) as u8,
+            ((x ^ y) * 13 % 256) as u8,
+        ])
+    }))
+}
+
+fn frame() -> (DynamicImage, Y) {
+    let y = Y::default().with_bboxes(&[
+        Bbox::default()
+            .with_xyxy(10.0, 10.0, 40.0, 40.0)
+            .with_confidence(0.9)
+            .with_id(0)
+            .with_name("person"),
+        Bbox::default()
+            .with_xyxy(60.0, 60.0, 90.0, 90.0)
+            .with_confidence(0.9)
+            .with_id(2)
+            .with_name("car"),
+    ]);
+    (textured(100, 100), y)
+}
+
+fn region(x: &DynamicImage, x1: u32, y1: u32, x2: u32, y2: u32) -> Vec<[u8; 3]> {
+    let rgb = x.to_rgb8();
+    (y1..y2)
+        .flat_map(|y| (x1..x2).map(move |x| (x, y)))
+        .map(|(x, y)| rgb.get_pixel(x, y).0)
+        .collect()
+}
+
+#[test]
+fn fill_only_touches_target_boxes() {
+    let (x, y) = frame();
+    let redactor = Redactor::new(config(RedactMethod::Fill { color: [1, 2, 3] }));
+    let out = redactor.redact_image(x.clone(), &y).unwrap();
+
+    assert!(region(&out, 10, 10, 40, 40).iter().all(|p| *p == [1, 2, 3]));
+    assert_eq!(region(&out, 60, 60, 90, 90), region(&x, 60, 60, 90, 90));
+    assert_eq!(region(&out, 0, 0, 10, 100), region(&x, 0, 0, 10, 100));
+}
+
+#[test]
+fn blur_and_pixelate_change_target_region() {
+    let (x, y) = frame();
+    for method in [
+        RedactMethod::Blur { ratio: 0.5 },
+        RedactMethod::Pixelate { block: 8 },
+    ] {
+        let out = Redactor::new(config(method))
+            .redact_image(x.clone(), &y)
+            .unwrap();
+        assert_ne!(region(&out, 10, 10, 40, 40), region(&x, 10, 10, 40, 40));
+        assert_eq!(region(&out, 60, 60, 90, 90), region(&x, 60, 60, 90, 90));
+    }
+}
+
+#[test]
+fn boxes_outside_frame_are_clipped() {
+    let x = textured(50, 50);
+    let y = Y::default().with_bboxes(&[Bbox::default()
+        .with_xyxy(-20.0, 30.0, 80.0, 90.0)
+        .with_id(0)
+        .with_name("person")]);
+    let redactor = Redactor::new(config(RedactMethod::Fill { color: [0, 0, 0] }));
+    let out = redactor.redact_image(x, &y).unwrap();
+    assert!(region(&out, 0, 30, 50, 50).iter().all(|p| *p == [0, 0, 0]));
+}
+
+#[test]
+fn redact_only_hides_targets_from_annotation() {
+    let (_, y) = frame();
+    let mut cfg = config(RedactMethod::Fill { color: [0, 0, 0] });
+    assert_eq!(
+        Redactor::new(cfg.clone()).visible(&[y.clone()])[0]
+            .bboxes()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    cfg.mode = RedactMode::RedactOnly;
+    let redactor = Redactor::new(cfg);
+    let visible = redactor.visible(std::slice::from_ref(&y));
+    let bboxes = visible[0].bboxes().unwrap();
+    assert_eq!(bboxes.len(), 1);
+    assert_eq!(bboxes[0].id(), 2);
+
+    // 跟踪 ID 与保留的检测框一一对应
+    let tracks = redactor.visible_tracks(&[y], &[vec![Some(7), Some(8)]]);
+    assert_eq!(tracks, vec![vec![Some(8)]]);
+}
+
+#[test]
+fn redact_only_drops_target_masks_and_keypoints() {
+    let (_, y) = frame();
+    let mask = |id: isize, name: &str| {
+        Mask::default()
+            .with_mask(GrayImage::new(100, 100))
+            .with_id(id)
+            .with_name(name)
+    };
+    let kpts = |x: f32| vec![Keypoint::default().with_xy(x, x).with_confidence(0.9)];
+    let y = y
+        .with_masks(&[mask(0, "person"), mask(2, "car")])
+        .with_keypoints(&[kpts(20.0), kpts(70.0)]);
+
+    let mut cfg = config(RedactMethod::Fill { color: [0, 0, 0] });
+    cfg.mode = RedactMode::RedactOnly;
+    let visible = Redactor::new(cfg).visible(&[y]);
+
+    let masks = visible[0].masks().unwrap();
+    assert_eq!(masks.len(), 1);
+    assert_eq!(masks[0].id(), 2);
+    let keypoints = visible[0].keypoints().unwrap();
+    assert_eq!(keypoints.len(), 1);
+    assert_eq!(keypoints[0][0].x(), 70.0);
+}
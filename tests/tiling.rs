@@ -0,0 +1,129 @@
+use image::{GrayImage, Luma};
+use usls::Keypoint;
+use yolo_vision::postprocess::{Detection, Instance};
+use yolo_vision::tiling::{tile_grid, Tile, TileConfig, TileMerge, TiledInference};
+use yolo_vision::utils::geometry::BoxF;
+
+fn covered(tiles: &[Tile], x: u32, y: u32) -> bool {
+    tiles
+        .iter()
+        .any(|t| x >= t.x && x < t.x + t.width && y >= t.y && y < t.y + t.height)
+}
+
+#[test]
+fn grid_covers_frame() {
+    let config = TileConfig::default();
+    for (w, h) in [(1920, 1080), (3840, 2160), (641, 640), (1280, 720)] {
+        let tiles = tile_grid(w, h, &config);
+        for t in &tiles {
+            assert!(t.x + t.width <= w && t.y + t.height <= h);
+            assert_eq!((t.width, t.height), (640, 640));
+        }
+        for (x, y) in [
+            (0, 0),
+            (w - 1, 0),
+            (0, h - 1),
+            (w - 1, h - 1),
+            (w / 2, h / 2),
+        ] {
+            assert!(covered(&tiles, x, y), "{}x{} misses ({}, {})", w, h, x, y);
+        }
+    }
+}
+
+#[test]
+fn grid_layout() {
+    // 1920 宽：步长 512，起点 0, 512, 1024, 1280(贴边)
+    let tiles = tile_grid(1920, 1080, &TileConfig::default());
+    let mut xs: Vec<u32> = tiles.iter().map(|t| t.x).collect();
+    xs.sort();
+    xs.dedup();
+    assert_eq!(xs, vec![0, 512, 1024, 1280]);
+    let mut ys: Vec<u32> = tiles.iter().map(|t| t.y).collect();
+    ys.sort();
+    ys.dedup();
+    assert_eq!(ys, vec![0, 440]);
+    assert_eq!(tiles.len(), 8);
+
+    // 比切片小的图像只有一个切片
+    let tiles = tile_grid(320, 240, &TileConfig::default());
+    assert_eq!(
+        tiles,
+        vec![Tile {
+            x: 0,
+            y: 0,
+            width: 320,
+            height: 240
+        }]
+    );
+}
+
+#[test]
+fn merge_across_seams() {
+    // 同一目标被切片边界截成两半，另有整帧推理给出的完整框
+    let left = Detection::new(BoxF::new(600., 100., 640., 160.), 0.8, 0);
+    let right = Detection::new(BoxF::new(512., 100., 660., 160.), 0.7, 0);
+    let other = Detection::new(BoxF::new(100., 100., 120., 120.), 0.6, 1);
+    let dets = [left, right, other];
+
+    let nmm = TiledInference::new(TileConfig::default().with_merge(TileMerge::Nmm));
+    let merged = nmm.merge(&dets);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].bbox, BoxF::new(512., 100., 660., 160.));
+    assert_eq!(merged[0].score, 0.8);
+
+    // NMS 按 IoU 判断，截断的小框与完整框 IoU 较低，二者都保留
+    let nms = TiledInference::new(TileConfig::default().with_merge(TileMerge::Nms));
+    assert_eq!(nms.merge(&dets).len(), 3);
+}
+
+fn tile_mask(width: u32, height: u32, x: u32, y: u32) -> GrayImage {
+    let mut m = GrayImage::new(width, height);
+    m.put_pixel(x, y, Luma([255]));
+    m
+}
+
+#[test]
+fn merge_carries_masks_and_keypoints() {
+    // 切片 (512, 0) 内的实例平移回整帧坐标
+    let tile = Instance::new(Detection::new(BoxF::new(0., 100., 148., 160.), 0.7, 0))
+        .with_mask(tile_mask(640, 640, 10, 120))
+        .with_keypoints(vec![Keypoint::default()
+            .with_xy(20., 130.)
+            .with_confidence(0.9)])
+        .translate(512, 0, 1920, 1080);
+    assert_eq!(tile.detection.bbox, BoxF::new(512., 100., 660., 160.));
+    let kpt = &tile.keypoints.as_ref().unwrap()[0];
+    assert_eq!((kpt.x(), kpt.y()), (532., 130.));
+    // 掩码只保留检测框范围，不分配整帧画布
+    let mask = tile.mask.as_ref().unwrap();
+    assert_eq!((mask.width, mask.height), (1920, 1080));
+    assert_eq!((mask.x, mask.y), (512, 100));
+    assert_eq!(mask.image.dimensions(), (148, 60));
+    assert_eq!(mask.get(522, 120), 255);
+    assert_eq!(mask.to_frame().get_pixel(522, 120)[0], 255);
+
+    let left = Instance::new(Detection::new(BoxF::new(600., 100., 640., 160.), 0.8, 0))
+        .with_mask(tile_mask(1920, 1080, 610, 110))
+        .with_keypoints(vec![Keypoint::default()
+            .with_xy(620., 130.)
+            .with_confidence(0.8)]);
+    let other = Instance::new(Detection::new(BoxF::new(100., 100., 120., 120.), 0.6, 1));
+
+    let nmm = TiledInference::new(TileConfig::default().with_merge(TileMerge::Nmm));
+    let merged = nmm.merge_instances(&[tile, left, other]);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].detection.bbox, BoxF::new(512., 100., 660., 160.));
+    assert_eq!(merged[0].detection.score, 0.8);
+    // 关键点来自分数最高的成员，掩码为并集
+    assert_eq!(merged[0].keypoints.as_ref().unwrap()[0].x(), 620.);
+    let mask = merged[0].mask.as_ref().unwrap();
+    assert_eq!(mask.get(522, 120), 255);
+    assert_eq!(mask.get(610, 110), 255);
+    assert!(merged[1].mask.is_none());
+
+    let nms = TiledInference::new(TileConfig::default().with_merge(TileMerge::Nms));
+    let kept = nms.merge_instances(&merged);
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[0].keypoints.as_ref().unwrap()[0].x(), 620.);
+}
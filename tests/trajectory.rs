@@ -0,0 +1,162 @@
+use image::{DynamicImage, RgbImage};
+use usls::{Bbox, Y};
+use yolo_vision::tracker::{IouTracker, TrackerConfig};
+use yolo_vision::trajectory::{
+    FrameClock, Trail, Trajectories, TrajectoryConfig, TrajectoryRecord,
+};
+use yolo_vision::utils::geometry::Anchor;
+
+fn bbox(x: f32, y: f32, id: isize) -> Bbox {
+    Bbox::default()
+        .with_xyxy(x, y, x + 20.0, y + 40.0)
+        .with_confidence(0.9)
+        .with_id(id)
+        .with_name(if id == 0 { "person" } else { "car" })
+}
+
+fn frame(boxes: &[Bbox]) -> Y {
+    Y::default().with_bboxes(boxes)
+}
+
+#[test]
+fn tracker_keeps_ids_for_moving_objects() {
+    let mut tracker = IouTracker::new(TrackerConfig::default());
+    let a = tracker.update(&frame(&[bbox(0.0, 0.0, 0), bbox(100.0, 0.0, 0)]));
+    assert_eq!(a.ids, vec![Some(1), Some(2)]);
+
+    // 顺序交换、轻微移动后仍保持 ID
+    let b = tracker.update(&frame(&[bbox(104.0, 2.0, 0), bbox(4.0, 2.0, 0)]));
+    assert_eq!(b.ids, vec![Some(2), Some(1)]);
+
+    // 不同类别不匹配
+    let c = tracker.update(&frame(&[bbox(4.0, 2.0, 2)]));
+    assert_eq!(c.ids, vec![Some(3)]);
+    assert!(c.ended.is_empty());
+}
+
+#[test]
+fn tracks_end_after_max_age() {
+    let mut tracker = IouTracker::new(TrackerConfig {
+        max_age: 2,
+        ..Default::default()
+    });
+    tracker.update(&frame(&[bbox(0.0, 0.0, 0)]));
+    assert!(tracker.update(&Y::default()).ended.is_empty());
+    assert!(tracker.update(&Y::default()).ended.is_empty());
+    let ended = tracker.update(&Y::default()).ended;
+    assert_eq!(ended.len(), 1);
+    assert_eq!(ended[0].id, 1);
+    assert!(tracker.tracks().is_empty());
+}
+
+#[test]
+fn trajectories_are_normalized_and_simplified() {
+    let config = TrajectoryConfig {
+        tracker: TrackerConfig {
+            max_age: 0,
+            ..Default::default()
+        },
+        anchor: Anchor::BottomCenter,
+        history: 3,
+        ..Default::default()
+    };
+    let mut trajectories = Trajectories::new(config, "gate-1");
+
+    // 沿直线移动 5 帧，之后消失
+    let ys: Vec<Y> = (0..5)
+        .map(|i| frame(&[bbox(10.0 * i as f32, 0.0, 0)]))
+        .collect();
+    let sizes = vec![(200, 100); 5];
+    let update = trajectories.update(&ys, &sizes, &[1000, 1040, 1080, 1120, 1160]);
+    assert_eq!(update.tracks, vec![vec![Some(1)]; 5]);
+    assert!(update.finished.is_empty());
+
+    // 每帧只保留最近 history 个点
+    assert_eq!(update.trails[0][0].points, vec![(0.05, 0.4)]);
+    assert_eq!(
+        update.trails[4][0].points,
+        vec![(0.15, 0.4), (0.2, 0.4), (0.25, 0.4)]
+    );
+
+    let update = trajectories.update(&[Y::default()], &[(200, 100)], &[2000]);
+    assert_eq!(update.finished.len(), 1);
+    let r = &update.finished[0];
+    assert_eq!((r.track_id, r.class_id), (1, 0));
+    assert_eq!(r.class_name.as_deref(), Some("person"));
+    assert_eq!(r.frames, 5);
+    assert_eq!((r.start_ms, r.end_ms), (1000, 1160));
+    // 共线点被简化掉，只剩首尾
+    assert_eq!(r.points.len(), 2);
+    assert_eq!((r.points[0].0, r.points[0].1), (0.05, 0.4));
+    assert_eq!((r.points[1].0, r.points[1].1), (0.25, 0.4));
+
+    // 不同分辨率下同一相对位置得到相同坐标
+    let mut other = Trajectories::new(TrajectoryConfig::default(), "s");
+    let update = other.update(
+        &[frame(&[Bbox::default().with_xyxy(20.0, 0.0, 60.0, 80.0)])],
+        &[(400, 200)],
+        &[0],
+    );
+    assert_eq!(update.trails[0][0].points, vec![(0.1, 0.4)]);
+}
+
+#[test]
+fn frame_clock_advances_per_frame() {
+    let mut clock = FrameClock::new(1000, 25.0);
+    assert_eq!(clock.next(3), vec![1000, 1040, 1080]);
+    assert_eq!(clock.next(2), vec![1120, 1160]);
+    assert_eq!(clock.pts(1160), std::time::Duration::from_millis(160));
+    assert_eq!(FrameClock::new(0, 30.0).next(4), vec![0, 33, 67, 100]);
+}
+
+#[test]
+fn export_and_finish() {
+    let path = std::env::temp_dir().join(format!("trajectories-{}.jsonl", std::process::id()));
+    std::fs::remove_file(&path).ok();
+    let config = TrajectoryConfig {
+        export: Some(path.to_string_lossy().to_string()),
+        ..Default::default()
+    };
+    let mut trajectories = Trajectories::new(config, "gate-1");
+    trajectories.update(
+        &[frame(&[bbox(0.0, 0.0, 0)]), frame(&[bbox(0.0, 10.0, 0)])],
+        &[(100, 100), (100, 100)],
+        &[5, 45],
+    );
+    // 单点轨迹不导出
+    trajectories.update(&[frame(&[bbox(60.0, 0.0, 2)])], &[(100, 100)], &[85]);
+
+    let records = trajectories.finish();
+    assert_eq!(records.len(), 1);
+    trajectories.export(&records).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let parsed: Vec<TrajectoryRecord> = content
+        .lines()
+        .map(|l| serde_json::from_str(l).unwrap())
+        .collect();
+    assert_eq!(parsed, records);
+    assert!(content.contains("\"points\":[[0.1,0.4,5],[0.1,0.5,45]]"));
+    std::fs::remove_file(path).ok();
+}
+
+#[test]
+fn trails_fade_towards_the_past() {
+    let trajectories = Trajectories::new(
+        TrajectoryConfig {
+            thickness: 1,
+            ..Default::default()
+        },
+        "s",
+    );
+    let trail = Trail {
+        track_id: 0,
+        points: vec![(0.0, 0.5), (0.5, 0.5), (1.0, 0.5)],
+    };
+    let x = DynamicImage::ImageRgb8(RgbImage::new(101, 11));
+    let out = trajectories.apply(vec![x], &[vec![trail]])[0].to_rgb8();
+    let old = out.get_pixel(20, 5).0;
+    let new = out.get_pixel(80, 5).0;
+    assert!(old[0] > 0 && old[0] < new[0]);
+    assert_eq!(out.get_pixel(80, 0).0, [0, 0, 0]);
+}
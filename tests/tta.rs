@@ -0,0 +1,92 @@
+use yolo_vision::postprocess::{Detection, FusionConfig, FusionMethod};
+use yolo_vision::tta::{
+    flip_box, flip_keypoints, merge, Candidate, TtaConfig, COCO_KEYPOINT_FLIP_PAIRS,
+};
+use yolo_vision::utils::geometry::BoxF;
+
+fn candidate(score: f32, xyxy: [f32; 4], keypoints: Vec<[f32; 3]>) -> Candidate {
+    Candidate {
+        detection: Detection::new(BoxF::from_xyxy(xyxy), score, 0),
+        keypoints,
+    }
+}
+
+#[test]
+fn flip_is_involution() {
+    let b = BoxF::new(10.0, 20.0, 110.0, 220.0);
+    assert_eq!(flip_box(&b, 640.0), BoxF::new(530.0, 20.0, 630.0, 220.0));
+    assert_eq!(flip_box(&flip_box(&b, 640.0), 640.0), b);
+
+    let kpts: Vec<[f32; 3]> = (0..17).map(|i| [i as f32, 2.0 * i as f32, 0.9]).collect();
+    let flipped = flip_keypoints(&kpts, 100.0, &COCO_KEYPOINT_FLIP_PAIRS);
+    // 鼻子（0）只做坐标翻转
+    assert_eq!(flipped[0], [100.0, 0.0, 0.9]);
+    // 左眼（1）与右眼（2）互换
+    assert_eq!(flipped[1], [98.0, 4.0, 0.9]);
+    assert_eq!(flipped[2], [99.0, 2.0, 0.9]);
+    assert_eq!(
+        flip_keypoints(&flipped, 100.0, &COCO_KEYPOINT_FLIP_PAIRS),
+        kpts
+    );
+}
+
+#[test]
+fn merge_boxes_and_keypoints() {
+    let original = vec![candidate(
+        0.8,
+        [0.0, 0.0, 10.0, 10.0],
+        vec![[2.0, 2.0, 1.0], [5.0, 5.0, 0.0]],
+    )];
+    let flipped = vec![candidate(
+        0.8,
+        [2.0, 0.0, 12.0, 10.0],
+        vec![[4.0, 2.0, 1.0], [7.0, 5.0, 0.5]],
+    )];
+    let merged = merge(&[original, flipped], &FusionConfig::default());
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].detection.bbox, BoxF::new(1.0, 0.0, 11.0, 10.0));
+    // 关键点按置信度加权，置信度为 0 的点不影响坐标
+    assert_eq!(merged[0].keypoints[0], [3.0, 2.0, 1.0]);
+    assert_eq!(merged[0].keypoints[1], [7.0, 5.0, 0.25]);
+}
+
+#[test]
+fn merge_keypoints_from_fused_members_only() {
+    // NMS 融合只保留分数最高的框，被抑制的候选不参与关键点平均
+    let original = vec![candidate(
+        0.9,
+        [0.0, 0.0, 10.0, 10.0],
+        vec![[2.0, 2.0, 1.0]],
+    )];
+    let flipped = vec![candidate(
+        0.6,
+        [1.0, 0.0, 11.0, 10.0],
+        vec![[8.0, 2.0, 1.0]],
+    )];
+    let config = FusionConfig {
+        method: FusionMethod::Nms,
+        ..Default::default()
+    };
+    let merged = merge(&[original, flipped], &config);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].keypoints, vec![[2.0, 2.0, 1.0]]);
+}
+
+#[test]
+fn flip_pairs_follow_keypoint_count() {
+    let config = TtaConfig::default();
+    assert_eq!(
+        config.flip_pairs_for(17).unwrap(),
+        &COCO_KEYPOINT_FLIP_PAIRS
+    );
+    assert!(config.flip_pairs_for(0).unwrap().is_empty());
+    // 非 COCO 点数的模型需要显式配置
+    assert!(config.flip_pairs_for(21).is_err());
+
+    let hand = TtaConfig {
+        flip_pairs: Some(vec![(1, 2)]),
+        ..Default::default()
+    };
+    assert_eq!(hand.flip_pairs_for(21).unwrap(), &[(1, 2)]);
+    assert!(hand.flip_pairs_for(2).is_err());
+}